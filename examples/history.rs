@@ -1,17 +1,10 @@
-use chrono::{DateTime, Local};
 use std::io;
-use std::time::SystemTime;
 use undo::{Add, At, History};
 
-fn custom_st_fmt(_: SystemTime, at: SystemTime) -> String {
-    let dt = DateTime::<Local>::from(at);
-    dt.format("%H:%M:%S").to_string()
-}
-
 fn main() -> io::Result<()> {
     let stdin = io::stdin();
     let mut target = String::new();
-    let mut history = History::<_>::builder().limit(10).capacity(10).build();
+    let mut history = History::builder().limit(10).capacity(10).build();
 
     loop {
         println!(
@@ -69,7 +62,7 @@ fn main() -> io::Result<()> {
             }
         }
 
-        println!("{}\n", history.display().set_st_fmt(&custom_st_fmt));
+        println!("{}\n", history.display().absolute_time(true));
         println!("Target: {target}");
     }
 }
@@ -21,6 +21,12 @@ impl crate::Edit for Add {
     }
 }
 
+impl crate::SizedEdit for Add {
+    fn size_hint(&self) -> usize {
+        size_of::<Self>()
+    }
+}
+
 impl Display for Add {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "Add '{}'", self.0)
@@ -0,0 +1,157 @@
+use crate::{Edit, Merged};
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::fmt::{self, Debug, Display, Formatter};
+
+/// Any [`Edit`] command.
+///
+/// This allows different edit types to be used in the same structure.
+/// Merging is forwarded to the wrapped edits if they are of the same type.
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, Any, Record};
+/// let mut target = String::new();
+/// let mut record = Record::new();
+///
+/// record.edit(&mut target, Any::new(Add('a')));
+/// record.edit(&mut target, Any::with_string(Add('b'), "Add 'b'"));
+/// assert_eq!(target, "ab");
+/// assert_eq!(record.undo_string().as_deref(), Some("Add 'b'"));
+/// ```
+pub struct Any<T, O> {
+    edit: Box<dyn DynEdit<Target = T, Output = O>>,
+    string: String,
+}
+
+impl<T, O> Any<T, O> {
+    /// Creates an `Any` from the provided edit.
+    pub fn new<E>(edit: E) -> Any<T, O>
+    where
+        E: Edit<Target = T, Output = O> + 'static,
+    {
+        Any {
+            edit: Box::new(edit),
+            string: String::new(),
+        }
+    }
+
+    /// Creates an `Any` from the provided edit and string.
+    ///
+    /// The string is used when displaying the edit.
+    pub fn with_string<E>(edit: E, string: impl Into<String>) -> Any<T, O>
+    where
+        E: Edit<Target = T, Output = O> + 'static,
+    {
+        Any {
+            edit: Box::new(edit),
+            string: string.into(),
+        }
+    }
+}
+
+impl<T, O> Edit for Any<T, O> {
+    type Target = T;
+    type Output = O;
+
+    fn edit(&mut self, target: &mut Self::Target) -> Self::Output {
+        self.edit.edit(target)
+    }
+
+    fn undo(&mut self, target: &mut Self::Target) -> Self::Output {
+        self.edit.undo(target)
+    }
+
+    fn redo(&mut self, target: &mut Self::Target) -> Self::Output {
+        self.edit.redo(target)
+    }
+
+    fn merge(&mut self, other: Self) -> Merged<Self> {
+        match self.edit.merge(other.edit) {
+            Merged::Yes => Merged::Yes,
+            Merged::No(edit) => Merged::No(Any {
+                edit,
+                string: other.string,
+            }),
+            Merged::Annul => Merged::Annul,
+        }
+    }
+}
+
+impl<T, O> Debug for Any<T, O> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Any")
+            .field("string", &self.string)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, O> Display for Any<T, O> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.string)
+    }
+}
+
+/// Object safe version of [`Edit`] that can merge with other boxed edits.
+trait DynEdit {
+    type Target;
+    type Output;
+
+    fn edit(&mut self, target: &mut Self::Target) -> Self::Output;
+
+    fn undo(&mut self, target: &mut Self::Target) -> Self::Output;
+
+    fn redo(&mut self, target: &mut Self::Target) -> Self::Output;
+
+    fn merge(
+        &mut self,
+        other: BoxedEdit<Self::Target, Self::Output>,
+    ) -> Merged<BoxedEdit<Self::Target, Self::Output>>;
+
+    fn as_any(&self) -> &dyn core::any::Any;
+
+    fn into_any(self: Box<Self>) -> Box<dyn core::any::Any>;
+}
+
+type BoxedEdit<T, O> = Box<dyn DynEdit<Target = T, Output = O>>;
+
+impl<E: Edit + 'static> DynEdit for E {
+    type Target = E::Target;
+    type Output = E::Output;
+
+    fn edit(&mut self, target: &mut E::Target) -> E::Output {
+        Edit::edit(self, target)
+    }
+
+    fn undo(&mut self, target: &mut E::Target) -> E::Output {
+        Edit::undo(self, target)
+    }
+
+    fn redo(&mut self, target: &mut E::Target) -> E::Output {
+        Edit::redo(self, target)
+    }
+
+    fn merge(
+        &mut self,
+        other: BoxedEdit<E::Target, E::Output>,
+    ) -> Merged<BoxedEdit<E::Target, E::Output>> {
+        // Edits can only be merged if they are of the same type.
+        if !other.as_any().is::<E>() {
+            return Merged::No(other);
+        }
+        let other = *other.into_any().downcast::<E>().unwrap();
+        match Edit::merge(self, other) {
+            Merged::Yes => Merged::Yes,
+            Merged::No(edit) => Merged::No(Box::new(edit)),
+            Merged::Annul => Merged::Annul,
+        }
+    }
+
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn core::any::Any> {
+        self
+    }
+}
@@ -0,0 +1,199 @@
+use core::fmt::{self, Debug, Formatter};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+#[cfg(feature = "std")]
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// A source of timestamps for the edits.
+///
+/// Every time an edit is applied, undone, or redone the structure asks its clock for the
+/// current time. The time is the duration since the Unix epoch, which can be any fixed
+/// point in time for clocks that do not know the real time.
+///
+/// When the `std` feature is enabled the structures use the [`SystemClock`] by default,
+/// otherwise all timestamps are zero unless a clock is set with
+/// [`record::Builder::clock`](crate::record::Builder::clock) or
+/// [`history::Builder::clock`](crate::history::Builder::clock).
+///
+/// # Examples
+/// ```
+/// # use core::sync::atomic::{AtomicU64, Ordering};
+/// # use core::time::Duration;
+/// # use undo::{Add, Clock, Record};
+/// struct Ticks(AtomicU64);
+///
+/// impl Clock for Ticks {
+///     fn now(&self) -> Duration {
+///         Duration::from_secs(self.0.fetch_add(1, Ordering::Relaxed))
+///     }
+/// }
+///
+/// static TICKS: Ticks = Ticks(AtomicU64::new(10));
+///
+/// let mut target = String::new();
+/// let mut record = Record::builder().clock(&TICKS).build();
+/// record.edit(&mut target, Add('a'));
+/// record.edit(&mut target, Add('b'));
+/// let times: Vec<_> = record.entries().map(|e| e.time_of_edit().as_secs()).collect();
+/// assert_eq!(times, [10, 11]);
+/// ```
+pub trait Clock: Sync {
+    /// Returns the current time as the duration since the Unix epoch.
+    fn now(&self) -> Duration;
+}
+
+/// The system clock, see [`SystemTime`].
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+}
+
+/// A clock that always moves forward, see [`Instant`].
+///
+/// The time is the system time when the clock was first used plus the time elapsed since then,
+/// so the timestamps are not affected when the system clock is changed.
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MonotonicClock;
+
+#[cfg(feature = "std")]
+impl Clock for MonotonicClock {
+    fn now(&self) -> Duration {
+        static START: OnceLock<(Instant, Duration)> = OnceLock::new();
+        let (instant, time) = START.get_or_init(|| (Instant::now(), SystemClock.now()));
+        *time + instant.elapsed()
+    }
+}
+
+/// A clock that uses the JavaScript `Date.now()`.
+///
+/// The [`SystemTime`] is not available on `wasm32-unknown-unknown`, so this clock is used
+/// instead of the [`SystemClock`] and the [`MonotonicClock`] by default when compiling to
+/// WebAssembly. Unlike the [`MonotonicClock`], this clock follows the clock of the system.
+///
+/// Requires the `wasm` feature to be enabled.
+#[cfg(feature = "wasm")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct JsClock;
+
+#[cfg(feature = "wasm")]
+impl Clock for JsClock {
+    fn now(&self) -> Duration {
+        Duration::from_secs_f64(js_sys::Date::now().max(0.0) / 1000.0)
+    }
+}
+
+/// Returns the clock that is used instead of `clock` on the platform.
+#[cfg(feature = "std")]
+const fn platform(clock: &'static dyn Clock) -> &'static dyn Clock {
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    return {
+        let _ = clock;
+        &JsClock
+    };
+    #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+    clock
+}
+
+/// How the edits are timestamped.
+///
+/// Set with [`record::Builder::timestamps`](crate::record::Builder::timestamps) or
+/// [`history::Builder::timestamps`](crate::history::Builder::timestamps).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimestampMode {
+    /// Use the [`SystemClock`], or the `JsClock` when compiling to WebAssembly with the `wasm` feature.
+    #[cfg(feature = "std")]
+    SystemTime,
+    /// Use the [`MonotonicClock`], or the `JsClock` when compiling to WebAssembly with the `wasm` feature.
+    #[cfg(feature = "std")]
+    Monotonic,
+    /// Do not timestamp the edits, so all timestamps are zero.
+    Off,
+}
+
+impl TimestampMode {
+    pub(crate) fn clock(self) -> ClockRef {
+        match self {
+            #[cfg(feature = "std")]
+            TimestampMode::SystemTime => ClockRef::new(platform(&SystemClock)),
+            #[cfg(feature = "std")]
+            TimestampMode::Monotonic => ClockRef::new(platform(&MonotonicClock)),
+            TimestampMode::Off => ClockRef(None),
+        }
+    }
+}
+
+/// The clock used by a structure, if any.
+///
+/// Without the `std` feature there is no clock by default, so all timestamps are zero.
+#[derive(Copy, Clone)]
+#[cfg_attr(not(feature = "std"), derive(Default))]
+pub(crate) struct ClockRef(Option<&'static dyn Clock>);
+
+impl ClockRef {
+    pub const fn new(clock: &'static dyn Clock) -> ClockRef {
+        ClockRef(Some(clock))
+    }
+
+    pub fn now(self) -> Duration {
+        self.0.map_or(Duration::ZERO, Clock::now)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn now_st(self) -> SystemTime {
+        UNIX_EPOCH + self.now()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for ClockRef {
+    fn default() -> Self {
+        ClockRef::new(platform(&SystemClock))
+    }
+}
+
+impl Debug for ClockRef {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Clock" } else { "None" })
+    }
+}
+
+/// Serializes a duration since the Unix epoch the same way as a [`SystemTime`].
+#[cfg(feature = "serde")]
+pub(crate) mod system_time {
+    use core::time::Duration;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "SystemTime")]
+    struct SystemTime {
+        secs_since_epoch: u64,
+        nanos_since_epoch: u32,
+    }
+
+    pub fn serialize<S: Serializer>(time: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        SystemTime {
+            secs_since_epoch: time.as_secs(),
+            nanos_since_epoch: time.subsec_nanos(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let st = SystemTime::deserialize(deserializer)?;
+        if st.nanos_since_epoch >= 1_000_000_000 {
+            return Err(D::Error::custom("nanos_since_epoch is too large"));
+        }
+        Ok(Duration::new(st.secs_since_epoch, st.nanos_since_epoch))
+    }
+}
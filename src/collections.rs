@@ -0,0 +1,631 @@
+//! Ready-made edits for common collections.
+//!
+//! Edits of the same kind are merged when they continue each other, like when typing
+//! or deleting items one at a time, so they can be undone and redone in a single step.
+
+use crate::{Edit, Merged};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::marker::PhantomData;
+use core::mem;
+use core::ops::Range;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash};
+
+/// An edit that inserts items into a [`Vec`].
+///
+/// Insertions that are next to each other are merged into a single edit.
+///
+/// # Examples
+/// ```
+/// # use undo::collections::VecInsert;
+/// # use undo::Record;
+/// let mut target = vec![1, 4];
+/// let mut record = Record::new();
+/// record.edit(&mut target, VecInsert::new(1, 2));
+/// record.edit(&mut target, VecInsert::new(2, 3));
+/// assert_eq!(target, [1, 2, 3, 4]);
+/// assert_eq!(record.len(), 1);
+///
+/// record.undo(&mut target);
+/// assert_eq!(target, [1, 4]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct VecInsert<T> {
+    index: usize,
+    len: usize,
+    items: Vec<T>,
+}
+
+impl<T> VecInsert<T> {
+    /// Returns an edit that inserts the item at the index.
+    pub fn new(index: usize, item: T) -> VecInsert<T> {
+        VecInsert::many(index, Vec::from([item]))
+    }
+
+    /// Returns an edit that inserts the items at the index, in order.
+    pub fn many(index: usize, items: Vec<T>) -> VecInsert<T> {
+        VecInsert {
+            index,
+            len: items.len(),
+            items,
+        }
+    }
+
+    /// Returns the index of the first inserted item.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<T> Edit for VecInsert<T> {
+    type Target = Vec<T>;
+    type Output = ();
+
+    fn edit(&mut self, target: &mut Vec<T>) {
+        self.len = self.items.len();
+        target.splice(self.index..self.index, mem::take(&mut self.items));
+    }
+
+    fn undo(&mut self, target: &mut Vec<T>) {
+        self.items = target.drain(self.index..self.index + self.len).collect();
+    }
+
+    fn merge(&mut self, other: Self) -> Merged<Self> {
+        if (self.index..=self.index + self.len).contains(&other.index) {
+            // The items are only stored while the edits are not applied.
+            if !other.items.is_empty() {
+                let at = other.index - self.index;
+                self.items.splice(at..at, other.items);
+            }
+            self.len += other.len;
+            Merged::Yes
+        } else {
+            Merged::No(other)
+        }
+    }
+}
+
+impl<T> Display for VecInsert<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Insert {} at {}", self.len, self.index)
+    }
+}
+
+/// An edit that removes items from a [`Vec`].
+///
+/// Removals next to each other are merged into a single edit,
+/// like when pressing backspace or delete repeatedly.
+///
+/// # Examples
+/// ```
+/// # use undo::collections::VecRemove;
+/// # use undo::Record;
+/// let mut target = vec![1, 2, 3, 4];
+/// let mut record = Record::new();
+/// record.edit(&mut target, VecRemove::new(2));
+/// record.edit(&mut target, VecRemove::new(1));
+/// assert_eq!(target, [1, 4]);
+/// assert_eq!(record.len(), 1);
+///
+/// record.undo(&mut target);
+/// assert_eq!(target, [1, 2, 3, 4]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct VecRemove<T> {
+    index: usize,
+    len: usize,
+    removed: Vec<T>,
+}
+
+impl<T> VecRemove<T> {
+    /// Returns an edit that removes the item at the index.
+    pub fn new(index: usize) -> VecRemove<T> {
+        VecRemove::range(index..index + 1)
+    }
+
+    /// Returns an edit that removes the items in the range.
+    pub fn range(range: Range<usize>) -> VecRemove<T> {
+        VecRemove {
+            index: range.start,
+            len: range.end.saturating_sub(range.start),
+            removed: Vec::new(),
+        }
+    }
+
+    /// Returns the items that were removed by the edit.
+    ///
+    /// This is empty until the edit has been applied.
+    pub fn removed(&self) -> &[T] {
+        &self.removed
+    }
+}
+
+impl<T> Edit for VecRemove<T> {
+    type Target = Vec<T>;
+    type Output = ();
+
+    fn edit(&mut self, target: &mut Vec<T>) {
+        self.removed = target.drain(self.index..self.index + self.len).collect();
+    }
+
+    fn undo(&mut self, target: &mut Vec<T>) {
+        target.splice(self.index..self.index, mem::take(&mut self.removed));
+    }
+
+    fn merge(&mut self, mut other: Self) -> Merged<Self> {
+        if other.index == self.index {
+            // Delete.
+            self.removed.append(&mut other.removed);
+            self.len += other.len;
+            Merged::Yes
+        } else if other.index + other.len == self.index {
+            // Backspace.
+            other.removed.append(&mut self.removed);
+            self.removed = other.removed;
+            self.index = other.index;
+            self.len += other.len;
+            Merged::Yes
+        } else {
+            Merged::No(other)
+        }
+    }
+}
+
+impl<T> Display for VecRemove<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Remove {} at {}", self.len, self.index)
+    }
+}
+
+/// An edit that swaps two items in a [`Vec`].
+///
+/// Swapping the same items twice in a row annuls the edits.
+///
+/// # Examples
+/// ```
+/// # use undo::collections::VecSwap;
+/// # use undo::Record;
+/// let mut target = vec![1, 2, 3];
+/// let mut record = Record::new();
+/// record.edit(&mut target, VecSwap::new(0, 2));
+/// assert_eq!(target, [3, 2, 1]);
+///
+/// record.edit(&mut target, VecSwap::new(2, 0));
+/// assert_eq!(target, [1, 2, 3]);
+/// assert!(record.is_empty());
+/// ```
+pub struct VecSwap<T> {
+    a: usize,
+    b: usize,
+    pd: PhantomData<fn(&mut T)>,
+}
+
+impl<T> VecSwap<T> {
+    /// Returns an edit that swaps the items at the indices.
+    pub const fn new(a: usize, b: usize) -> VecSwap<T> {
+        VecSwap {
+            a,
+            b,
+            pd: PhantomData,
+        }
+    }
+}
+
+impl<T> Edit for VecSwap<T> {
+    type Target = Vec<T>;
+    type Output = ();
+
+    fn edit(&mut self, target: &mut Vec<T>) {
+        target.swap(self.a, self.b);
+    }
+
+    fn undo(&mut self, target: &mut Vec<T>) {
+        target.swap(self.a, self.b);
+    }
+
+    fn merge(&mut self, other: Self) -> Merged<Self> {
+        if (self.a, self.b) == (other.a, other.b) || (self.a, self.b) == (other.b, other.a) {
+            Merged::Annul
+        } else {
+            Merged::No(other)
+        }
+    }
+}
+
+impl<T> Clone for VecSwap<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for VecSwap<T> {}
+
+impl<T> Debug for VecSwap<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("VecSwap")
+            .field("a", &self.a)
+            .field("b", &self.b)
+            .finish()
+    }
+}
+
+impl<T> Display for VecSwap<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Swap {} and {}", self.a, self.b)
+    }
+}
+
+/// A map that can be edited by [`MapInsert`] and [`MapRemove`].
+///
+/// Implemented for [`BTreeMap`], and for `HashMap` when the `std` feature is enabled.
+pub trait Map<K, V> {
+    /// Inserts the value at the key, returning the previous value.
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+
+    /// Removes the value at the key and returns it.
+    fn remove(&mut self, key: &K) -> Option<V>;
+}
+
+impl<K: Ord, V> Map<K, V> for BTreeMap<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        BTreeMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        BTreeMap::remove(self, key)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V, S: BuildHasher> Map<K, V> for HashMap<K, V, S> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        HashMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        HashMap::remove(self, key)
+    }
+}
+
+/// An edit that inserts a value into a map.
+///
+/// Insertions at the same key are merged into a single edit,
+/// which restores the value from before the first insertion when undone.
+///
+/// # Examples
+/// ```
+/// # use std::collections::HashMap;
+/// # use undo::collections::MapInsert;
+/// # use undo::Record;
+/// let mut target = HashMap::from([("a", 1)]);
+/// let mut record = Record::new();
+/// record.edit(&mut target, MapInsert::new("a", 2));
+/// record.edit(&mut target, MapInsert::new("a", 3));
+/// assert_eq!(target["a"], 3);
+/// assert_eq!(record.len(), 1);
+///
+/// record.undo(&mut target);
+/// assert_eq!(target["a"], 1);
+/// ```
+pub struct MapInsert<K, V, M = BTreeMap<K, V>> {
+    key: K,
+    value: Option<V>,
+    old: Option<V>,
+    pd: PhantomData<fn(&mut M)>,
+}
+
+impl<K, V, M> MapInsert<K, V, M> {
+    /// Returns an edit that inserts the value at the key.
+    pub fn new(key: K, value: V) -> MapInsert<K, V, M> {
+        MapInsert {
+            key,
+            value: Some(value),
+            old: None,
+            pd: PhantomData,
+        }
+    }
+
+    /// Returns the key of the edit.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+}
+
+impl<K: Clone + PartialEq, V, M: Map<K, V>> Edit for MapInsert<K, V, M> {
+    type Target = M;
+    type Output = ();
+
+    fn edit(&mut self, target: &mut M) {
+        if let Some(value) = self.value.take() {
+            self.old = target.insert(self.key.clone(), value);
+        }
+    }
+
+    fn undo(&mut self, target: &mut M) {
+        self.value = match self.old.take() {
+            Some(old) => target.insert(self.key.clone(), old),
+            None => target.remove(&self.key),
+        };
+    }
+
+    fn merge(&mut self, other: Self) -> Merged<Self> {
+        if self.key == other.key {
+            self.value = other.value;
+            Merged::Yes
+        } else {
+            Merged::No(other)
+        }
+    }
+}
+
+impl<K: Clone, V: Clone, M> Clone for MapInsert<K, V, M> {
+    fn clone(&self) -> Self {
+        MapInsert {
+            key: self.key.clone(),
+            value: self.value.clone(),
+            old: self.old.clone(),
+            pd: PhantomData,
+        }
+    }
+}
+
+impl<K: Debug, V: Debug, M> Debug for MapInsert<K, V, M> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("MapInsert")
+            .field("key", &self.key)
+            .field("value", &self.value)
+            .field("old", &self.old)
+            .finish()
+    }
+}
+
+impl<K: Debug, V, M> Display for MapInsert<K, V, M> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Insert {:?}", self.key)
+    }
+}
+
+/// An edit that removes a value from a map.
+///
+/// Removals at the same key are merged into a single edit.
+///
+/// # Examples
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use undo::collections::MapRemove;
+/// # use undo::Record;
+/// let mut target = BTreeMap::from([("a", 1), ("b", 2)]);
+/// let mut record = Record::new();
+/// record.edit(&mut target, MapRemove::new("a"));
+/// assert!(!target.contains_key("a"));
+///
+/// record.undo(&mut target);
+/// assert_eq!(target["a"], 1);
+/// ```
+pub struct MapRemove<K, V, M = BTreeMap<K, V>> {
+    key: K,
+    removed: Option<V>,
+    pd: PhantomData<fn(&mut M)>,
+}
+
+impl<K, V, M> MapRemove<K, V, M> {
+    /// Returns an edit that removes the value at the key.
+    pub fn new(key: K) -> MapRemove<K, V, M> {
+        MapRemove {
+            key,
+            removed: None,
+            pd: PhantomData,
+        }
+    }
+
+    /// Returns the key of the edit.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the value that was removed by the edit.
+    ///
+    /// This is `None` until the edit has been applied, or if there was no value at the key.
+    pub fn removed(&self) -> Option<&V> {
+        self.removed.as_ref()
+    }
+}
+
+impl<K: Clone + PartialEq, V, M: Map<K, V>> Edit for MapRemove<K, V, M> {
+    type Target = M;
+    type Output = ();
+
+    fn edit(&mut self, target: &mut M) {
+        self.removed = target.remove(&self.key);
+    }
+
+    fn undo(&mut self, target: &mut M) {
+        if let Some(removed) = self.removed.take() {
+            target.insert(self.key.clone(), removed);
+        }
+    }
+
+    fn merge(&mut self, other: Self) -> Merged<Self> {
+        // Removing the same key again does nothing.
+        if self.key == other.key {
+            Merged::Yes
+        } else {
+            Merged::No(other)
+        }
+    }
+}
+
+impl<K: Clone, V: Clone, M> Clone for MapRemove<K, V, M> {
+    fn clone(&self) -> Self {
+        MapRemove {
+            key: self.key.clone(),
+            removed: self.removed.clone(),
+            pd: PhantomData,
+        }
+    }
+}
+
+impl<K: Debug, V: Debug, M> Debug for MapRemove<K, V, M> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("MapRemove")
+            .field("key", &self.key)
+            .field("removed", &self.removed)
+            .finish()
+    }
+}
+
+impl<K: Debug, V, M> Display for MapRemove<K, V, M> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Remove {:?}", self.key)
+    }
+}
+
+/// An edit that inserts text into a [`String`].
+///
+/// Insertions that continue each other are merged into a single edit, like when typing.
+///
+/// # Examples
+/// ```
+/// # use undo::collections::StringInsert;
+/// # use undo::Record;
+/// let mut target = String::new();
+/// let mut record = Record::new();
+/// record.edit(&mut target, StringInsert::new(0, "ab"));
+/// record.edit(&mut target, StringInsert::new(2, "c"));
+/// assert_eq!(target, "abc");
+/// assert_eq!(record.len(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct StringInsert {
+    at: usize,
+    text: String,
+}
+
+impl StringInsert {
+    /// Returns an edit that inserts the text at the byte position.
+    pub fn new(at: usize, text: impl Into<String>) -> StringInsert {
+        StringInsert {
+            at,
+            text: text.into(),
+        }
+    }
+
+    /// Returns the inserted text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Edit for StringInsert {
+    type Target = String;
+    type Output = ();
+
+    fn edit(&mut self, target: &mut String) {
+        target.insert_str(self.at, &self.text);
+    }
+
+    fn undo(&mut self, target: &mut String) {
+        target.drain(self.at..self.at + self.text.len());
+    }
+
+    fn merge(&mut self, other: Self) -> Merged<Self> {
+        if other.at == self.at + self.text.len() {
+            self.text.push_str(&other.text);
+            Merged::Yes
+        } else {
+            Merged::No(other)
+        }
+    }
+}
+
+impl Display for StringInsert {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Insert {:?}", self.text)
+    }
+}
+
+/// An edit that removes text from a [`String`].
+///
+/// Removals next to each other are merged into a single edit,
+/// like when pressing backspace or delete repeatedly.
+///
+/// # Examples
+/// ```
+/// # use undo::collections::StringRemove;
+/// # use undo::Record;
+/// let mut target = String::from("abc");
+/// let mut record = Record::new();
+/// record.edit(&mut target, StringRemove::new(2..3));
+/// record.edit(&mut target, StringRemove::new(1..2));
+/// assert_eq!(target, "a");
+/// assert_eq!(record.len(), 1);
+///
+/// record.undo(&mut target);
+/// assert_eq!(target, "abc");
+/// ```
+#[derive(Clone, Debug)]
+pub struct StringRemove {
+    at: usize,
+    len: usize,
+    removed: String,
+}
+
+impl StringRemove {
+    /// Returns an edit that removes the text in the byte range.
+    pub fn new(range: Range<usize>) -> StringRemove {
+        StringRemove {
+            at: range.start,
+            len: range.end.saturating_sub(range.start),
+            removed: String::new(),
+        }
+    }
+
+    /// Returns the text that was removed by the edit.
+    ///
+    /// This is empty until the edit has been applied.
+    pub fn removed(&self) -> &str {
+        &self.removed
+    }
+}
+
+impl Edit for StringRemove {
+    type Target = String;
+    type Output = ();
+
+    fn edit(&mut self, target: &mut String) {
+        self.removed = target.drain(self.at..self.at + self.len).collect();
+    }
+
+    fn undo(&mut self, target: &mut String) {
+        target.insert_str(self.at, &self.removed);
+    }
+
+    fn merge(&mut self, mut other: Self) -> Merged<Self> {
+        if other.at == self.at {
+            // Delete.
+            self.removed.push_str(&other.removed);
+            self.len += other.len;
+            Merged::Yes
+        } else if other.at + other.len == self.at {
+            // Backspace.
+            other.removed.push_str(&self.removed);
+            self.removed = other.removed;
+            self.at = other.at;
+            self.len += other.len;
+            Merged::Yes
+        } else {
+            Merged::No(other)
+        }
+    }
+}
+
+impl Display for StringRemove {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Remove {:?}", self.removed)
+    }
+}
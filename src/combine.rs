@@ -0,0 +1,75 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Combines the outputs of the edits that are applied when going to another position.
+///
+/// Methods like [`Record::go_to_with`](crate::Record::go_to_with) can use a combiner
+/// instead of collecting the outputs in a [`Vec`], which avoids the allocation
+/// when the outputs are not needed.
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, Ignore, Last, Record};
+/// let mut target = String::new();
+/// let mut record = Record::new();
+/// record.edit(&mut target, Add('a'));
+/// record.edit(&mut target, Add('b'));
+///
+/// let Ignore = record.go_to_with(&mut target, 0);
+/// assert_eq!(target, "");
+/// let last: Last<()> = record.go_to_with(&mut target, 2);
+/// assert_eq!(last.into_inner(), Some(()));
+/// ```
+pub trait OutputCombiner<O>: Default {
+    /// Adds the output of an edit.
+    fn push(&mut self, output: O);
+
+    /// Reserves capacity for at least `additional` more outputs.
+    ///
+    /// Does nothing by default.
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<O> OutputCombiner<O> for Vec<O> {
+    fn push(&mut self, output: O) {
+        Vec::push(self, output);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+}
+
+/// An [`OutputCombiner`] that drops the outputs.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ignore;
+
+impl<O> OutputCombiner<O> for Ignore {
+    fn push(&mut self, _: O) {}
+}
+
+/// An [`OutputCombiner`] that only keeps the output of the last edit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Last<O>(Option<O>);
+
+impl<O> Last<O> {
+    /// Returns the output of the last edit, or `None` if no edits were applied.
+    pub fn into_inner(self) -> Option<O> {
+        self.0
+    }
+}
+
+impl<O> Default for Last<O> {
+    fn default() -> Self {
+        Last(None)
+    }
+}
+
+impl<O> OutputCombiner<O> for Last<O> {
+    fn push(&mut self, output: O) {
+        self.0 = Some(output);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{Edit, Merged, SizedEdit};
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::marker::PhantomData;
+use flate2::read::{DeflateDecoder, DeflateEncoder};
+use flate2::Compression;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::Read;
+
+/// Compresses and decompresses the serialized edits.
+///
+/// Used by [`Compressed`] to store edits that are no longer close to the head.
+pub trait Compressor {
+    /// Returns the compressed bytes.
+    fn compress(bytes: &[u8]) -> Vec<u8>;
+
+    /// Returns the decompressed bytes.
+    ///
+    /// The bytes are always the output of [`Compressor::compress`].
+    fn decompress(bytes: &[u8]) -> Vec<u8>;
+}
+
+/// The default compressor, using the deflate algorithm from [`flate2`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Deflate;
+
+impl Compressor for Deflate {
+    fn compress(bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        DeflateEncoder::new(bytes, Compression::default())
+            .read_to_end(&mut out)
+            .expect("compressing from memory should not fail");
+        out
+    }
+
+    fn decompress(bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        DeflateDecoder::new(bytes)
+            .read_to_end(&mut out)
+            .expect("the bytes should be compressed by the same compressor");
+        out
+    }
+}
+
+/// An edit that can be stored serialized and compressed.
+///
+/// The edit is decompressed when it is undone or redone, so compression is
+/// transparent to the target. Use [`record::Builder::compress_after`](crate::record::Builder::compress_after)
+/// or [`history::Builder::compress_after`](crate::history::Builder::compress_after)
+/// to compress the entries that are far enough behind the head.
+///
+/// Requires the `compress` feature to be enabled.
+///
+/// # Examples
+/// ```
+/// # use undo::{Compressed, Edit, Record};
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Push(char);
+///
+/// impl Edit for Push {
+///     type Target = String;
+///     type Output = ();
+///
+///     fn edit(&mut self, target: &mut String) {
+///         target.push(self.0);
+///     }
+///
+///     fn undo(&mut self, target: &mut String) {
+///         self.0 = target.pop().unwrap();
+///     }
+/// }
+///
+/// let mut target = String::new();
+/// let mut record: Record<Compressed<Push>> = Record::builder().compress_after(1).build();
+/// record.edit(&mut target, Compressed::new(Push('a')));
+/// record.edit(&mut target, Compressed::new(Push('b')));
+/// record.edit(&mut target, Compressed::new(Push('c')));
+/// assert!(record.get_entry(0).unwrap().as_ref().is_compressed());
+/// assert!(!record.get_entry(2).unwrap().as_ref().is_compressed());
+///
+/// record.undo(&mut target);
+/// record.undo(&mut target);
+/// record.undo(&mut target);
+/// assert_eq!(target, "");
+/// ```
+pub struct Compressed<E, C = Deflate> {
+    state: State<E>,
+    pd: PhantomData<fn() -> C>,
+}
+
+enum State<E> {
+    Edit(E),
+    Bytes(Vec<u8>),
+}
+
+impl<E, C> Compressed<E, C> {
+    /// Returns a new uncompressed edit.
+    pub const fn new(edit: E) -> Compressed<E, C> {
+        Compressed {
+            state: State::Edit(edit),
+            pd: PhantomData,
+        }
+    }
+
+    /// Returns `true` if the edit is compressed.
+    pub fn is_compressed(&self) -> bool {
+        matches!(self.state, State::Bytes(_))
+    }
+}
+
+impl<E: Serialize + DeserializeOwned, C: Compressor> Compressed<E, C> {
+    /// Serializes and compresses the edit if it is not already compressed.
+    pub fn compress(&mut self) {
+        if let State::Edit(edit) = &self.state {
+            let bytes = bincode::serialize(edit).expect("the edit should be serializable");
+            self.state = State::Bytes(C::compress(&bytes));
+        }
+    }
+
+    /// Decompresses and deserializes the edit if it is compressed,
+    /// returning a mutable reference to it.
+    pub fn decompress(&mut self) -> &mut E {
+        if let State::Bytes(bytes) = &self.state {
+            self.state = State::Edit(decode::<E, C>(bytes));
+        }
+        match &mut self.state {
+            State::Edit(edit) => edit,
+            State::Bytes(_) => unreachable!(),
+        }
+    }
+
+    /// Returns the edit, decompressing it if needed.
+    pub fn into_inner(mut self) -> E {
+        self.decompress();
+        match self.state {
+            State::Edit(edit) => edit,
+            State::Bytes(_) => unreachable!(),
+        }
+    }
+}
+
+fn decode<E: DeserializeOwned, C: Compressor>(bytes: &[u8]) -> E {
+    let bytes = C::decompress(bytes);
+    bincode::deserialize(&bytes).expect("the edit should be deserializable")
+}
+
+impl<E, C> Edit for Compressed<E, C>
+where
+    E: Edit + Serialize + DeserializeOwned,
+    C: Compressor,
+{
+    type Target = E::Target;
+    type Output = E::Output;
+
+    fn edit(&mut self, target: &mut E::Target) -> E::Output {
+        self.decompress().edit(target)
+    }
+
+    fn undo(&mut self, target: &mut E::Target) -> E::Output {
+        self.decompress().undo(target)
+    }
+
+    fn redo(&mut self, target: &mut E::Target) -> E::Output {
+        self.decompress().redo(target)
+    }
+
+    fn merge(&mut self, other: Self) -> Merged<Self> {
+        match self.decompress().merge(other.into_inner()) {
+            Merged::Yes => Merged::Yes,
+            Merged::No(edit) => Merged::No(Compressed::new(edit)),
+            Merged::Annul => Merged::Annul,
+        }
+    }
+}
+
+impl<E: SizedEdit, C> SizedEdit for Compressed<E, C> {
+    fn size_hint(&self) -> usize {
+        match &self.state {
+            State::Edit(edit) => edit.size_hint(),
+            State::Bytes(bytes) => size_of::<Self>() + bytes.capacity(),
+        }
+    }
+}
+
+impl<E: Clone, C> Clone for Compressed<E, C> {
+    fn clone(&self) -> Self {
+        let state = match &self.state {
+            State::Edit(edit) => State::Edit(edit.clone()),
+            State::Bytes(bytes) => State::Bytes(bytes.clone()),
+        };
+        Compressed {
+            state,
+            pd: PhantomData,
+        }
+    }
+}
+
+impl<E: Debug, C> Debug for Compressed<E, C> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match &self.state {
+            State::Edit(edit) => f.debug_tuple("Compressed").field(edit).finish(),
+            State::Bytes(bytes) => f
+                .debug_struct("Compressed")
+                .field("len", &bytes.len())
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+impl<E, C> Display for Compressed<E, C>
+where
+    E: Display + DeserializeOwned,
+    C: Compressor,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match &self.state {
+            State::Edit(edit) => Display::fmt(edit, f),
+            State::Bytes(bytes) => Display::fmt(&decode::<E, C>(bytes), f),
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use crate::Edit;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// An [`Edit`] command made from a function that stores a diff of the change.
+///
+/// Works the same as [`FromFn`](crate::FromFn), but instead of storing a clone of the
+/// target only the difference between the old and new target is stored.
+/// The target is still cloned while the diff is computed.
+///
+/// # Examples
+/// ```
+/// # use undo::{Diff, Record};
+/// let mut target = String::from("hello world");
+/// let mut record = Record::new();
+///
+/// let insert = |i, s| Diff::new(move |t: &mut String| t.insert_str(i, s));
+///
+/// record.edit(&mut target, insert(5, ","));
+/// record.edit(&mut target, insert(12, "!"));
+/// assert_eq!(target, "hello, world!");
+///
+/// record.undo(&mut target);
+/// record.undo(&mut target);
+/// assert_eq!(target, "hello world");
+/// record.redo(&mut target);
+/// assert_eq!(target, "hello, world");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Diff<F, T: Differ> {
+    f: F,
+    diff: Option<T::Diff>,
+}
+
+impl<F, T: Differ> Diff<F, T> {
+    /// Creates a new `Diff` from `f`.
+    pub const fn new(f: F) -> Self {
+        Diff { f, diff: None }
+    }
+}
+
+impl<F, T> Edit for Diff<F, T>
+where
+    F: FnMut(&mut T),
+    T: Clone + Differ,
+{
+    type Target = T;
+    type Output = ();
+
+    fn edit(&mut self, target: &mut Self::Target) -> Self::Output {
+        let old = target.clone();
+        (self.f)(target);
+        self.diff = Some(T::diff(&old, target));
+    }
+
+    fn undo(&mut self, target: &mut Self::Target) -> Self::Output {
+        if let Some(diff) = &self.diff {
+            target.revert(diff);
+        }
+    }
+
+    fn redo(&mut self, target: &mut Self::Target) -> Self::Output {
+        if let Some(diff) = &self.diff {
+            target.apply(diff);
+        }
+    }
+}
+
+/// Computes and applies the difference between two values.
+///
+/// Used by [`Diff`] to store changes to the target.
+/// Implemented for [`String`] and [`Vec`] using Myers' diff algorithm.
+pub trait Differ {
+    /// The difference between two values.
+    type Diff;
+
+    /// Returns the difference that changes `old` into `new`.
+    fn diff(old: &Self, new: &Self) -> Self::Diff;
+
+    /// Changes the value from the old value into the new value.
+    fn apply(&mut self, diff: &Self::Diff);
+
+    /// Changes the value from the new value back into the old value.
+    fn revert(&mut self, diff: &Self::Diff);
+}
+
+/// A change between two sequences.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change<S> {
+    old: usize,
+    new: usize,
+    removed: S,
+    inserted: S,
+}
+
+impl<T: Clone + PartialEq> Differ for Vec<T> {
+    type Diff = Vec<Change<Vec<T>>>;
+
+    fn diff(old: &Self, new: &Self) -> Self::Diff {
+        myers(old, new)
+            .into_iter()
+            .map(|(o, n)| Change {
+                old: o.start,
+                new: n.start,
+                removed: old[o].to_vec(),
+                inserted: new[n].to_vec(),
+            })
+            .collect()
+    }
+
+    fn apply(&mut self, diff: &Self::Diff) {
+        for change in diff.iter().rev() {
+            let range = change.old..change.old + change.removed.len();
+            self.splice(range, change.inserted.iter().cloned());
+        }
+    }
+
+    fn revert(&mut self, diff: &Self::Diff) {
+        for change in diff.iter().rev() {
+            let range = change.new..change.new + change.inserted.len();
+            self.splice(range, change.removed.iter().cloned());
+        }
+    }
+}
+
+impl Differ for String {
+    type Diff = Vec<Change<String>>;
+
+    fn diff(old: &Self, new: &Self) -> Self::Diff {
+        let mut changes: Vec<(Range<usize>, Range<usize>)> = Vec::new();
+        for (mut o, mut n) in myers(old.as_bytes(), new.as_bytes()) {
+            // The bytes around a change are equal in both strings,
+            // so the change can be grown until it is on char boundaries.
+            while !old.is_char_boundary(o.start) || !new.is_char_boundary(n.start) {
+                o.start -= 1;
+                n.start -= 1;
+            }
+            while !old.is_char_boundary(o.end) || !new.is_char_boundary(n.end) {
+                o.end += 1;
+                n.end += 1;
+            }
+            match changes.last_mut() {
+                Some((last_o, last_n)) if last_o.end >= o.start => {
+                    last_o.end = o.end;
+                    last_n.end = n.end;
+                }
+                _ => changes.push((o, n)),
+            }
+        }
+        changes
+            .into_iter()
+            .map(|(o, n)| Change {
+                old: o.start,
+                new: n.start,
+                removed: String::from(&old[o]),
+                inserted: String::from(&new[n]),
+            })
+            .collect()
+    }
+
+    fn apply(&mut self, diff: &Self::Diff) {
+        for change in diff.iter().rev() {
+            let range = change.old..change.old + change.removed.len();
+            self.replace_range(range, &change.inserted);
+        }
+    }
+
+    fn revert(&mut self, diff: &Self::Diff) {
+        for change in diff.iter().rev() {
+            let range = change.new..change.new + change.inserted.len();
+            self.replace_range(range, &change.removed);
+        }
+    }
+}
+
+/// Returns the ranges in `old` and `new` that differ, using Myers' diff algorithm.
+fn myers<T: PartialEq>(old: &[T], new: &[T]) -> Vec<(Range<usize>, Range<usize>)> {
+    // Skip the common prefix and suffix since they are often large.
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = a.len() + b.len();
+    let at = |k: isize| (k + max as isize) as usize;
+
+    // Find the shortest edit script, storing the furthest reaching paths for each step.
+    let mut v = vec![0; 2 * max + 2];
+    let mut trace = Vec::new();
+    'search: for d in 0..=max as isize {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // Walk the paths backwards and collect the positions that differ.
+    let (mut x, mut y) = (n, m);
+    let mut changed = Vec::new();
+    for (d, v) in trace.iter().enumerate().skip(1).rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[at(prev_k)];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+        }
+        changed.push((prev_x as usize, prev_y as usize, x == prev_x));
+        x = prev_x;
+        y = prev_y;
+    }
+    changed.reverse();
+
+    // Group the insertions and deletions into ranges.
+    let mut ranges: Vec<(Range<usize>, Range<usize>)> = Vec::new();
+    for (x, y, is_insert) in changed {
+        let (x, y) = (x + prefix, y + prefix);
+        let (o, n) = if is_insert {
+            (x..x, y..y + 1)
+        } else {
+            (x..x + 1, y..y)
+        };
+        match ranges.last_mut() {
+            Some((last_o, last_n)) if last_o.end == o.start && last_n.end == n.start => {
+                last_o.end = o.end;
+                last_n.end = n.end;
+            }
+            _ => ranges.push((o, n)),
+        }
+    }
+    ranges
+}
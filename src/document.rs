@@ -0,0 +1,184 @@
+use crate::socket::Slot;
+use crate::{At, Edit, History};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+use core::num::NonZeroUsize;
+use std::io;
+
+/// A document that owns its target and a [`History`], and saves the target with a callback.
+///
+/// The document is dirty when the target is not in the saved state of the history.
+/// [`Document::save`] calls the callback with the target and marks the current state as saved.
+/// The document can also save itself after a number of changes, see [`Document::set_autosave`].
+///
+/// # Examples
+/// ```
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// # use undo::{Add, Document};
+/// let disk = Rc::new(RefCell::new(String::new()));
+/// let file = disk.clone();
+/// let mut document = Document::new(String::new(), move |target: &String| {
+///     file.replace(target.clone());
+///     Ok(())
+/// });
+///
+/// document.edit(Add('a'));
+/// assert!(document.is_dirty());
+/// document.save().unwrap();
+/// assert!(!document.is_dirty());
+/// assert_eq!(*disk.borrow(), "a");
+///
+/// document.edit(Add('b'));
+/// document.revert();
+/// assert_eq!(document.target(), "a");
+/// ```
+pub struct Document<T, E, S = ()> {
+    target: T,
+    history: History<E, S>,
+    persist: Persist<T>,
+    autosave: Option<NonZeroUsize>,
+    changes: usize,
+    error: Option<io::Error>,
+}
+
+/// The callback that saves the target.
+type Persist<T> = Box<dyn FnMut(&T) -> io::Result<()>>;
+
+impl<T, E> Document<T, E> {
+    /// Returns a new document with the target, that is saved with `persist`.
+    pub fn new(target: T, persist: impl FnMut(&T) -> io::Result<()> + 'static) -> Document<T, E> {
+        Document::from_parts(target, History::new(), persist)
+    }
+}
+
+impl<T, E, S> Document<T, E, S> {
+    /// Returns a document from the target, the history, and the callback that saves the target.
+    ///
+    /// The history should be in the state that the target is in.
+    pub fn from_parts(
+        target: T,
+        history: History<E, S>,
+        persist: impl FnMut(&T) -> io::Result<()> + 'static,
+    ) -> Document<T, E, S> {
+        Document {
+            target,
+            history,
+            persist: Box::new(persist),
+            autosave: None,
+            changes: 0,
+            error: None,
+        }
+    }
+
+    /// Returns the target and the history.
+    pub fn into_parts(self) -> (T, History<E, S>) {
+        (self.target, self.history)
+    }
+
+    /// Returns a reference to the target.
+    pub fn target(&self) -> &T {
+        &self.target
+    }
+
+    /// Returns a reference to the history.
+    pub fn history(&self) -> &History<E, S> {
+        &self.history
+    }
+
+    /// Returns a mutable reference to the history.
+    pub fn history_mut(&mut self) -> &mut History<E, S> {
+        &mut self.history
+    }
+
+    /// Returns `true` if the target has changed since it was saved.
+    pub fn is_dirty(&self) -> bool {
+        !self.history.is_saved()
+    }
+
+    /// Sets the number of changes after which the document is saved automatically.
+    ///
+    /// Edits, undos, redos, and going to another position each count as one change. If the automatic save fails
+    /// the document stays dirty, and the error can be taken with [`Document::take_autosave_error`].
+    /// Automatic saving is off by default.
+    pub fn set_autosave(&mut self, changes: Option<NonZeroUsize>) {
+        self.autosave = changes;
+    }
+
+    /// Returns the error of the last automatic save that failed, if any.
+    pub fn take_autosave_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+impl<T, E, S: Slot> Document<T, E, S> {
+    /// Saves the target with the callback and marks the current state as saved.
+    ///
+    /// The saved state is not changed if the callback fails.
+    pub fn save(&mut self) -> io::Result<()> {
+        (self.persist)(&self.target)?;
+        self.history.set_saved();
+        self.changes = 0;
+        Ok(())
+    }
+
+    /// Counts a change and saves the document if the autosave limit is reached.
+    fn changed(&mut self) {
+        self.changes += 1;
+        if self.autosave.is_some_and(|n| self.changes >= n.get()) && self.is_dirty() {
+            self.error = self.save().err();
+        }
+    }
+}
+
+impl<T, E: Edit<Target = T>, S: Slot> Document<T, E, S> {
+    /// Pushes the edit to the history and executes its [`Edit::edit`] method.
+    pub fn edit(&mut self, edit: E) -> E::Output {
+        let output = self.history.edit(&mut self.target, edit);
+        self.changed();
+        output
+    }
+
+    /// Calls the [`Edit::undo`] method for the active edit
+    /// and sets the previous one as the new active one.
+    pub fn undo(&mut self) -> Option<E::Output> {
+        let output = self.history.undo(&mut self.target)?;
+        self.changed();
+        Some(output)
+    }
+
+    /// Calls the [`Edit::redo`] method for the active edit
+    /// and sets the next one as the new active one.
+    pub fn redo(&mut self) -> Option<E::Output> {
+        let output = self.history.redo(&mut self.target)?;
+        self.changed();
+        Some(output)
+    }
+
+    /// Repeatedly calls [`Edit::undo`] or [`Edit::redo`] until the position `at` is reached.
+    pub fn go_to(&mut self, at: At) -> Vec<E::Output> {
+        let outputs = self.history.go_to(&mut self.target, at);
+        if !outputs.is_empty() {
+            self.changed();
+        }
+        outputs
+    }
+
+    /// Reverts the changes done to the target since it was saved.
+    pub fn revert(&mut self) -> Vec<E::Output> {
+        let outputs = self.history.revert(&mut self.target);
+        self.changes = 0;
+        outputs
+    }
+}
+
+impl<T: Debug, E: Debug, S: Debug> Debug for Document<T, E, S> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Document")
+            .field("target", &self.target)
+            .field("history", &self.history)
+            .field("autosave", &self.autosave)
+            .finish_non_exhaustive()
+    }
+}
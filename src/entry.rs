@@ -1,21 +1,43 @@
-use crate::{Edit, Merged};
+#[cfg(feature = "serde")]
+use crate::schema::EntryDe;
+#[cfg(feature = "async")]
+use crate::AsyncEdit;
+use crate::{At, Edit, EditRef, EditWithCtx, Merged};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
 use core::fmt::{self, Debug, Display, Formatter};
+use core::time::Duration;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "std")]
-use std::time::SystemTime;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Wrapper around an [`Edit`] command that contains additional metadata.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
 pub struct Entry<E> {
     edit: E,
-    #[cfg(feature = "std")]
-    st_edit: SystemTime,
-    #[cfg(feature = "std")]
-    st_undo: SystemTime,
-    #[cfg(feature = "std")]
-    st_redo: SystemTime,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) label: Option<At>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) meta: BTreeMap<String, String>,
+    /// The display text of the group if this entry starts a group.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) group: Option<String>,
+    /// If this entry is in the same group as the previous entry.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) joined: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) seq: usize,
+    /// The label of the barrier after this entry, if there is one.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) barrier: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::clock::system_time"))]
+    st_edit: Duration,
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::clock::system_time"))]
+    st_undo: Duration,
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::clock::system_time"))]
+    st_redo: Duration,
 }
 
 impl<E> AsRef<E> for Entry<E> {
@@ -24,23 +46,123 @@ impl<E> AsRef<E> for Entry<E> {
     }
 }
 
+impl<E> AsMut<E> for Entry<E> {
+    fn as_mut(&mut self) -> &mut E {
+        &mut self.edit
+    }
+}
+
 impl<E> Entry<E> {
-    pub(crate) const fn new(edit: E) -> Self {
+    /// Returns a new entry for the edit.
+    ///
+    /// The entry can be added to a structure without executing the edit with
+    /// [`Record::push_unexecuted`](crate::Record::push_unexecuted) or
+    /// [`History::push_unexecuted`](crate::History::push_unexecuted).
+    pub const fn new(edit: E) -> Self {
         Entry {
             edit,
-            #[cfg(feature = "std")]
-            st_edit: SystemTime::UNIX_EPOCH,
-            #[cfg(feature = "std")]
-            st_undo: SystemTime::UNIX_EPOCH,
-            #[cfg(feature = "std")]
-            st_redo: SystemTime::UNIX_EPOCH,
+            label: None,
+            meta: BTreeMap::new(),
+            group: None,
+            joined: false,
+            seq: 0,
+            barrier: None,
+            st_edit: Duration::ZERO,
+            st_undo: Duration::ZERO,
+            st_redo: Duration::ZERO,
         }
     }
 
+    /// Sets the time the edit method was called.
+    pub fn with_time_of_edit(mut self, time: Duration) -> Self {
+        self.st_edit = time;
+        self
+    }
+
+    /// Adds the metadata key-value pair to the entry.
+    pub fn with_meta(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.meta.insert(key.into(), value.into());
+        self
+    }
+
+    /// Returns the sequence number of the entry.
+    ///
+    /// The entries are numbered from `1` in the order they are pushed, and the number
+    /// of an entry does not change when entries before it are removed because of the limit.
+    /// Returns `0` if the entry has not been pushed yet.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::builder().limit(2).build();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// record.edit(&mut target, Add('c'));
+    /// let seqs: Vec<_> = record.entries().map(|e| e.seq()).collect();
+    /// assert_eq!(seqs, [2, 3]);
+    /// ```
+    pub fn seq(&self) -> usize {
+        self.seq
+    }
+
+    /// Returns the label of the barrier after the entry, if there is one.
+    ///
+    /// See [`Record::push_barrier`](crate::Record::push_barrier) for more information.
+    pub fn barrier(&self) -> Option<&str> {
+        self.barrier.as_deref()
+    }
+
+    /// Returns the metadata value for the `key`.
+    pub fn get_meta(&self, key: &str) -> Option<&str> {
+        self.meta.get(key).map(String::as_str)
+    }
+
+    /// Returns an iterator over the metadata key-value pairs.
+    ///
+    /// The metadata is set by [`Record::edit_with_meta`](crate::Record::edit_with_meta)
+    /// or [`History::edit_with_meta`](crate::History::edit_with_meta).
+    pub fn meta(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.meta.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Returns the time the edit method was called, as given by the [`Clock`](crate::Clock).
+    pub fn time_of_edit(&self) -> Duration {
+        self.st_edit
+    }
+
+    /// Returns the last time the undo method was called, as given by the [`Clock`](crate::Clock).
+    ///
+    /// Returns zero if it has never been called.
+    pub fn time_of_undo(&self) -> Duration {
+        self.st_undo
+    }
+
+    /// Returns the last time the redo method was called, as given by the [`Clock`](crate::Clock).
+    ///
+    /// Returns zero if it has never been called.
+    pub fn time_of_redo(&self) -> Duration {
+        self.st_redo
+    }
+
+    /// Returns the largest of the edit, undo, and redo times.
+    pub fn time_of_latest(&self) -> Duration {
+        self.st_edit.max(self.st_undo).max(self.st_redo)
+    }
+
     /// Returns the time the edit method was called.
     #[cfg(feature = "std")]
     pub fn st_of_edit(&self) -> SystemTime {
-        self.st_edit
+        UNIX_EPOCH + self.st_edit
+    }
+
+    /// Sets the time the edit method was called.
+    ///
+    /// This can be used to restore the timestamps of entries that are loaded from elsewhere.
+    /// Times before the [`UNIX_EPOCH`](SystemTime::UNIX_EPOCH) are set to the epoch.
+    #[cfg(feature = "std")]
+    pub fn set_st_of_edit(&mut self, st: SystemTime) {
+        self.st_edit = st.duration_since(UNIX_EPOCH).unwrap_or_default();
     }
 
     /// Returns the last time the undo method was called.
@@ -48,7 +170,7 @@ impl<E> Entry<E> {
     /// Returns [`UNIX_EPOCH`](SystemTime::UNIX_EPOCH) if it has never been called.
     #[cfg(feature = "std")]
     pub fn st_of_undo(&self) -> SystemTime {
-        self.st_undo
+        UNIX_EPOCH + self.st_undo
     }
 
     /// Returns the last time the redo method was called.
@@ -56,38 +178,29 @@ impl<E> Entry<E> {
     /// Returns [`UNIX_EPOCH`](SystemTime::UNIX_EPOCH) if it has never been called.
     #[cfg(feature = "std")]
     pub fn st_of_redo(&self) -> SystemTime {
-        self.st_redo
+        UNIX_EPOCH + self.st_redo
     }
 
     /// Returns the largest of the edit, undo, and redo times.
     #[cfg(feature = "std")]
     pub fn st_of_latest(&self) -> SystemTime {
-        self.st_edit.max(self.st_undo).max(self.st_redo)
+        UNIX_EPOCH + self.time_of_latest()
     }
 }
 
 impl<E: Edit> Entry<E> {
-    pub(crate) fn edit(&mut self, target: &mut E::Target) -> E::Output {
-        #[cfg(feature = "std")]
-        {
-            self.st_edit = SystemTime::now();
-        }
+    pub(crate) fn edit(&mut self, target: &mut E::Target, now: Duration) -> E::Output {
+        self.st_edit = now;
         self.edit.edit(target)
     }
 
-    pub(crate) fn undo(&mut self, target: &mut E::Target) -> E::Output {
-        #[cfg(feature = "std")]
-        {
-            self.st_undo = SystemTime::now();
-        }
+    pub(crate) fn undo(&mut self, target: &mut E::Target, now: Duration) -> E::Output {
+        self.st_undo = now;
         self.edit.undo(target)
     }
 
-    pub(crate) fn redo(&mut self, target: &mut E::Target) -> E::Output {
-        #[cfg(feature = "std")]
-        {
-            self.st_redo = SystemTime::now();
-        }
+    pub(crate) fn redo(&mut self, target: &mut E::Target, now: Duration) -> E::Output {
+        self.st_redo = now;
         self.edit.redo(target)
     }
 
@@ -95,14 +208,116 @@ impl<E: Edit> Entry<E> {
     where
         Self: Sized,
     {
-        match self.edit.merge(other.edit) {
+        self.merge_with(other, E::merge_with_meta)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<E: AsyncEdit> Entry<E> {
+    pub(crate) async fn edit_async(&mut self, target: &mut E::Target, now: Duration) -> E::Output {
+        self.st_edit = now;
+        self.edit.edit(target).await
+    }
+
+    pub(crate) async fn undo_async(&mut self, target: &mut E::Target, now: Duration) -> E::Output {
+        self.st_undo = now;
+        self.edit.undo(target).await
+    }
+
+    pub(crate) async fn redo_async(&mut self, target: &mut E::Target, now: Duration) -> E::Output {
+        self.st_redo = now;
+        self.edit.redo(target).await
+    }
+}
+
+impl<E: EditWithCtx> Entry<E> {
+    pub(crate) fn edit_with(
+        &mut self,
+        target: &mut E::Target,
+        ctx: &mut E::Context,
+        now: Duration,
+    ) -> E::Output {
+        self.st_edit = now;
+        self.edit.edit(target, ctx)
+    }
+
+    pub(crate) fn undo_with(
+        &mut self,
+        target: &mut E::Target,
+        ctx: &mut E::Context,
+        now: Duration,
+    ) -> E::Output {
+        self.st_undo = now;
+        self.edit.undo(target, ctx)
+    }
+
+    pub(crate) fn redo_with(
+        &mut self,
+        target: &mut E::Target,
+        ctx: &mut E::Context,
+        now: Duration,
+    ) -> E::Output {
+        self.st_redo = now;
+        self.edit.redo(target, ctx)
+    }
+}
+
+impl<E: EditRef> Entry<E> {
+    pub(crate) fn edit_ref<'a>(
+        &mut self,
+        target: &'a mut E::Target,
+        now: Duration,
+    ) -> E::Output<'a> {
+        self.st_edit = now;
+        self.edit.edit(target)
+    }
+
+    pub(crate) fn undo_ref<'a>(
+        &mut self,
+        target: &'a mut E::Target,
+        now: Duration,
+    ) -> E::Output<'a> {
+        self.st_undo = now;
+        self.edit.undo(target)
+    }
+
+    pub(crate) fn redo_ref<'a>(
+        &mut self,
+        target: &'a mut E::Target,
+        now: Duration,
+    ) -> E::Output<'a> {
+        self.st_redo = now;
+        self.edit.redo(target)
+    }
+}
+
+impl<E> Entry<E> {
+    pub(crate) fn merge_with(&mut self, other: Self, merge: MergeFn<E>) -> Merged<Self> {
+        // Merging would lose the metadata of one of the entries,
+        // or move the entry into or out of a group.
+        let grouped = self.joined || self.group.is_some();
+        if self.barrier.is_some()
+            || self.meta != other.meta
+            || other.group.is_some()
+            || other.joined != grouped
+        {
+            return Merged::No(other);
+        }
+        let self_meta = EntryMeta {
+            meta: &self.meta,
+            seq: self.seq,
+            st_edit: self.st_edit,
+        };
+        let other_meta = EntryMeta {
+            meta: &other.meta,
+            seq: other.seq,
+            st_edit: other.st_edit,
+        };
+        match merge(&mut self.edit, other.edit, &self_meta, &other_meta) {
             Merged::Yes => {
-                #[cfg(feature = "std")]
-                {
-                    // st_undo and st_redo is always UNIX_EPOCH
-                    // when merging, so we don't store them.
-                    self.st_edit = other.st_edit;
-                }
+                // st_undo and st_redo is always zero
+                // when merging, so we don't store them.
+                self.st_edit = other.st_edit;
                 Merged::Yes
             }
             Merged::No(edit) => Merged::No(Self { edit, ..other }),
@@ -111,8 +326,114 @@ impl<E: Edit> Entry<E> {
     }
 }
 
+/// The function used to merge two edits.
+pub(crate) type MergeFn<E> = fn(&mut E, E, &EntryMeta, &EntryMeta) -> Merged<E>;
+
+/// The metadata of an [`Entry`], without the edit.
+///
+/// Passed to [`Edit::merge_with_meta`] so merge decisions can depend on
+/// when the edits were made.
+#[derive(Copy, Clone, Debug)]
+pub struct EntryMeta<'a> {
+    meta: &'a BTreeMap<String, String>,
+    seq: usize,
+    st_edit: Duration,
+}
+
+impl EntryMeta<'_> {
+    /// Returns the sequence number of the entry, see [`Entry::seq`].
+    pub fn seq(&self) -> usize {
+        self.seq
+    }
+
+    /// Returns the metadata value for the `key`.
+    pub fn get_meta(&self, key: &str) -> Option<&str> {
+        self.meta.get(key).map(String::as_str)
+    }
+
+    /// Returns the time the edit method was called, as given by the [`Clock`](crate::Clock).
+    pub fn time_of_edit(&self) -> Duration {
+        self.st_edit
+    }
+
+    /// Returns the time the edit method was called.
+    #[cfg(feature = "std")]
+    pub fn st_of_edit(&self) -> SystemTime {
+        UNIX_EPOCH + self.st_edit
+    }
+}
+
+/// Entries are equal if they have the same edit and metadata, and were made at the same time.
+///
+/// The times of the last undo and redo are not compared.
+impl<E: PartialEq> PartialEq for Entry<E> {
+    fn eq(&self, other: &Entry<E>) -> bool {
+        self.edit == other.edit
+            && self.label == other.label
+            && self.meta == other.meta
+            && self.group == other.group
+            && self.joined == other.joined
+            && self.seq == other.seq
+            && self.barrier == other.barrier
+            && self.st_edit == other.st_edit
+    }
+}
+
 impl<E: Display> Display for Entry<E> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         Display::fmt(&self.edit, f)
     }
 }
+
+#[cfg(feature = "serde")]
+impl<E> From<Entry<E>> for EntryDe<E> {
+    fn from(entry: Entry<E>) -> Self {
+        EntryDe {
+            edit: entry.edit,
+            label: entry.label,
+            meta: entry.meta,
+            group: entry.group,
+            joined: entry.joined,
+            seq: entry.seq,
+            barrier: entry.barrier,
+            #[cfg(feature = "std")]
+            st_edit: UNIX_EPOCH + entry.st_edit,
+            #[cfg(feature = "std")]
+            st_undo: UNIX_EPOCH + entry.st_undo,
+            #[cfg(feature = "std")]
+            st_redo: UNIX_EPOCH + entry.st_redo,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<E> From<EntryDe<E>> for Entry<E> {
+    fn from(entry: EntryDe<E>) -> Self {
+        Entry {
+            edit: entry.edit,
+            label: entry.label,
+            meta: entry.meta,
+            group: entry.group,
+            joined: entry.joined,
+            seq: entry.seq,
+            barrier: entry.barrier,
+            #[cfg(feature = "std")]
+            st_edit: since_epoch(entry.st_edit),
+            #[cfg(feature = "std")]
+            st_undo: since_epoch(entry.st_undo),
+            #[cfg(feature = "std")]
+            st_redo: since_epoch(entry.st_redo),
+            #[cfg(not(feature = "std"))]
+            st_edit: Duration::ZERO,
+            #[cfg(not(feature = "std"))]
+            st_undo: Duration::ZERO,
+            #[cfg(not(feature = "std"))]
+            st_redo: Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "std"))]
+fn since_epoch(st: SystemTime) -> Duration {
+    st.duration_since(UNIX_EPOCH).unwrap_or_default()
+}
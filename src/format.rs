@@ -2,9 +2,11 @@
 
 use crate::At;
 use alloc::string::ToString;
+use alloc::vec::Vec;
 #[cfg(feature = "colored")]
 use colored::{Color, Colorize};
 use core::fmt::{self, Write};
+use core::ops::{Bound, RangeBounds};
 #[cfg(feature = "std")]
 use std::time::SystemTime;
 
@@ -14,6 +16,12 @@ pub(crate) fn default_st_fmt(now: SystemTime, at: SystemTime) -> String {
     format!("{elapsed:.1?}")
 }
 
+#[cfg(feature = "chrono")]
+pub(crate) fn absolute_st_fmt(_: SystemTime, at: SystemTime) -> String {
+    let dt = chrono::DateTime::<chrono::Local>::from(at);
+    dt.format("%H:%M:%S").to_string()
+}
+
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct Format {
     #[cfg(feature = "colored")]
@@ -35,6 +43,49 @@ impl Default for Format {
     }
 }
 
+/// The positions that are shown by the display structures.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Window {
+    pub start: Bound<usize>,
+    pub end: Bound<usize>,
+    pub reverse: bool,
+    pub limit: Option<usize>,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Window {
+            start: Bound::Unbounded,
+            end: Bound::Unbounded,
+            reverse: false,
+            limit: None,
+        }
+    }
+}
+
+impl Window {
+    /// Returns `true` if all positions are shown in the default order.
+    pub fn is_full(self) -> bool {
+        self.start == Bound::Unbounded
+            && self.end == Bound::Unbounded
+            && !self.reverse
+            && self.limit.is_none()
+    }
+
+    /// Returns the shown positions in `0..=len`, in the order they are shown.
+    pub fn positions(self, len: usize) -> Vec<usize> {
+        let mut positions: Vec<_> = (0..=len)
+            .rev()
+            .filter(|i| (self.start, self.end).contains(i))
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect();
+        if self.reverse {
+            positions.reverse();
+        }
+        positions
+    }
+}
+
 impl Format {
     pub fn level_text(self, f: &mut fmt::Formatter, text: &str, level: usize) -> fmt::Result {
         #[cfg(feature = "colored")]
@@ -140,6 +191,14 @@ impl Format {
         }
     }
 
+    pub fn branch(self, f: &mut fmt::Formatter, label: &str) -> fmt::Result {
+        #[cfg(feature = "colored")]
+        if self.colored {
+            return write!(f, " {}{}{}", "(".yellow(), label.magenta(), ")".yellow());
+        }
+        write!(f, " ({label})")
+    }
+
     #[cfg(feature = "std")]
     pub fn elapsed(self, f: &mut fmt::Formatter, string: String) -> fmt::Result {
         #[cfg(feature = "colored")]
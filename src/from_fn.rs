@@ -0,0 +1,106 @@
+use crate::Edit;
+use core::mem;
+
+/// An [`Edit`] command made from a function.
+///
+/// The target is cloned before the function is called,
+/// and the clone is swapped back in when the edit is undone.
+///
+/// # Examples
+/// ```
+/// # use undo::{FromFn, Record};
+/// let mut target = String::new();
+/// let mut record = Record::new();
+/// let push = |c| FromFn::new(move |s: &mut String| s.push(c));
+///
+/// record.edit(&mut target, push('a'));
+/// record.edit(&mut target, push('b'));
+/// assert_eq!(target, "ab");
+///
+/// record.undo(&mut target);
+/// assert_eq!(target, "a");
+/// record.redo(&mut target);
+/// assert_eq!(target, "ab");
+/// ```
+#[derive(Clone, Debug)]
+pub struct FromFn<F, T> {
+    f: F,
+    target: Option<T>,
+}
+
+impl<F, T> FromFn<F, T> {
+    /// Creates a new `FromFn` from `f`.
+    pub const fn new(f: F) -> Self {
+        FromFn { f, target: None }
+    }
+}
+
+impl<F, T> Edit for FromFn<F, T>
+where
+    F: FnMut(&mut T),
+    T: Clone,
+{
+    type Target = T;
+    type Output = ();
+
+    fn edit(&mut self, target: &mut Self::Target) -> Self::Output {
+        self.target = Some(target.clone());
+        (self.f)(target)
+    }
+
+    fn undo(&mut self, target: &mut Self::Target) -> Self::Output {
+        if let Some(old) = self.target.as_mut() {
+            mem::swap(old, target);
+        }
+    }
+
+    fn redo(&mut self, target: &mut Self::Target) -> Self::Output {
+        if let Some(new) = self.target.as_mut() {
+            mem::swap(new, target);
+        }
+    }
+}
+
+/// An [`Edit`] command made from a fallible function.
+///
+/// Same as [`FromFn`] but for functions that outputs [`Result`].
+#[derive(Clone, Debug)]
+pub struct TryFromFn<F, T> {
+    f: F,
+    target: Option<T>,
+}
+
+impl<F, T> TryFromFn<F, T> {
+    /// Creates a new `TryFromFn` from `f`.
+    pub const fn new(f: F) -> Self {
+        TryFromFn { f, target: None }
+    }
+}
+
+impl<F, T, E> Edit for TryFromFn<F, T>
+where
+    F: FnMut(&mut T) -> Result<(), E>,
+    T: Clone,
+{
+    type Target = T;
+    type Output = Result<(), E>;
+
+    fn edit(&mut self, target: &mut Self::Target) -> Self::Output {
+        self.target = Some(target.clone());
+        (self.f)(target)
+    }
+
+    fn undo(&mut self, target: &mut Self::Target) -> Self::Output {
+        if let Some(old) = self.target.as_mut() {
+            mem::swap(old, target);
+        }
+        Ok(())
+    }
+
+    fn redo(&mut self, target: &mut Self::Target) -> Self::Output {
+        if let Some(new) = self.target.as_mut() {
+            mem::swap(new, target);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+use crate::socket::{Slot, Socket};
+use crate::{Edit, Event, History, Record};
+use alloc::collections::BTreeMap;
+use alloc::string::ToString;
+use core::fmt::Display;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A group of [`Record`]s or [`History`]s where one of them can be active.
+///
+/// Edits, undos, and redos are forwarded to the active structure.
+/// The group emits [`Event::ActiveChanged`] with the old and the new key
+/// to its own slot when the active structure changes.
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, Group, Record};
+/// let mut a = String::new();
+/// let mut b = String::new();
+/// let mut group = Group::new();
+/// group.insert("a", Record::new());
+/// group.insert("b", Record::new());
+///
+/// group.set_active("a");
+/// group.edit(&mut a, Add('a'));
+/// group.set_active("b");
+/// group.edit(&mut b, Add('b'));
+/// assert_eq!(a, "a");
+/// assert_eq!(b, "b");
+///
+/// group.undo(&mut b);
+/// assert_eq!(b, "");
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "K: Serialize, V: Serialize, S: Serialize",
+        deserialize = "K: Ord + Deserialize<'de>, V: Deserialize<'de>, S: Deserialize<'de>"
+    ))
+)]
+#[derive(Clone, Debug)]
+pub struct Group<K, V, S = ()> {
+    entries: BTreeMap<K, V>,
+    active: Option<K>,
+    socket: Socket<S>,
+}
+
+impl<K, V> Group<K, V> {
+    /// Returns a new group.
+    pub fn new() -> Group<K, V> {
+        Group {
+            entries: BTreeMap::new(),
+            active: None,
+            socket: Socket::default(),
+        }
+    }
+}
+
+impl<K, V, S> Group<K, V, S> {
+    /// Returns the number of structures in the group.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the group is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Sets how the event should be handled when the active structure changes.
+    pub fn connect(&mut self, slot: S) -> Option<S> {
+        self.socket.connect(Some(slot))
+    }
+
+    /// Removes and returns the slot if it exists.
+    pub fn disconnect(&mut self) -> Option<S> {
+        self.socket.disconnect()
+    }
+
+    /// Returns a mutable reference to the slot if it exists.
+    pub fn slot_mut(&mut self) -> Option<&mut S> {
+        self.socket.slot_mut()
+    }
+
+    /// Returns the key of the active structure.
+    pub fn active_key(&self) -> Option<&K> {
+        self.active.as_ref()
+    }
+
+    /// Returns an iterator over the keys and structures in the group.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter()
+    }
+}
+
+impl<K: Ord, V, S> Group<K, V, S> {
+    /// Returns a reference to the structure with the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key)
+    }
+
+    /// Returns a mutable reference to the structure with the key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.entries.get_mut(key)
+    }
+
+    /// Returns a reference to the active structure.
+    pub fn active(&self) -> Option<&V> {
+        self.active.as_ref().and_then(|key| self.entries.get(key))
+    }
+
+    /// Returns a mutable reference to the active structure.
+    pub fn active_mut(&mut self) -> Option<&mut V> {
+        self.active
+            .as_ref()
+            .and_then(|key| self.entries.get_mut(key))
+    }
+
+    /// Inserts the structure with the key, returning the previous structure if any.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.entries.insert(key, value)
+    }
+}
+
+impl<K: Ord + Display, V, S: Slot> Group<K, V, S> {
+    /// Removes and returns the structure with the key.
+    ///
+    /// If the structure is active the group will no longer have an active structure.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if self.active.as_ref() == Some(key) {
+            self.clear_active();
+        }
+        self.entries.remove(key)
+    }
+
+    /// Sets the structure with the key as the active one.
+    ///
+    /// Returns `false` if the key is not in the group.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::sync::mpsc;
+    /// # use undo::{Add, Event, Group, Record};
+    /// let (sender, receiver) = mpsc::channel();
+    /// let mut group = Group::default();
+    /// group.connect(sender);
+    /// group.insert("a", Record::<Add>::new());
+    /// group.insert("b", Record::new());
+    ///
+    /// group.set_active("a");
+    /// group.set_active("b");
+    /// group.clear_active();
+    /// let events: Vec<_> = receiver.try_iter().collect();
+    /// assert_eq!(events[1], Event::ActiveChanged { old: Some("a".into()), new: Some("b".into()) });
+    /// assert_eq!(events[2], Event::ActiveChanged { old: Some("b".into()), new: None });
+    /// ```
+    pub fn set_active(&mut self, key: K) -> bool {
+        if !self.entries.contains_key(&key) {
+            return false;
+        }
+        if self.active.as_ref() != Some(&key) {
+            let old = self.active.replace(key);
+            self.emit_active(old);
+        }
+        true
+    }
+
+    /// Clears the active structure.
+    pub fn clear_active(&mut self) {
+        if let Some(old) = self.active.take() {
+            self.emit_active(Some(old));
+        }
+    }
+
+    /// Emits [`Event::ActiveChanged`] from the old key to the current one.
+    fn emit_active(&mut self, old: Option<K>) {
+        let new = self.active.as_ref();
+        self.socket.emit(|| Event::ActiveChanged {
+            old: old.map(|key| key.to_string()),
+            new: new.map(ToString::to_string),
+        });
+    }
+}
+
+impl<K: Ord, E: Edit, F: Slot, S> Group<K, Record<E, F>, S> {
+    /// Calls the [`Record::edit`] method on the active record.
+    pub fn edit(&mut self, target: &mut E::Target, edit: E) -> Option<E::Output> {
+        self.active_mut().map(|record| record.edit(target, edit))
+    }
+
+    /// Calls the [`Record::undo`] method on the active record.
+    pub fn undo(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        self.active_mut().and_then(|record| record.undo(target))
+    }
+
+    /// Calls the [`Record::redo`] method on the active record.
+    pub fn redo(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        self.active_mut().and_then(|record| record.redo(target))
+    }
+
+    /// Calls the [`Record::set_saved`] method on the active record.
+    pub fn set_saved(&mut self) {
+        if let Some(record) = self.active_mut() {
+            record.set_saved();
+        }
+    }
+}
+
+impl<K: Ord, E: Edit, F: Slot, S> Group<K, History<E, F>, S> {
+    /// Calls the [`History::edit`] method on the active history.
+    pub fn edit(&mut self, target: &mut E::Target, edit: E) -> Option<E::Output> {
+        self.active_mut().map(|history| history.edit(target, edit))
+    }
+
+    /// Calls the [`History::undo`] method on the active history.
+    pub fn undo(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        self.active_mut().and_then(|history| history.undo(target))
+    }
+
+    /// Calls the [`History::redo`] method on the active history.
+    pub fn redo(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        self.active_mut().and_then(|history| history.redo(target))
+    }
+
+    /// Calls the [`History::set_saved`] method on the active history.
+    pub fn set_saved(&mut self) {
+        if let Some(history) = self.active_mut() {
+            history.set_saved();
+        }
+    }
+}
+
+impl<K, V, S> Default for Group<K, V, S> {
+    fn default() -> Group<K, V, S> {
+        Group {
+            entries: BTreeMap::new(),
+            active: None,
+            socket: Socket::default(),
+        }
+    }
+}
@@ -1,25 +1,47 @@
 //! A history tree of edit commands.
 
+mod branch_map;
 mod builder;
 mod checkpoint;
 mod display;
 mod queue;
+mod reflog;
+mod visit;
 
 pub use builder::Builder;
 pub use checkpoint::Checkpoint;
 pub use display::Display;
 pub use queue::Queue;
+pub use reflog::Movement;
+pub use visit::{Labels, TreeVisitor};
 
+use crate::entry::MergeFn;
+use crate::record::{compress_entries, GoToError};
+use crate::region;
+#[cfg(feature = "serde")]
+use crate::schema::{self, BranchDe, EntryDe, HistoryDe};
 use crate::socket::Slot;
-use crate::{At, Edit, Entry, Event, Record};
-use alloc::collections::VecDeque;
+#[cfg(feature = "async")]
+use crate::AsyncEdit;
+use crate::{
+    At, Edit, EditRef, EditWithCtx, Entry, Event, MergePolicy, OutputCombiner, Record, RegionEdit,
+    SizedEdit, Stats, Truncate, ValidatedEdit,
+};
+use alloc::collections::{BTreeMap, VecDeque};
 use alloc::string::String;
 use alloc::vec::Vec;
+use branch_map::BranchMap;
 use core::fmt;
+use core::iter;
 use core::mem;
+use core::num::NonZeroUsize;
+use core::ops::RangeBounds;
+use core::time::Duration;
+use reflog::RefLog;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use slab::Slab;
+#[cfg(feature = "std")]
+use std::time::SystemTime;
 
 /// A history tree of [`Edit`] commands.
 ///
@@ -57,9 +79,36 @@ use slab::Slab;
 #[derive(Clone, Debug)]
 pub struct History<E, S = ()> {
     root: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    generation: u32,
     saved: Option<At>,
     record: Record<E, S>,
-    branches: Slab<Branch<E>>,
+    branches: BranchMap<E>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    dropped: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    savepoints: BTreeMap<String, At>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) trunk: Trunk,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) max_branches: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) total_limit: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    visits: u64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    reflog: RefLog,
+}
+
+/// The branch that is shown as the main line of the history.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) enum Trunk {
+    /// The trunk is always the current root branch.
+    #[default]
+    Root,
+    /// The trunk ends at the entry with the label, or at the start of the history.
+    Pinned(Option<At>),
 }
 
 impl<E> History<E> {
@@ -67,14 +116,81 @@ impl<E> History<E> {
     pub fn new() -> History<E> {
         History::builder().build()
     }
-}
 
-impl<E, S> History<E, S> {
     /// Returns a new history builder.
-    pub fn builder() -> Builder<E, S> {
-        Builder::default()
+    ///
+    /// The slot type is set when [`Builder::connect`] is called.
+    pub fn builder() -> Builder<E> {
+        Builder::new()
+    }
+
+    /// Creates a history from the serializable format.
+    ///
+    /// See the [`schema`](crate::schema) module for more information.
+    ///
+    /// # Errors
+    /// Returns an error if the format version is not supported or the data is not consistent.
+    #[cfg(feature = "serde")]
+    pub fn from_serializable(de: HistoryDe<E>) -> Result<History<E>, schema::Error> {
+        schema::check_version(de.version)?;
+        let is_branch = |id| de.branches.contains_key(&id);
+        if !is_branch(de.root) {
+            return Err(schema::Error::Invalid("missing root branch"));
+        }
+        let is_at = |at: At| is_branch(at.root) && at.generation() == de.generation;
+        let is_valid = de
+            .branches
+            .iter()
+            .filter(|&(&id, _)| id != de.root)
+            .all(|(_, b)| is_at(b.parent) && !b.entries.is_empty())
+            && de.saved.is_none_or(is_at)
+            && de.savepoints.values().copied().all(is_at);
+        if !is_valid {
+            return Err(schema::Error::Invalid("branch does not exist"));
+        }
+        let branches = de
+            .branches
+            .into_iter()
+            .map(|(id, branch)| {
+                let entries = branch.entries.into_iter().map(Entry::from).collect();
+                let parent = branch.parent;
+                let label = branch.label;
+                (
+                    id,
+                    Branch {
+                        parent,
+                        entries,
+                        label,
+                        visited: 0,
+                    },
+                )
+            })
+            .collect::<BranchMap<E>>();
+        let mut record = Record::from_serializable(de.record)?;
+        // Entries in the branches are numbered too.
+        let seq = branches
+            .iter()
+            .flat_map(|(_, b)| b.entries())
+            .map(Entry::seq);
+        record.seq = seq.fold(record.seq, usize::max);
+        Ok(History {
+            root: de.root,
+            generation: de.generation,
+            saved: de.saved,
+            record,
+            branches,
+            dropped: de.dropped,
+            savepoints: de.savepoints,
+            trunk: Trunk::Root,
+            max_branches: None,
+            total_limit: None,
+            visits: 0,
+            reflog: RefLog::default(),
+        })
     }
+}
 
+impl<E, S> History<E, S> {
     /// Reserves capacity for at least `additional` more edits.
     ///
     /// # Panics
@@ -108,6 +224,41 @@ impl<E, S> History<E, S> {
         self.record.limit()
     }
 
+    /// Returns the maximum number of branches besides the current root branch, if there is one.
+    pub fn max_branches(&self) -> Option<usize> {
+        self.max_branches
+    }
+
+    /// Returns the maximum number of entries that are kept in all branches, if there is one.
+    pub fn total_limit(&self) -> Option<usize> {
+        self.total_limit
+    }
+
+    /// Returns the number of entries in all branches.
+    pub fn total_len(&self) -> usize {
+        self.record.len()
+            + self
+                .branches
+                .iter()
+                .map(|(_, b)| b.entries.len())
+                .sum::<usize>()
+    }
+
+    /// Returns the memory limit of the history in bytes, if it has one.
+    pub fn memory_limit(&self) -> Option<usize> {
+        self.record.memory_limit()
+    }
+
+    /// Returns the estimated memory used by the edits in the current branch in bytes.
+    ///
+    /// See [`Record::memory_usage`] for more information.
+    pub fn memory_usage(&self) -> usize
+    where
+        E: SizedEdit,
+    {
+        self.record.memory_usage()
+    }
+
     /// Sets how the event should be handled when the state changes.
     pub fn connect(&mut self, slot: S) -> Option<S> {
         self.record.connect(slot)
@@ -118,6 +269,11 @@ impl<E, S> History<E, S> {
         self.record.disconnect()
     }
 
+    /// Returns a mutable reference to the slot if it exists.
+    pub fn slot_mut(&mut self) -> Option<&mut S> {
+        self.record.slot_mut()
+    }
+
     /// Returns `true` if the target is in a saved state, `false` otherwise.
     pub fn is_saved(&self) -> bool {
         self.record.is_saved()
@@ -127,10 +283,35 @@ impl<E, S> History<E, S> {
     pub fn saved(&self) -> Option<At> {
         self.record
             .saved
-            .map(|index| At::new(self.root, index))
+            .map(|index| self.at(self.root, index))
             .or(self.saved)
     }
 
+    /// Returns the position of the savepoint with the `name`.
+    pub fn savepoint(&self, name: &str) -> Option<At> {
+        self.savepoints.get(name).copied()
+    }
+
+    /// Returns an iterator over the names and positions of the savepoints.
+    pub fn saved_states(&self) -> impl Iterator<Item = (&str, At)> {
+        self.savepoints.iter().map(|(k, &v)| (k.as_str(), v))
+    }
+
+    /// Marks the current position as a savepoint with the `name`,
+    /// returning the previous position of the savepoint if any.
+    ///
+    /// Savepoints are independent of the saved state, and are kept until the edits
+    /// they refer to are removed from the history.
+    pub fn save_as(&mut self, name: impl Into<String>) -> Option<At> {
+        let head = self.head();
+        self.savepoints.insert(name.into(), head)
+    }
+
+    /// Removes the savepoint with the `name`, returning its position if it existed.
+    pub fn remove_savepoint(&mut self, name: &str) -> Option<At> {
+        self.savepoints.remove(name)
+    }
+
     /// Returns `true` if the history can undo.
     pub fn can_undo(&self) -> bool {
         self.record.can_undo()
@@ -143,7 +324,192 @@ impl<E, S> History<E, S> {
 
     /// Returns the current position in the history.
     pub fn head(&self) -> At {
-        At::new(self.root, self.record.head())
+        self.at(self.root, self.record.head())
+    }
+
+    /// Returns the position at `index` in the branch `root`.
+    ///
+    /// Unlike [`At::new`], the position is in the current generation of branch ids,
+    /// see [`History::compact`].
+    pub fn at(&self, root: usize, index: usize) -> At {
+        At::with_generation(root, index, self.generation)
+    }
+
+    /// Returns `true` if `at` is a position in the history.
+    ///
+    /// The ids of removed branches are not reused until [`History::compact`] is called,
+    /// so positions in removed branches stay invalid even after new branches are created.
+    /// Positions from before a compaction are in an older [generation](At::generation)
+    /// and are never valid, even if their ids have been given to other branches.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('b'));
+    /// let (id, _) = history.branches().find(|&(id, _)| id != history.head().root).unwrap();
+    /// let a = history.children_of(history.parent_of(history.head()).unwrap())[1];
+    /// assert!(history.is_valid(a));
+    ///
+    /// history.drain_branch(id).for_each(drop);
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('c'));
+    /// assert!(!history.is_valid(a));
+    /// ```
+    pub fn is_valid(&self, at: At) -> bool {
+        self.contains(at)
+    }
+
+    fn contains(&self, at: At) -> bool {
+        if at.generation() != self.generation {
+            return false;
+        }
+        if at.root == self.root {
+            return at.index <= self.len();
+        }
+        self.branches.get(at.root).is_some_and(|branch| {
+            let start = branch.parent.index;
+            start <= at.index && at.index <= start + branch.len()
+        })
+    }
+
+    /// Returns the position in the branch that stores the entry at `at`.
+    ///
+    /// Positions in a branch that are before its own entries belong to one of its ancestors.
+    fn canonical(&self, mut at: At) -> Option<At> {
+        if !self.contains(at) {
+            return None;
+        }
+        while at.root != self.root {
+            let parent = self.branches[at.root].parent;
+            if at.index > parent.index {
+                break;
+            }
+            at.root = parent.root;
+        }
+        Some(at)
+    }
+
+    /// Returns the position before `at` in the tree.
+    ///
+    /// The same position can be described by every branch that goes through it,
+    /// so the returned positions always use the branch that stores the entry.
+    /// Returns `None` if `at` is the start of the history or does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.edit(&mut target, Add('b'));
+    /// let ab = history.head();
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('c'));
+    /// let ac = history.head();
+    ///
+    /// assert_eq!(history.parent_of(ab), history.parent_of(ac));
+    /// assert_eq!(history.children_of(history.parent_of(ac).unwrap()).len(), 2);
+    /// ```
+    pub fn parent_of(&self, at: At) -> Option<At> {
+        let at = self.canonical(at)?;
+        let index = at.index.checked_sub(1)?;
+        self.canonical(self.at(at.root, index))
+    }
+
+    /// Returns the positions directly after `at` in the tree.
+    ///
+    /// The next position in the same branch comes first, followed by the
+    /// first position of each branch that starts at `at`.
+    /// Returns an empty vector if `at` does not exist.
+    pub fn children_of(&self, at: At) -> Vec<At> {
+        let Some(at) = self.canonical(at) else {
+            return Vec::new();
+        };
+        let next = self.at(at.root, at.index + 1);
+        let root = self.root;
+        self.contains(next)
+            .then_some(next)
+            .into_iter()
+            .chain(
+                self.branches
+                    .iter()
+                    .filter(|&(id, b)| id != root && b.parent == at)
+                    .map(|(id, _)| self.at(id, at.index + 1)),
+            )
+            .collect()
+    }
+
+    /// Returns the positions on the path from `a` to `b` in the tree, including both ends.
+    ///
+    /// The path goes up from `a` to the closest common position and then down to `b`.
+    /// Returns an empty vector if `a` or `b` does not exist.
+    pub fn path_between(&self, a: At, b: At) -> Vec<At> {
+        let Some((mut up, down)) = self.split_path(a, b) else {
+            return Vec::new();
+        };
+        up.extend(down.into_iter().rev());
+        up
+    }
+
+    /// Returns the path from `a` up to the closest common position, including both ends,
+    /// and the path from `b` up to the closest common position, excluding it.
+    fn split_path(&self, a: At, b: At) -> Option<(Vec<At>, Vec<At>)> {
+        let ancestors = |at: At| {
+            let mut path: Vec<At> = self.canonical(at).into_iter().collect();
+            while let Some(parent) = path.last().and_then(|&at| self.parent_of(at)) {
+                path.push(parent);
+            }
+            path
+        };
+        let mut up = ancestors(a);
+        let mut down = ancestors(b);
+        if up.is_empty() || down.is_empty() {
+            return None;
+        }
+        // Remove the common part of the paths, but keep the closest common position.
+        while up.len() > 1 && down.len() > 1 && up[up.len() - 2] == down[down.len() - 2] {
+            up.pop();
+            down.pop();
+        }
+        down.pop();
+        Some((up, down))
+    }
+
+    /// Returns the entries that would be undone and redone to go from `a` to `b`,
+    /// without changing the target.
+    ///
+    /// This can be used to preview the changes before calling [`History::go_to`].
+    /// Returns `None` if `a` or `b` does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.edit(&mut target, Add('b'));
+    /// let ab = history.head();
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('c'));
+    /// history.edit(&mut target, Add('d'));
+    ///
+    /// let diff = history.diff(history.head(), ab).unwrap();
+    /// assert_eq!(diff.undo.len(), 2);
+    /// assert_eq!(diff.redo.len(), 1);
+    /// assert_eq!(*diff.redo[0].1.as_ref(), Add('b'));
+    /// ```
+    pub fn diff(&self, a: At, b: At) -> Option<TreeDiff<'_, E>> {
+        let (mut up, down) = self.split_path(a, b)?;
+        up.pop();
+        let entry = |at: At| (at, self.entry_at(at).unwrap());
+        Some(TreeDiff {
+            undo: up.into_iter().map(entry).collect(),
+            redo: down.into_iter().rev().map(entry).collect(),
+        })
     }
 
     /// Returns the head of the next branch in the history.
@@ -154,7 +520,7 @@ impl<E, S> History<E, S> {
         self.branches
             .iter()
             .find(|&(id, _)| id > self.root)
-            .map(|(id, branch)| At::new(id, branch.parent.index + 1))
+            .map(|(id, branch)| self.at(id, branch.parent.index + 1))
     }
 
     /// Returns the head of the previous branch in the history.
@@ -165,7 +531,7 @@ impl<E, S> History<E, S> {
         self.branches
             .iter()
             .rfind(|&(id, _)| id < self.root)
-            .map(|(id, branch)| At::new(id, branch.parent.index + 1))
+            .map(|(id, branch)| self.at(id, branch.parent.index + 1))
     }
 
     /// Returns the entry at the index in the current root branch.
@@ -175,11 +541,135 @@ impl<E, S> History<E, S> {
         self.record.get_entry(index)
     }
 
+    /// Returns a mutable reference to the entry that leads to `at`, in any branch.
+    ///
+    /// The edit in the entry can be changed with [`AsMut`], but it must still be able
+    /// to undo and redo the same change to the target, otherwise the target will
+    /// get out of sync with the history. See [`Record::get_edit_mut`] for more information.
+    ///
+    /// Returns `None` if `at` does not exist or is the start of the history.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// let a = history.head();
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('b'));
+    ///
+    /// let entry = history.get_entry_mut(a).unwrap();
+    /// assert_eq!(*entry.as_mut(), Add('a'));
+    /// ```
+    pub fn get_entry_mut(&mut self, at: At) -> Option<&mut Entry<E>> {
+        let at = self.canonical(at)?;
+        let index = at.index.checked_sub(1)?;
+        if at.root == self.root {
+            self.record.entries.get_mut(index)
+        } else {
+            let branch = self.branches.get_mut(at.root)?;
+            branch.entries.get_mut(index - branch.parent.index)
+        }
+    }
+
+    /// Returns the entry that leads to `at`, in any branch.
+    pub(crate) fn entry_at(&self, at: At) -> Option<&Entry<E>> {
+        let at = self.canonical(at)?;
+        let index = at.index.checked_sub(1)?;
+        if at.root == self.root {
+            self.record.entries.get(index)
+        } else {
+            let branch = self.branches.get(at.root)?;
+            branch.entries.get(index - branch.parent.index)
+        }
+    }
+
+    /// Returns the end of the trunk, the branch that is shown as the main line of the history.
+    ///
+    /// By default the trunk is the current branch, and it changes every time
+    /// the history switches to another branch. If the history was built with
+    /// [`Builder::auto_promote`] disabled, the trunk only changes when
+    /// [`History::promote_branch`] is called, or when an edit is added to the end of the trunk.
+    pub fn trunk(&self) -> At {
+        let end = self.at(self.root, self.len());
+        match self.trunk {
+            Trunk::Root => end,
+            Trunk::Pinned(None) => self.at(self.root, 0),
+            Trunk::Pinned(Some(label)) => self
+                .all_entries()
+                .find(|(_, e)| e.label == Some(label))
+                .map_or(end, |(at, _)| at),
+        }
+    }
+
+    /// Makes the current branch the trunk of the history.
+    ///
+    /// The trunk is shown as the main line by [`History::display`], with all other
+    /// branches shown as branching off from it. This only has an effect if the
+    /// history was built with [`Builder::auto_promote`] disabled.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::builder().auto_promote(false).build();
+    /// history.edit(&mut target, Add('a'));
+    /// history.edit(&mut target, Add('b'));
+    /// let ab = history.head();
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('c'));
+    ///
+    /// // The trunk stays on the first branch until the new branch is promoted.
+    /// assert_eq!(history.trunk(), ab);
+    /// history.promote_branch();
+    /// assert_eq!(history.trunk(), history.head());
+    /// ```
+    pub fn promote_branch(&mut self) {
+        if let Trunk::Pinned(_) = self.trunk {
+            self.trunk = Trunk::Pinned(self.tip_label());
+        }
+    }
+
+    /// Returns the label of the last entry in the current branch, giving it one if it has none.
+    fn tip_label(&mut self) -> Option<At> {
+        let at = self.at(self.root, self.len() + self.dropped);
+        let entry = self.record.entries.back_mut()?;
+        Some(*entry.label.get_or_insert(at))
+    }
+
     /// Returns an iterator over the entries in the current root branch.
     pub fn entries(&self) -> impl Iterator<Item = &Entry<E>> {
         self.record.entries()
     }
 
+    /// Returns the number of entries that can be redone in the current branch.
+    pub fn redo_len(&self) -> usize {
+        self.record.redo_len()
+    }
+
+    /// Returns an iterator over the entries that can be redone in the current branch,
+    /// in the order they are redone.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.edit(&mut target, Add('b'));
+    /// history.undo(&mut target);
+    /// history.undo(&mut target);
+    ///
+    /// let menu: Vec<_> = history.redoable_entries().map(|e| e.to_string()).collect();
+    /// assert_eq!(menu, ["Add 'a'", "Add 'b'"]);
+    /// ```
+    pub fn redoable_entries(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &Entry<E>> + ExactSizeIterator {
+        self.record.redoable_entries()
+    }
+
     /// Returns the branch with the given id.
     pub fn get_branch(&self, id: usize) -> Option<&Branch<E>> {
         self.branches.get(id)
@@ -190,39 +680,346 @@ impl<E, S> History<E, S> {
         self.branches.iter()
     }
 
+    /// Returns the statistics of the history.
+    ///
+    /// The timestamps and the number of branches include all branches in the history.
+    /// See [`Stats`] for more information.
+    pub fn stats(&self) -> Stats {
+        let entries = || self.all_entries().map(|(_, e)| e);
+        Stats {
+            earliest: entries().map(Entry::time_of_edit).min(),
+            latest: entries().map(Entry::time_of_latest).max(),
+            branches: self.branches.len(),
+            ..self.record.stats
+        }
+    }
+
+    /// Resets the counts in the statistics of the history.
+    pub fn reset_stats(&mut self) {
+        self.record.reset_stats();
+    }
+
+    /// Sets the label of the branch, returning the previous label if any.
+    ///
+    /// The label follows the branch when the history switches branches, and is removed
+    /// together with the branch. The current root branch can also be labeled.
+    /// Does nothing if the branch does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, At, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.set_branch_label(history.head().root, "main");
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('b'));
+    /// history.set_branch_label(history.head().root, "experiment-1");
+    ///
+    /// let main = history.find_branch("main").unwrap();
+    /// history.go_to(&mut target, At::new(main, 1));
+    /// assert_eq!(target, "a");
+    /// assert_eq!(history.branch_label(history.head().root), Some("main"));
+    /// ```
+    pub fn set_branch_label(&mut self, id: usize, label: impl Into<String>) -> Option<String> {
+        let branch = self.branches.get_mut(id)?;
+        branch.label.replace(label.into())
+    }
+
+    /// Removes the label of the branch, returning it if any.
+    pub fn remove_branch_label(&mut self, id: usize) -> Option<String> {
+        self.branches.get_mut(id)?.label.take()
+    }
+
+    /// Returns the label of the branch.
+    pub fn branch_label(&self, id: usize) -> Option<&str> {
+        self.branches.get(id)?.label()
+    }
+
+    /// Returns the id of the branch with the label.
+    ///
+    /// Labels do not need to be unique, in which case the branch with the lowest id is returned.
+    pub fn find_branch(&self, label: &str) -> Option<usize> {
+        self.branches
+            .iter()
+            .find(|(_, b)| b.label() == Some(label))
+            .map(|(id, _)| id)
+    }
+
+    /// Returns an iterator over all the entries in the history and their positions.
+    ///
+    /// The history is walked depth-first starting from the current root branch,
+    /// where the branches are visited right after their parent entry.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, At, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.edit(&mut target, Add('b'));
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('c'));
+    ///
+    /// let all: Vec<_> = history.all_entries().map(|(at, e)| (at, *e.as_ref())).collect();
+    /// assert_eq!(
+    ///     all,
+    ///     [
+    ///         (At::new(1, 1), Add('a')),
+    ///         (At::new(0, 2), Add('b')),
+    ///         (At::new(1, 2), Add('c')),
+    ///     ]
+    /// );
+    /// ```
+    pub fn all_entries(&self) -> impl Iterator<Item = (At, &Entry<E>)> {
+        let mut entries = Vec::new();
+        self.walk(
+            self.at(self.root, 0),
+            self.record.entries.iter(),
+            &mut entries,
+        );
+        entries.into_iter()
+    }
+
+    fn walk<'a>(
+        &'a self,
+        parent: At,
+        iter: impl Iterator<Item = &'a Entry<E>>,
+        entries: &mut Vec<(At, &'a Entry<E>)>,
+    ) {
+        let visit = |at: At, entries: &mut Vec<_>| {
+            for (id, branch) in self
+                .branches
+                .iter()
+                .filter(|&(id, b)| id != self.root && b.parent == at)
+            {
+                self.walk(self.at(id, at.index), branch.entries.iter(), entries);
+            }
+        };
+        visit(parent, entries);
+        for (i, entry) in iter.enumerate() {
+            let at = self.at(parent.root, parent.index + i + 1);
+            entries.push((at, entry));
+            visit(at, entries);
+        }
+    }
+
+    /// Sets the metadata `value` for the `key`, returning the previous value if any.
+    ///
+    /// The metadata is not affected by [`History::clear`].
+    pub fn set_meta(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        self.record.set_meta(key, value)
+    }
+
+    /// Returns the metadata value for the `key`.
+    pub fn get_meta(&self, key: &str) -> Option<&str> {
+        self.record.get_meta(key)
+    }
+
+    /// Removes and returns the metadata value for the `key`.
+    pub fn remove_meta(&mut self, key: &str) -> Option<String> {
+        self.record.remove_meta(key)
+    }
+
+    /// Returns an iterator over the metadata key-value pairs.
+    pub fn meta(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.record.meta()
+    }
+
+    /// Starts a group, where all edits until the matching [`History::end_group`]
+    /// are undone and redone as a single edit.
+    ///
+    /// See [`Record::begin_group`] for more information.
+    pub fn begin_group(&mut self, text: impl Into<String>) {
+        self.record.begin_group(text);
+    }
+
+    /// Ends the group started by the last call to [`History::begin_group`].
+    ///
+    /// Returns `false` if there is no group to end.
+    pub fn end_group(&mut self) -> bool {
+        self.record.end_group()
+    }
+
     /// Returns a queue.
-    pub fn queue(&mut self) -> Queue<E, S> {
+    pub fn queue(&mut self) -> Queue<'_, E, S> {
         Queue::from(self)
     }
 
     /// Returns a checkpoint.
-    pub fn checkpoint(&mut self) -> Checkpoint<E, S> {
+    pub fn checkpoint(&mut self) -> Checkpoint<'_, E, S> {
         Checkpoint::from(self)
     }
 
+    /// Returns a deep copy of the history without a slot.
+    ///
+    /// The branches and positions are the same in the fork, so the positions
+    /// can be used with both histories. Use [`History::compare`] to find the
+    /// entries that differ between the history and the fork.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, At, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    ///
+    /// let mut preview_target = target.clone();
+    /// let mut preview = history.fork::<()>();
+    /// preview.edit(&mut preview_target, Add('b'));
+    /// assert_eq!(preview_target, "ab");
+    /// assert_eq!(history.len(), 1);
+    /// assert_eq!(preview.head(), At::new(history.head().root, 2));
+    /// ```
+    pub fn fork<T>(&self) -> History<E, T>
+    where
+        E: Clone,
+    {
+        History {
+            root: self.root,
+            generation: self.generation,
+            saved: self.saved,
+            record: self.record.fork(),
+            branches: self.branches.clone(),
+            dropped: self.dropped,
+            savepoints: self.savepoints.clone(),
+            trunk: self.trunk,
+            max_branches: self.max_branches,
+            total_limit: self.total_limit,
+            visits: self.visits,
+            reflog: self.reflog.clone(),
+        }
+    }
+
+    /// Returns a record with copies of the entries on the path from the start of the history to `to`.
+    ///
+    /// The record is at the end of the path, and keeps the timestamps of the entries
+    /// and the saved state and savepoints that are on the path.
+    /// This can be used to share only the edits that produced a state.
+    /// Returns `None` if `to` does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History, Record};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.set_saved();
+    /// history.edit(&mut target, Add('b'));
+    /// let ab = history.head();
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('c'));
+    ///
+    /// let record: Record<_> = history.extract_path(ab).unwrap();
+    /// assert_eq!(record.len(), 2);
+    /// assert_eq!(record.head(), 2);
+    /// assert_eq!(record.saved(), Some(1));
+    /// assert_eq!(*record.entries().last().unwrap().as_ref(), Add('b'));
+    /// ```
+    pub fn extract_path<T>(&self, to: At) -> Option<Record<E, T>>
+    where
+        E: Clone,
+    {
+        let mut path = alloc::vec![self.canonical(to)?];
+        while let Some(parent) = path.last().and_then(|&at| self.parent_of(at)) {
+            path.push(parent);
+        }
+        path.reverse();
+        let index_of = |at: At| {
+            let at = self.canonical(at)?;
+            path.iter().position(|&p| p == at)
+        };
+
+        let mut record = self.record.fork();
+        record.entries = path[1..]
+            .iter()
+            .map(|&at| self.entry_at(at).unwrap().clone())
+            .collect();
+        record.index = record.entries.len();
+        record.saved = self.saved().and_then(index_of);
+        record.savepoints = self
+            .savepoints
+            .iter()
+            .filter_map(|(name, &at)| Some((name.clone(), index_of(at)?)))
+            .collect();
+        record.group = None;
+        record.discarded = record.discarded.map(|_| Vec::new());
+        if let Some(snapshot) = &mut record.snapshot {
+            snapshot.clear();
+        }
+        Some(record)
+    }
+
+    /// Compares the entries in the history with the entries in `other`.
+    ///
+    /// Entries are the same if they have the same edit and were made at the same position,
+    /// which is the case for the entries a [fork](History::fork) shares with its history.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.edit(&mut target, Add('b'));
+    ///
+    /// let mut fork = history.fork::<()>();
+    /// fork.undo(&mut target);
+    /// fork.edit(&mut target, Add('c'));
+    /// fork.prune_branches(|_, _| false);
+    ///
+    /// let comparison = history.compare(&fork);
+    /// assert_eq!(comparison.added, [fork.head()]);
+    /// assert_eq!(comparison.removed, [history.head()]);
+    /// ```
+    pub fn compare<T>(&self, other: &History<E, T>) -> Comparison
+    where
+        E: PartialEq,
+    {
+        // Entries are identified by their label and edit.
+        fn id<E>((at, e): (At, &Entry<E>)) -> (At, At, &E) {
+            (at, e.label.unwrap_or(at), e.as_ref())
+        }
+        let ours: Vec<_> = self.all_entries().map(id).collect();
+        let theirs: Vec<_> = other.all_entries().map(id).collect();
+        let diff = |a: &[(At, At, &E)], b: &[(At, At, &E)]| {
+            a.iter()
+                .filter(|&&(_, label, e)| !b.iter().any(|&(_, l, f)| l == label && f == e))
+                .map(|&(at, _, _)| at)
+                .collect()
+        };
+        Comparison {
+            added: diff(&theirs, &ours),
+            removed: diff(&ours, &theirs),
+        }
+    }
+
     /// Returns a structure for configurable formatting of the history.
-    pub fn display(&self) -> Display<E, S> {
+    pub fn display(&self) -> Display<'_, E, S> {
         Display::from(self)
     }
 
-    fn rm_child_of(&mut self, at: At) {
-        // We need to check if any of the branches had the removed node as root.
-        let mut dead: Vec<_> = self
-            .branches()
-            .filter(|&(_, child)| child.parent == at)
-            .map(|(id, _)| id)
-            .collect();
+    /// Removes the branches and all their descendants, returning the removed branches.
+    fn rm_branches(&mut self, mut dead: Vec<usize>) -> Vec<Branch<E>> {
+        let root = self.root;
+        let mut removed = Vec::new();
         while let Some(id) = dead.pop() {
+            // The branch might already have been removed as a descendant of another branch.
+            if !self.branches.contains(id) {
+                continue;
+            }
             // Remove the dead branch.
-            self.branches.remove(id);
+            removed.push(self.branches.remove(id));
             self.saved = self.saved.filter(|s| s.root != id);
+            self.savepoints.retain(|_, at| at.root != id);
             // Add the children of the dead branch so they are removed too.
             dead.extend(
                 self.branches()
-                    .filter(|&(_, child)| child.parent.root == id)
+                    .filter(|&(child, b)| child != root && b.parent.root == id)
                     .map(|(id, _)| id),
             )
         }
+        removed
     }
 
     fn mk_path(&mut self, mut to: usize) -> Option<impl Iterator<Item = (usize, Branch<E>)>> {
@@ -241,162 +1038,1478 @@ impl<E, S> History<E, S> {
         Some(path.into_iter().rev())
     }
 
+    /// Lowers the index of the branches that are children of the root at or after `from`,
+    /// and all the positions that are inside of them.
+    fn shift_children(&mut self, from: usize, removed: usize) {
+        let root = self.root;
+        let mut moved: Vec<_> = self
+            .branches
+            .iter_mut()
+            .filter(|&(id, ref b)| id != root && b.parent.root == root && b.parent.index >= from)
+            .map(|(id, b)| {
+                b.parent.index -= removed;
+                id
+            })
+            .collect();
+        while let Some(id) = moved.pop() {
+            self.saved
+                .iter_mut()
+                .chain(self.savepoints.values_mut())
+                .filter(|at| at.root == id)
+                .for_each(|at| at.index -= removed);
+            moved.extend(
+                self.branches
+                    .iter_mut()
+                    .filter(|&(child, ref b)| child != root && b.parent.root == id)
+                    .map(|(child, b)| {
+                        b.parent.index -= removed;
+                        child
+                    }),
+            );
+        }
+    }
+
+    /// Takes the branch out of the slab, leaving an empty branch in its place.
+    ///
+    /// The label and the last visit are kept in the slab since they belong to the id.
     fn nil_replace(&mut self, id: usize) -> Option<Branch<E>> {
         let dest = self.branches.get_mut(id)?;
-        let dest = mem::replace(dest, Branch::NIL);
-        Some(dest)
+        let label = dest.label.take();
+        let visited = dest.visited;
+        let branch = mem::replace(dest, Branch::NIL);
+        dest.label = label;
+        dest.visited = visited;
+        Some(branch)
+    }
+
+    /// Converts the history into the serializable format.
+    ///
+    /// The slot and the merge policy are not included.
+    /// See the [`schema`](crate::schema) module for more information.
+    #[cfg(feature = "serde")]
+    pub fn into_serializable(self) -> HistoryDe<E> {
+        HistoryDe {
+            version: schema::VERSION,
+            root: self.root,
+            generation: self.generation,
+            saved: self.saved,
+            record: self.record.into_serializable(),
+            branches: self
+                .branches
+                .into_iter()
+                .map(|(id, branch)| {
+                    let entries = branch.entries.into_iter().map(EntryDe::from).collect();
+                    let parent = branch.parent;
+                    let label = branch.label;
+                    (
+                        id,
+                        BranchDe {
+                            parent,
+                            entries,
+                            label,
+                        },
+                    )
+                })
+                .collect(),
+            dropped: self.dropped,
+            savepoints: self.savepoints,
+        }
     }
 }
 
 impl<E, S: Slot> History<E, S> {
+    /// Holds back the events until [`History::end_batch`] is called,
+    /// where they are emitted as a single [`Event::Batch`].
+    ///
+    /// See [`Record::begin_batch`] for more information.
+    pub fn begin_batch(&mut self) {
+        self.record.begin_batch();
+    }
+
+    /// Ends the batch started by [`History::begin_batch`], emitting the held back
+    /// events if it is the outermost batch.
+    pub fn end_batch(&mut self) {
+        self.record.end_batch();
+    }
+
     /// Marks the target as currently being in a saved or unsaved state.
     pub fn set_saved(&mut self) {
         self.saved = None;
         self.record.set_saved();
     }
 
+    /// Puts a barrier at the current position with the label,
+    /// returning `false` if there is nothing to undo.
+    ///
+    /// See [`Record::push_barrier`] for more information.
+    pub fn push_barrier(&mut self, label: impl Into<String>) -> bool {
+        self.record.push_barrier(label)
+    }
+
+    /// Sets if [`History::undo`] can undo past barriers, see [`History::push_barrier`].
+    pub fn set_undo_through_barriers(&mut self, on: bool) {
+        self.record.set_undo_through_barriers(on);
+    }
+
     /// Clears the saved state of the target.
     pub fn clear_saved(&mut self) {
         self.saved = None;
         self.record.clear_saved();
     }
 
-    /// Removes all edits from the history without undoing them.
-    pub fn clear(&mut self) {
-        let old_root = self.root;
-        self.saved = None;
-        self.record.clear();
-        self.branches.clear();
-        self.root = self.branches.insert(Branch::NIL);
+    /// Marks the position `at` in any branch as the saved state.
+    ///
+    /// This is useful when loading a target together with a serialized history,
+    /// where the target was saved at another position than the head.
+    /// Returns `false` and does nothing if `at` does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// let a = history.head();
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('b'));
+    ///
+    /// assert!(history.set_saved_at(a));
+    /// assert!(!history.is_saved());
+    /// history.revert(&mut target);
+    /// assert_eq!(target, "a");
+    /// assert!(history.is_saved());
+    /// ```
+    pub fn set_saved_at(&mut self, at: At) -> bool {
+        let Some(at) = self.canonical(at) else {
+            return false;
+        };
+        let was_saved = self.is_saved();
+        if at.root == self.root {
+            self.saved = None;
+            self.record.saved = Some(at.index);
+        } else {
+            self.saved = Some(at);
+            self.record.saved = None;
+        }
+        let is_saved = self.is_saved();
         self.record
             .socket
-            .emit_if(old_root != self.root, || Event::Root(self.root));
+            .emit_if(was_saved != is_saved, || Event::Saved(is_saved));
+        true
     }
 
-    fn set_root(&mut self, new: At, rm_saved: Option<usize>) {
-        debug_assert_ne!(self.root, new.root);
-
-        // Update all branches that are now children of the new root.
-        //
-        // |           | <- The old root is now a child of the new root.
-        // | |         | | <- This branch is still a child of the old root.
-        // |/          |/
-        // |    ->    /
-        // o |       o | <- This branch is now a child of the new root.
-        // |/        |/
-        // |         |
-        //
-        // If we split at 'o' all branches that are children of 'o' or below should now
-        // be children of the new root. All branches that are above should still be
-        // children of the old root.
-        self.branches
-            .iter_mut()
-            .filter(|(_, child)| child.parent.root == self.root && child.parent.index <= new.index)
-            .for_each(|(_, child)| child.parent.root = new.root);
+    /// Pushes the entry on top of the history without executing its edit.
+    ///
+    /// See [`Record::push_unexecuted`] for more information.
+    pub fn push_unexecuted(&mut self, entry: Entry<E>) {
+        self.push_with(entry, None);
+    }
 
-        match (self.saved, rm_saved) {
-            (Some(saved), None) if saved.root == new.root => {
-                self.saved = None;
-                self.record.saved = Some(saved.index);
-            }
-            (None, Some(saved)) => {
-                self.saved = Some(At::new(self.root, saved));
-            }
-            _ => (),
+    /// Removes the branches where `keep` returns `false` together with all their descendants,
+    /// returning the number of removed branches.
+    ///
+    /// The current root branch is always kept. The saved state and savepoints
+    /// in the removed branches are cleared.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('b'));
+    /// assert_eq!(history.branches().count(), 2);
+    ///
+    /// assert_eq!(history.prune_branches(|_, _| false), 1);
+    /// assert_eq!(history.branches().count(), 1);
+    /// ```
+    pub fn prune_branches(&mut self, keep: impl Fn(usize, &Branch<E>) -> bool) -> usize {
+        let root = self.root;
+        let dead = self
+            .branches()
+            .filter(|&(id, branch)| id != root && !keep(id, branch))
+            .map(|(id, _)| id)
+            .collect();
+        let removed = self.rm_branches(dead).len();
+        self.record
+            .socket
+            .emit_if(removed > 0, || Event::Pruned(removed));
+        removed
+    }
+
+    /// Removes all branches except the current branch, returning the number of removed branches.
+    ///
+    /// The history is left with a single linear branch, like a [`Record`], which keeps
+    /// serialized histories small. The saved state and savepoints are kept if they are
+    /// in the current branch. Emits [`Event::Pruned`] if any branches were removed.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.set_saved();
+    /// history.edit(&mut target, Add('b'));
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('c'));
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('d'));
+    ///
+    /// assert_eq!(history.flatten(), 2);
+    /// assert_eq!(history.branches().count(), 1);
+    /// assert_eq!(history.total_len(), 2);
+    /// history.revert(&mut target);
+    /// assert_eq!(target, "a");
+    /// ```
+    pub fn flatten(&mut self) -> usize {
+        self.prune_branches(|_, _| false)
+    }
+
+    /// Returns the number of ids that were used by removed branches.
+    ///
+    /// The ids of removed branches are not given to new branches, so the ids keep growing
+    /// as branches are removed until [`History::compact`] is called.
+    pub fn unused_branch_ids(&self) -> usize {
+        self.branches.next_id() - self.branches.len()
+    }
+
+    /// Gives the branches the ids from `0` in the order of their old ids, returning the new id
+    /// of every branch by its old id.
+    ///
+    /// The head, the saved state, and the savepoints are moved to the new ids. Since the ids of
+    /// removed branches can be given to other branches after the compaction, the history starts
+    /// a new [generation](At::generation) of branch ids, and positions from before the compaction
+    /// are no longer [valid](History::is_valid). Positions that are kept outside of the history
+    /// can be moved to the new generation with [`History::at`] and the returned ids.
+    /// Does nothing if there are no unused ids. Emits [`Event::Renumbered`] if any ids were changed.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('b'));
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('c'));
+    /// history.prune_branches(|_, _| false);
+    /// assert_eq!(history.unused_branch_ids(), 2);
+    ///
+    /// let old = history.head();
+    /// let ids = history.compact();
+    /// assert_eq!(history.unused_branch_ids(), 0);
+    /// assert!(!history.is_valid(old));
+    /// assert_eq!(history.head(), history.at(ids[&old.root], old.index));
+    /// assert_eq!(history.head().root, 0);
+    /// ```
+    pub fn compact(&mut self) -> BTreeMap<usize, usize> {
+        if self.unused_branch_ids() == 0 {
+            return self.branches.iter().map(|(id, _)| (id, id)).collect();
+        }
+        let old_head = self.begin_op();
+        let labels: Vec<_> = self
+            .all_entries()
+            .map(|(at, entry)| (entry.label.unwrap_or(at), at))
+            .collect();
+        let ids = self.branches.compact();
+        // The old positions might refer to other branches now, so they are left in the old generation.
+        self.generation += 1;
+        let generation = self.generation;
+        let id = |root: usize| ids.get(&root).copied().unwrap_or(root);
+        let remap = |at: At| At::with_generation(id(at.root), at.index, generation);
+
+        self.root = id(self.root);
+        self.branches
+            .iter_mut()
+            .for_each(|(_, branch)| branch.parent = remap(branch.parent));
+        self.saved
+            .iter_mut()
+            .chain(self.savepoints.values_mut())
+            .for_each(|at| *at = remap(*at));
+
+        // The old labels can be given to new entries now that the ids are reused,
+        // so all the entries get new labels from their new positions.
+        let dropped = self.dropped;
+        let label =
+            |root: usize, index: usize| At::with_generation(root, index + dropped, generation);
+        let root = self.root;
+        for (i, entry) in self.record.entries.iter_mut().enumerate() {
+            entry.label = Some(label(root, i + 1));
+        }
+        for (id, branch) in self.branches.iter_mut().filter(|&(id, _)| id != root) {
+            let start = branch.parent.index;
+            for (i, entry) in branch.entries.iter_mut().enumerate() {
+                entry.label = Some(label(id, start + i + 1));
+            }
+        }
+        let relabel = |old: At| {
+            let (_, at) = labels.iter().find(|&&(l, _)| l == old)?;
+            Some(label(id(at.root), at.index))
+        };
+        if let Trunk::Pinned(Some(old)) = self.trunk {
+            self.trunk = match relabel(old) {
+                Some(new) => Trunk::Pinned(Some(new)),
+                None => Trunk::Pinned(self.tip_label()),
+            };
         }
+        self.relabel_reflog(relabel);
 
-        debug_assert_ne!(self.saved.map(|s| s.root), Some(new.root));
+        let changed = ids.iter().any(|(old, new)| old != new);
+        self.record
+            .socket
+            .emit_if(changed, || Event::Renumbered(ids.clone()));
+        self.end_op(old_head);
+        ids
+    }
 
-        self.root = new.root;
-        self.record.socket.emit(|| Event::Root(new.root));
+    /// Sets the maximum number of branches that are kept besides the current root branch,
+    /// returning the number of removed branches.
+    ///
+    /// When there are more branches the least recently visited branches without
+    /// children are removed, emitting [`Event::BranchDropped`] for each of them.
+    /// The saved state and savepoints in the removed branches are cleared.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('b'));
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('c'));
+    /// assert_eq!(history.branches().count(), 3);
+    ///
+    /// assert_eq!(history.set_max_branches(Some(1)), 1);
+    /// assert_eq!(history.branches().count(), 2);
+    /// ```
+    pub fn set_max_branches(&mut self, max_branches: Option<usize>) -> usize {
+        self.max_branches = max_branches;
+        self.record.socket.begin_op();
+        let removed = self.evict_branches();
+        self.record.socket.end_op();
+        removed
     }
-}
 
-impl<E: Edit, S: Slot> History<E, S> {
-    /// Pushes the [`Edit`] to the top of the history and executes its [`Edit::edit`] method.
-    pub fn edit(&mut self, target: &mut E::Target, edit: E) -> E::Output {
-        let head = self.head();
-        let (output, merged, tail, rm_saved) = self.record.edit_and_push(target, Entry::new(edit));
+    /// Removes the least recently visited branches until the maximum number of branches
+    /// is not exceeded, returning the number of removed branches.
+    fn evict_branches(&mut self) -> usize {
+        let Some(max) = self.max_branches else {
+            return 0;
+        };
+        let root = self.root;
+        let mut removed = 0;
+        while self.branches.len() > max + 1 {
+            // Only branches without children are removed so the other branches keep their parents.
+            let is_leaf = |id| {
+                self.branches()
+                    .all(|(child, b)| child == root || b.parent.root != id)
+            };
+            let Some(id) = self
+                .branches()
+                .filter(|&(id, _)| id != root && is_leaf(id))
+                .min_by_key(|(_, b)| b.visited)
+                .map(|(id, _)| id)
+            else {
+                break;
+            };
+            self.rm_branches(alloc::vec![id]);
+            self.record.socket.emit(|| Event::BranchDropped(id));
+            removed += 1;
+        }
+        removed
+    }
 
-        // Check if the limit has been reached.
-        if !merged && head.index == self.record.head() {
-            let root = self.root;
-            self.rm_child_of(At::new(root, 0));
-            self.branches
-                .iter_mut()
-                .filter(|(_, child)| child.parent.root == root)
-                .for_each(|(_, child)| child.parent.index -= 1);
+    /// Sets the maximum number of entries that are kept in all branches,
+    /// returning the number of removed entries.
+    ///
+    /// When there are more entries the least recently visited branches without
+    /// children are removed first, emitting [`Event::BranchDropped`] for each of them.
+    /// If the current branch alone has too many entries, the oldest entries in it
+    /// are removed like when the [`limit`](History::limit) is reached.
+    /// Only entries that have been applied are removed from the current branch.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('b'));
+    /// history.edit(&mut target, Add('c'));
+    /// assert_eq!(history.total_len(), 3);
+    ///
+    /// assert_eq!(history.set_total_limit(Some(1)), 2);
+    /// assert_eq!(history.total_len(), 1);
+    /// assert_eq!(history.branches().count(), 1);
+    /// assert_eq!(target, "bc");
+    /// ```
+    pub fn set_total_limit(&mut self, total_limit: Option<usize>) -> usize {
+        self.total_limit = total_limit;
+        let old_head = self.begin_op();
+        let removed = self.evict_entries();
+        self.end_op(old_head);
+        removed
+    }
+
+    /// Removes the least recently visited branches, and then the oldest entries in the
+    /// current branch, until the total limit is not exceeded, returning the number of removed entries.
+    fn evict_entries(&mut self) -> usize {
+        let Some(max) = self.total_limit else {
+            return 0;
+        };
+        let root = self.root;
+        let mut removed = 0;
+        while self.total_len() > max {
+            let is_leaf = |id| {
+                self.branches()
+                    .all(|(child, b)| child == root || b.parent.root != id)
+            };
+            let Some(id) = self
+                .branches()
+                .filter(|&(id, _)| id != root && is_leaf(id))
+                .min_by_key(|(_, b)| b.visited)
+                .map(|(id, _)| id)
+            else {
+                break;
+            };
+            removed += self
+                .rm_branches(alloc::vec![id])
+                .iter()
+                .map(|b| b.entries.len())
+                .sum::<usize>();
+            self.record.socket.emit(|| Event::BranchDropped(id));
+        }
+
+        let excess = self.total_len().saturating_sub(max).min(self.record.head());
+        let excess = self.record.protect_front(excess);
+        if excess > 0 {
+            self.record.drain(..excess).for_each(drop);
+            self.record.stats.dropped += excess;
+            self.record.socket.emit(|| Event::Dropped(excess));
+            self.drop_front(excess);
+            removed += excess;
         }
+        removed
+    }
+
+    /// Removes the branch with the `id` together with all its descendants,
+    /// returning an iterator over the removed entries.
+    ///
+    /// The entries of the branch are yielded first, followed by the entries of its descendants.
+    /// The saved state and savepoints in the removed branches are cleared.
+    /// Returns an empty iterator and does nothing if the branch is the current root branch
+    /// or does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('b'));
+    ///
+    /// let (id, _) = history.branches().find(|&(id, _)| id != history.head().root).unwrap();
+    /// let drained: Vec<_> = history.drain_branch(id).map(|e| *e.as_ref()).collect();
+    /// assert_eq!(drained, [Add('a')]);
+    /// assert_eq!(history.branches().count(), 1);
+    /// ```
+    pub fn drain_branch(&mut self, id: usize) -> impl Iterator<Item = Entry<E>> {
+        let removed = if id != self.root && self.branches.contains(id) {
+            self.rm_branches(alloc::vec![id])
+        } else {
+            Vec::new()
+        };
+        let n = removed.len();
+        self.record.socket.emit_if(n > 0, || Event::Pruned(n));
+        removed.into_iter().flat_map(|branch| branch.entries)
+    }
+
+    /// Removes the branches where none of the entries have been used since `st`
+    /// together with all their descendants, returning the number of removed branches.
+    ///
+    /// See [`History::prune_branches`] for more information.
+    #[cfg(feature = "std")]
+    pub fn prune_older_than(&mut self, st: SystemTime) -> usize {
+        self.prune_branches(|_, branch| branch.entries().any(|e| e.st_of_latest() >= st))
+    }
+
+    /// Sets the limit of the history, returning the number of removed entries.
+    ///
+    /// The limit applies to the entries in the current branch, see [`Record::set_limit`].
+    /// The branches that split off from the removed entries are removed too,
+    /// together with their saved state and savepoints.
+    ///
+    /// # Examples
+    /// ```
+    /// # use core::num::NonZeroUsize;
+    /// # use undo::{Add, History, Truncate};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('b'));
+    /// history.edit(&mut target, Add('c'));
+    /// assert_eq!(history.branches().count(), 2);
+    ///
+    /// let limit = NonZeroUsize::new(1).unwrap();
+    /// assert_eq!(history.set_limit(limit, Truncate::Front), Some(1));
+    /// assert_eq!(history.len(), 1);
+    /// assert_eq!(history.branches().count(), 1);
+    /// ```
+    pub fn set_limit(&mut self, limit: NonZeroUsize, truncate: Truncate) -> Option<usize> {
+        let old_head = self.begin_op();
+        let old_len = self.record.len();
+        let Some((front, back)) = self.record.truncate_to(limit, truncate) else {
+            self.record.socket.end_op();
+            return None;
+        };
+        let root = self.root;
+
+        // Remove the branches that split off from the removed entries.
+        let end = old_len - back - front;
+        self.drop_front(front);
+        let dead = self
+            .branches()
+            .filter(|&(id, b)| id != root && b.parent.root == root && b.parent.index > end)
+            .map(|(id, _)| id)
+            .collect();
+        self.rm_branches(dead);
+        self.savepoints
+            .retain(|_, at| at.root != root || at.index <= end);
+        self.end_op(old_head);
+        Some(front + back)
+    }
+
+    /// Removes all edits from the history without undoing them.
+    pub fn clear(&mut self) {
+        let old_root = self.root;
+        let head = self.begin_op();
+        self.saved = None;
+        self.record.clear();
+        self.branches.clear();
+        self.root = self.branches.insert(Branch::NIL);
+        self.savepoints.retain(|_, at| *at == head);
+        let start = self.at(self.root, 0);
+        self.savepoints.values_mut().for_each(|at| *at = start);
+        self.emit_root(old_root);
+        self.end_op(head);
+    }
+
+    /// Pushes the entry, merging it with the previous entry using `merge` if it is provided.
+    fn push_with(&mut self, entry: Entry<E>, merge: Option<MergeFn<E>>) {
+        let old_head = self.begin_op();
+        let head = old_head;
+        // The trunk follows the edits that are added to the end of it.
+        let extends_trunk = match self.trunk {
+            Trunk::Root => false,
+            Trunk::Pinned(label) => {
+                let prev = head.index.checked_sub(1);
+                label == prev.and_then(|i| self.record.entries[i].label)
+                    && label.is_some() == prev.is_some()
+            }
+        };
+        let (merged, tail, rm_saved) = self.record.push_with(entry, merge);
+
+        // Check if a limit has been reached.
+        let dropped = if merged {
+            0
+        } else {
+            head.index + 1 - self.record.head()
+        };
+        self.drop_front(dropped);
 
         // Handle new branch by putting the tail into the empty root branch
         // before we swap the root with the new branch.
         if !tail.is_empty() {
             let next = self.branches.insert(Branch::NIL);
-            let new = At::new(next, head.index);
+            let new = self.at(next, head.index - dropped);
             let root = self.branches.get_mut(head.root).unwrap();
             debug_assert!(root.entries.is_empty());
             root.parent = new;
             root.entries = tail;
             self.set_root(new, rm_saved);
+            self.evict_branches();
+        }
+        self.evict_entries();
+
+        // Give the new entry a label that does not change when the root changes.
+        let head = self.head();
+        let label = self.at(head.root, head.index + self.dropped);
+        if let Some(entry) = head
+            .index
+            .checked_sub(1)
+            .and_then(|i| self.record.entries.get_mut(i))
+        {
+            entry.label.get_or_insert(label);
+        }
+        if extends_trunk {
+            let label = head
+                .index
+                .checked_sub(1)
+                .and_then(|i| self.record.entries[i].label);
+            self.trunk = Trunk::Pinned(label);
         }
+        self.end_op(old_head);
+    }
+
+    /// Removes the branches and savepoints that are before `n` in the root branch
+    /// after `n` entries have been removed from the front of the record.
+    fn drop_front(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        self.dropped += n;
+        let root = self.root;
+        let dead = self
+            .branches()
+            .filter(|&(id, b)| id != root && b.parent.root == root && b.parent.index < n)
+            .map(|(id, _)| id)
+            .collect();
+        self.rm_branches(dead);
+        self.savepoints
+            .retain(|_, at| match at.index.checked_sub(n) {
+                _ if at.root != root => true,
+                Some(index) => {
+                    at.index = index;
+                    true
+                }
+                None => false,
+            });
+        self.shift_children(n, n);
+    }
+
+    /// Starts an operation whose events are batched if events are coalesced,
+    /// returning the head before the operation.
+    fn begin_op(&mut self) -> At {
+        self.record.socket.begin_op();
+        self.head()
+    }
+
+    /// Ends the operation started by [`History::begin_op`].
+    fn end_op(&mut self, old_head: At) {
+        self.emit_head(old_head);
+        self.log_head();
+        self.record.socket.end_op();
+    }
+
+    /// Emits [`Event::Head`] and [`Event::HeadChanged`] if the head has changed.
+    fn emit_head(&mut self, old: At) {
+        let new = self.head();
+        if old != new {
+            self.record.socket.emit(|| Event::Head(new));
+            self.record.socket.emit(|| Event::HeadChanged { old, new });
+        }
+    }
+
+    /// Reverses the split done when pushing an entry moved the tail of the root into `old`.
+    ///
+    /// The pushed entry must already have been removed.
+    pub(crate) fn unsplit(&mut self, old: usize) {
+        let new = self.root;
+        debug_assert_ne!(old, new);
+        let could_redo = self.can_redo();
+        let mut branch = self.nil_replace(old).unwrap();
+        debug_assert_eq!(branch.parent, self.head());
+        self.record.entries.append(&mut branch.entries);
+
+        // All children of the new root were children of the old root before the split.
+        self.branches
+            .iter_mut()
+            .filter(|&(id, ref child)| id != old && id != new && child.parent.root == new)
+            .for_each(|(_, child)| child.parent.root = old);
+        self.savepoints
+            .values_mut()
+            .filter(|at| at.root == new)
+            .for_each(|at| at.root = old);
+        if let Some(saved) = self.saved.filter(|saved| saved.root == old) {
+            self.saved = None;
+            self.record.saved = Some(saved.index);
+        }
+
+        self.branches.remove(new);
+        self.root = old;
+        let can_redo = self.can_redo();
+        let socket = &mut self.record.socket;
+        socket.begin_op();
+        socket.emit_if(could_redo != can_redo, || Event::Redo(can_redo));
+        self.emit_root(new);
+        self.record.socket.end_op();
+    }
+
+    fn set_root(&mut self, new: At, rm_saved: Option<usize>) {
+        debug_assert_ne!(self.root, new.root);
+
+        // Update all branches that are now children of the new root.
+        //
+        // |           | <- The old root is now a child of the new root.
+        // | |         | | <- This branch is still a child of the old root.
+        // |/          |/
+        // |    ->    /
+        // o |       o | <- This branch is now a child of the new root.
+        // |/        |/
+        // |         |
+        //
+        // If we split at 'o' all branches that are children of 'o' or below should now
+        // be children of the new root. All branches that are above should still be
+        // children of the old root.
+        self.branches
+            .iter_mut()
+            .filter(|&(id, ref child)| {
+                id != new.root && child.parent.root == self.root && child.parent.index <= new.index
+            })
+            .for_each(|(_, child)| child.parent.root = new.root);
+        self.savepoints
+            .values_mut()
+            .filter(|at| at.root == self.root && at.index <= new.index)
+            .for_each(|at| at.root = new.root);
+
+        match (self.saved, rm_saved) {
+            (Some(saved), None) if saved.root == new.root => {
+                self.saved = None;
+                self.record.saved = Some(saved.index);
+            }
+            (None, Some(saved)) => {
+                self.saved = Some(self.at(self.root, saved));
+            }
+            _ => (),
+        }
+
+        debug_assert_ne!(self.saved.map(|s| s.root), Some(new.root));
+
+        let old = self.root;
+        self.visits += 1;
+        self.branches[old].visited = self.visits;
+        self.root = new.root;
+        self.emit_root(old);
+    }
+
+    /// Emits [`Event::Root`] and [`Event::Branch`] if the root has changed.
+    fn emit_root(&mut self, old: usize) {
+        let new = self.root;
+        if old != new {
+            self.record.socket.emit(|| Event::Root(new));
+            self.record.socket.emit(|| Event::Branch { old, new });
+        }
+    }
+}
+
+impl<E: Edit, S: Slot> History<E, S> {
+    /// Pushes the [`Edit`] to the top of the history and executes its [`Edit::edit`] method.
+    pub fn edit(&mut self, target: &mut E::Target, edit: E) -> E::Output {
+        let mut entry = Entry::new(edit);
+        let output = self
+            .record
+            .hooks
+            .edit(&mut entry, target, self.record.clock.now());
+        self.push(entry, true);
+        output
+    }
 
+    /// Same as [`History::edit`] but also stores the metadata in the entry.
+    ///
+    /// See [`Record::edit_with_meta`] for more information.
+    pub fn edit_with_meta<K: Into<String>, V: Into<String>>(
+        &mut self,
+        target: &mut E::Target,
+        edit: E,
+        meta: impl IntoIterator<Item = (K, V)>,
+    ) -> E::Output {
+        let mut entry = Entry::new(edit);
+        entry.meta = meta
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect();
+        let output = self
+            .record
+            .hooks
+            .edit(&mut entry, target, self.record.clock.now());
+        self.push(entry, true);
         output
     }
 
+    fn push(&mut self, entry: Entry<E>, merge: bool) {
+        self.push_with(entry, merge.then_some(E::merge_with_meta));
+    }
+
+    /// Merges consecutive entries in every branch of the history.
+    ///
+    /// Entries are merged using [`Edit::merge`] the same way as when they are pushed,
+    /// following the merge policy and never across a barrier. Two entries are never merged if the state between them is the current position,
+    /// the saved state, or the parent of another branch, so the tree keeps the same shape.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Edit, History, Merged};
+    /// struct Append(String);
+    ///
+    /// impl Edit for Append {
+    ///     type Target = String;
+    ///     type Output = ();
+    ///
+    ///     fn edit(&mut self, target: &mut String) {
+    ///         target.push_str(&self.0);
+    ///     }
+    ///
+    ///     fn undo(&mut self, target: &mut String) {
+    ///         target.truncate(target.len() - self.0.len());
+    ///     }
+    ///
+    ///     fn merge(&mut self, other: Self) -> Merged<Self> {
+    ///         self.0.push_str(&other.0);
+    ///         Merged::Yes
+    ///     }
+    /// }
+    ///
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Append("a".into()));
+    /// history.set_saved();
+    /// history.edit(&mut target, Append("b".into()));
+    /// history.set_saved();
+    /// history.edit(&mut target, Append("c".into()));
+    /// history.clear_saved();
+    /// assert_eq!(history.len(), 3);
+    ///
+    /// history.compress();
+    /// assert_eq!(history.len(), 1);
+    /// history.undo(&mut target);
+    /// assert_eq!(target, "");
+    /// ```
+    pub fn compress(&mut self) {
+        let could_undo = self.can_undo();
+        let could_redo = self.can_redo();
+        let old_index = self.record.index;
+
+        // All positions that other parts of the history refers to must be kept.
+        let head = self.begin_op();
+        let root = self.root;
+        let mut pinned: Vec<At> = self
+            .branches
+            .iter()
+            .filter(|&(id, _)| id != root)
+            .map(|(_, b)| b.parent)
+            .collect();
+        pinned.push(head);
+        pinned.extend(self.saved());
+        pinned.extend(self.savepoints.values());
+
+        let generation = self.generation;
+        let at = |root: usize, index: usize| At::with_generation(root, index, generation);
+        let mut maps = BTreeMap::new();
+        let policy = self.record.merge_policy;
+        let map = compress_entries(&mut self.record.entries, 0, policy, |i| {
+            pinned.contains(&at(root, i))
+        });
+        maps.insert(root, map);
+
+        let parents: BTreeMap<_, _> = self.branches.iter().map(|(id, b)| (id, b.parent)).collect();
+        for (id, branch) in self.branches.iter_mut().filter(|&(id, _)| id != root) {
+            let offset = branch.parent.index;
+            let map = compress_entries(&mut branch.entries, offset, policy, |i| {
+                pinned.contains(&at(id, i))
+            });
+            maps.insert(id, map);
+        }
+
+        // Maps a position before compression to the position after compression.
+        let remap = |at: At| -> At {
+            let mut index = maps[&at.root][at.index - offset_of(&parents, root, at.root)];
+            let mut id = at.root;
+            while id != root {
+                let parent = parents[&id];
+                let old = offset_of(&parents, root, id);
+                let new = maps[&parent.root][parent.index - offset_of(&parents, root, parent.root)];
+                index = index + new - old;
+                id = parent.root;
+            }
+            At { index, ..at }
+        };
+
+        for (id, branch) in self.branches.iter_mut().filter(|&(id, _)| id != root) {
+            branch.parent = remap(parents[&id]);
+        }
+        self.record.index = remap(head).index;
+        self.record.saved = self.record.saved.map(|i| remap(at(root, i)).index);
+        self.saved = self.saved.map(remap);
+        self.savepoints.values_mut().for_each(|at| *at = remap(*at));
+
+        let can_undo = self.can_undo();
+        let can_redo = self.can_redo();
+        let socket = &mut self.record.socket;
+        socket.emit_if(could_undo != can_undo, || Event::Undo(can_undo));
+        socket.emit_if(could_redo != can_redo, || Event::Redo(can_redo));
+        self.record.emit_index(old_index);
+        self.end_op(head);
+    }
+
+    /// Merges the entries in the range of the current branch into a single entry.
+    ///
+    /// Works the same as [`Record::merge_range`], but will also return `false` and do nothing
+    /// if the state between two of the entries in the range is the parent of another branch.
+    pub fn merge_range(&mut self, range: impl RangeBounds<usize>) -> bool {
+        let (start, end) = crate::record::range_of(range, self.len());
+        self.merge_range_with(start, end, self.record.merge_policy)
+    }
+
+    /// Merges the entries in `start..end` if the policy allows it, see [`History::merge_range`].
+    fn merge_range_with(&mut self, start: usize, end: usize, policy: MergePolicy<E>) -> bool {
+        let root = self.root;
+        let is_parent = self.branches.iter().any(|(id, b)| {
+            id != root && b.parent.root == root && start < b.parent.index && b.parent.index < end
+        });
+        if is_parent {
+            return false;
+        }
+
+        let old_head = self.begin_op();
+        let old_len = self.len();
+        let merged = self.record.merge_range_with(start, end, policy);
+        let removed = old_len - self.len();
+        if removed > 0 {
+            self.savepoints.retain(|_, at| match at.index {
+                _ if at.root != root || at.index <= start => true,
+                index if index >= end => {
+                    at.index -= removed;
+                    true
+                }
+                _ => false,
+            });
+            self.shift_children(end, removed);
+        }
+        self.end_op(old_head);
+        merged
+    }
+
+    /// Merges the two most recently applied entries in the current branch into a single entry.
+    ///
+    /// See [`History::merge_range`] and [`Record::merge_top`] for more information.
+    pub fn merge_top(&mut self) -> bool {
+        let index = self.record.head();
+        index >= 2 && self.merge_range_with(index - 2, index, MergePolicy::Always)
+    }
+
     /// Calls the [`Edit::undo`] method for the active edit
     /// and sets the previous one as the new active one.
     pub fn undo(&mut self, target: &mut E::Target) -> Option<E::Output> {
-        self.record.undo(target)
+        let old_head = self.begin_op();
+        let output = self.record.undo(target);
+        self.end_op(old_head);
+        output
     }
 
     /// Calls the [`Edit::redo`] method for the active edit
     /// and sets the next one as the new active one.
     pub fn redo(&mut self, target: &mut E::Target) -> Option<E::Output> {
-        self.record.redo(target)
+        let old_head = self.begin_op();
+        let output = self.record.redo(target);
+        self.end_op(old_head);
+        output
+    }
+
+    /// Redoes the first edit in the branch `id`, if the branch starts right after the head.
+    ///
+    /// This allows a redo menu to choose between the children of the head, see [`History::children_of`].
+    /// If `id` is the current branch this is the same as [`History::redo`].
+    /// Returns `None` and does nothing if the branch does not start right after the head.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// let a = history.head().root;
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('b'));
+    /// let b = history.head().root;
+    /// history.undo(&mut target);
+    ///
+    /// history.redo_into(&mut target, a);
+    /// assert_eq!(target, "a");
+    /// assert_eq!(history.redo_into(&mut target, b), None);
+    /// history.undo(&mut target);
+    /// history.redo_into(&mut target, b);
+    /// assert_eq!(target, "b");
+    /// ```
+    pub fn redo_into(&mut self, target: &mut E::Target, id: usize) -> Option<E::Output> {
+        if id == self.root {
+            return self.redo(target);
+        }
+        let branch = self.branches.get(id)?;
+        let head = self.head();
+        if branch.parent != head || branch.entries.is_empty() {
+            return None;
+        }
+        self.go_to(target, self.at(id, head.index + 1))
+            .into_iter()
+            .next()
+    }
+
+    /// Calls [`History::undo`] up to `n` times, stopping early if there are no more edits to undo.
+    ///
+    /// Groups are undone as a single edit, and the undo stops at a barrier.
+    /// The events are only emitted once, after all the edits have been undone.
+    /// The returned vector contains the output of each successful [`History::undo`] call.
+    pub fn undo_n(&mut self, target: &mut E::Target, n: usize) -> Vec<E::Output> {
+        let old_head = self.begin_op();
+        let outputs = self.record.undo_n(target, n);
+        self.end_op(old_head);
+        outputs
+    }
+
+    /// Calls [`History::redo`] up to `n` times, stopping early if there are no more edits to redo.
+    ///
+    /// Groups are redone as a single edit, and the redo stops at a barrier.
+    /// The events are only emitted once, after all the edits have been redone.
+    /// The returned vector contains the output of each successful [`History::redo`] call.
+    pub fn redo_n(&mut self, target: &mut E::Target, n: usize) -> Vec<E::Output> {
+        let old_head = self.begin_op();
+        let outputs = self.record.redo_n(target, n);
+        self.end_op(old_head);
+        outputs
+    }
+
+    /// Undoes all the edits in the current branch.
+    ///
+    /// The events are only emitted once, after all the edits have been undone.
+    pub fn undo_all(&mut self, target: &mut E::Target) -> Vec<E::Output> {
+        self.go_to(target, self.at(self.root, 0))
+    }
+
+    /// Redoes all the edits in the current branch.
+    ///
+    /// The events are only emitted once, after all the edits have been redone.
+    pub fn redo_all(&mut self, target: &mut E::Target) -> Vec<E::Output> {
+        self.go_to(target, self.at(self.root, self.len()))
     }
 
     /// Revert the changes done to the target since the saved state.
     pub fn revert(&mut self, target: &mut E::Target) -> Vec<E::Output> {
+        self.revert_with(target)
+    }
+
+    /// Same as [`History::revert`] but combines the outputs with the [`OutputCombiner`].
+    pub fn revert_with<C: OutputCombiner<E::Output>>(&mut self, target: &mut E::Target) -> C {
         let Some(saved) = self.saved() else {
-            return Vec::new();
+            return C::default();
         };
-        self.go_to(target, saved)
+        self.go_to_with(target, saved)
+    }
+
+    /// Go to the savepoint with the `name`.
+    ///
+    /// Does nothing if there is no savepoint with the `name`.
+    pub fn revert_to(&mut self, target: &mut E::Target, name: &str) -> Vec<E::Output> {
+        self.savepoint(name)
+            .map_or_else(Vec::new, |at| self.go_to(target, at))
+    }
+
+    /// Same as [`History::go_to`] but returns an error if `at` does not exist.
+    ///
+    /// Returns `Ok` with no outputs if the head is already at `at`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, At, History};
+    /// # use undo::record::GoToError;
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// let root = history.head().root;
+    ///
+    /// assert_eq!(history.try_go_to(&mut target, At::new(root, 2)), Err(GoToError::OutOfBounds));
+    /// assert_eq!(
+    ///     history.try_go_to(&mut target, At::new(root + 1, 0)),
+    ///     Err(GoToError::UnknownBranch(root + 1))
+    /// );
+    /// assert_eq!(history.try_go_to(&mut target, At::new(root, 0)).unwrap().len(), 1);
+    /// ```
+    pub fn try_go_to(
+        &mut self,
+        target: &mut E::Target,
+        at: At,
+    ) -> Result<Vec<E::Output>, GoToError> {
+        // Branches from an older generation might have been given to other branches.
+        if at.generation() != self.generation || !self.branches.contains(at.root) {
+            return Err(GoToError::UnknownBranch(at.root));
+        }
+        if !self.contains(at) {
+            return Err(GoToError::OutOfBounds);
+        }
+        Ok(self.go_to(target, at))
     }
 
     /// Repeatedly calls [`Edit::undo`] or [`Edit::redo`] until the edit at `at` is reached.
+    ///
+    /// Does nothing if `at` is from before the history was [compacted](History::compact).
     pub fn go_to(&mut self, target: &mut E::Target, at: At) -> Vec<E::Output> {
+        self.go_to_with(target, at)
+    }
+
+    /// Same as [`History::go_to`] but combines the outputs with the [`OutputCombiner`].
+    ///
+    /// See [`OutputCombiner`] for more information.
+    pub fn go_to_with<C: OutputCombiner<E::Output>>(
+        &mut self,
+        target: &mut E::Target,
+        at: At,
+    ) -> C {
+        let old_head = self.begin_op();
+        let mut outputs = Outputs(C::default());
+        self.go_to_inner(target, at, &mut outputs);
+        self.end_op(old_head);
+        outputs.0
+    }
+
+    /// Same as [`History::go_to`] but also returns the positions of the entries that were undone and redone.
+    ///
+    /// See [`Navigation`] for more information.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, At, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.edit(&mut target, Add('b'));
+    /// let ab = history.head();
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('c'));
+    ///
+    /// let report = history.go_to_with_report(&mut target, ab);
+    /// assert_eq!(target, "ab");
+    /// assert_eq!(report.undone.len(), 1);
+    /// assert_eq!(report.redone, [ab]);
+    /// let undone = history.get_entry_mut(report.undone[0]).unwrap();
+    /// assert_eq!(*undone.as_ref(), Add('c'));
+    /// ```
+    pub fn go_to_with_report(&mut self, target: &mut E::Target, at: At) -> Navigation<E::Output> {
+        let old_head = self.begin_op();
+        let mut report = Navigation::default();
+        self.go_to_inner(target, at, &mut report);
+        self.end_op(old_head);
+        report
+            .undone
+            .iter_mut()
+            .chain(&mut report.redone)
+            .for_each(|at| *at = self.at(at.root, at.index));
+        report
+    }
+
+    fn go_to_inner(
+        &mut self,
+        target: &mut E::Target,
+        at: At,
+        report: &mut impl Collect<E::Output>,
+    ) {
+        if at.generation() != self.generation {
+            return;
+        }
         if self.root == at.root {
-            return self.record.go_to(target, at.index);
+            return self.go_to_index(target, at.index, report);
         }
 
         // Get the path from `root` to `branch`.
         let Some(path) = self.mk_path(at.root) else {
-            return Vec::new();
+            return;
         };
 
-        let mut outputs = Vec::new();
         for (id, branch) in path {
             // Move to the parent of the branch so we can apply the edits in the branch on top of it.
-            let mut outs = self.record.go_to(target, branch.parent.index);
-            outputs.append(&mut outs);
+            self.go_to_index(target, branch.parent.index, report);
             // Apply the edits in the branch and move older edits into their own branch.
             for entry in branch.entries {
                 let index = self.record.head();
-                let (_, _, entries, rm_saved) = self.record.redo_and_push(target, entry);
+                let (output, _, entries, rm_saved) = self.record.redo_and_push(target, entry);
                 if !entries.is_empty() {
-                    let new = At::new(id, index);
+                    let new = self.at(id, index);
                     let root = self.branches.get_mut(self.root).unwrap();
                     debug_assert!(root.entries.is_empty());
                     root.parent = new;
                     root.entries = entries;
+                    // The positions below the split are moved to the new root, the same as in `set_root`.
+                    report.reroot(self.root, new);
                     self.set_root(new, rm_saved);
                 }
+                report.redone(self.root, iter::once(index + 1));
+                report.outputs().push(output);
             }
         }
 
-        let mut outs = self.record.go_to(target, at.index);
-        outputs.append(&mut outs);
-        outputs
+        self.go_to_index(target, at.index, report);
+    }
+
+    /// Goes to the index in the current root branch and adds the undone or redone positions to the report.
+    fn go_to_index(
+        &mut self,
+        target: &mut E::Target,
+        index: usize,
+        report: &mut impl Collect<E::Output>,
+    ) {
+        let old = self.record.head();
+        self.record.go_to_into(target, index, report.outputs());
+        let new = self.record.head();
+        if new < old {
+            report.undone(self.root, (new + 1..=old).rev());
+        } else {
+            report.redone(self.root, old + 1..=new);
+        }
+    }
+}
+
+impl<E: Edit + Clone, S: Slot> History<E, S> {
+    /// Replays the edits in the branch on top of `onto`, returning the outputs of the edits.
+    ///
+    /// The replayed edits are the ones from the position where the branch and `onto` split
+    /// to the end of the branch, so they are the same no matter which branch is the current one.
+    /// The edits are cloned, so the branch is kept as it is, and the replayed
+    /// edits end up in a new branch unless `onto` is at the end of a branch.
+    /// The replayed edits are not merged with each other.
+    /// Does nothing if the branch or `onto` does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// let a = history.head();
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('b'));
+    /// history.edit(&mut target, Add('c'));
+    /// let experiment = history.head().root;
+    ///
+    /// // Replay 'b' and 'c' on top of 'a'.
+    /// history.rebase(&mut target, experiment, a);
+    /// assert_eq!(target, "abc");
+    /// ```
+    pub fn rebase(&mut self, target: &mut E::Target, branch: usize, onto: At) -> Vec<E::Output> {
+        // The storage of the branches changes when the root changes,
+        // so the edits are found from the end of the branch instead.
+        let end = if branch == self.root {
+            Some(self.len())
+        } else {
+            self.branches.get(branch).map(|b| b.parent.index + b.len())
+        };
+        let Some(diff) = end.and_then(|end| self.diff(onto, self.at(branch, end))) else {
+            return Vec::new();
+        };
+        let edits: Vec<E> = diff
+            .redo
+            .into_iter()
+            .map(|(_, entry)| entry.as_ref().clone())
+            .collect();
+        if edits.is_empty() {
+            return Vec::new();
+        }
+
+        self.go_to(target, onto);
+        edits
+            .into_iter()
+            .map(|edit| {
+                let mut entry = Entry::new(edit);
+                let output = self
+                    .record
+                    .hooks
+                    .edit(&mut entry, target, self.record.clock.now());
+                self.push(entry, false);
+                output
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<E: AsyncEdit, S: Slot> History<E, S> {
+    /// Pushes the edit to the top of the history and awaits its [`AsyncEdit::edit`] method.
+    ///
+    /// Branching, merging, groups, the saved state, and the events works the same as in
+    /// [`History::edit`], except that the edits are merged with [`AsyncEdit::merge`] without the
+    /// metadata. The [hooks](crate::EditHook) are not called, since they only observe [`Edit`] commands.
+    pub async fn edit_async(&mut self, target: &mut E::Target, edit: E) -> E::Output {
+        let mut entry = Entry::new(edit);
+        let output = entry.edit_async(target, self.record.clock.now()).await;
+        self.push_with(entry, Some(|a, b, _, _| a.merge(b)));
+        output
+    }
+
+    /// Awaits the [`AsyncEdit::undo`] method for the active edit
+    /// and sets the previous one as the new active one.
+    pub async fn undo_async(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        let old_head = self.begin_op();
+        let output = self.record.undo_async(target).await;
+        self.end_op(old_head);
+        output
+    }
+
+    /// Awaits the [`AsyncEdit::redo`] method for the active edit
+    /// and sets the next one as the new active one.
+    pub async fn redo_async(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        let old_head = self.begin_op();
+        let output = self.record.redo_async(target).await;
+        self.end_op(old_head);
+        output
+    }
+}
+
+impl<E: EditWithCtx, S: Slot> History<E, S> {
+    /// Pushes the edit to the top of the history and executes its [`EditWithCtx::edit`] method.
+    ///
+    /// Branching, merging, groups, the saved state, and the events works the same as in
+    /// [`History::edit`], except that the edits are merged with [`EditWithCtx::merge`] without the
+    /// metadata. The [hooks](crate::EditHook) are not called, since they only observe [`Edit`] commands.
+    pub fn edit_with(
+        &mut self,
+        target: &mut E::Target,
+        ctx: &mut E::Context,
+        edit: E,
+    ) -> E::Output {
+        let mut entry = Entry::new(edit);
+        let output = entry.edit_with(target, ctx, self.record.clock.now());
+        self.push_with(entry, Some(|a, b, _, _| a.merge(b)));
+        output
+    }
+
+    /// Calls the [`EditWithCtx::undo`] method for the active edit
+    /// and sets the previous one as the new active one.
+    pub fn undo_with(&mut self, target: &mut E::Target, ctx: &mut E::Context) -> Option<E::Output> {
+        let old_head = self.begin_op();
+        let output = self.record.undo_with(target, ctx);
+        self.end_op(old_head);
+        output
+    }
+
+    /// Calls the [`EditWithCtx::redo`] method for the active edit
+    /// and sets the next one as the new active one.
+    pub fn redo_with(&mut self, target: &mut E::Target, ctx: &mut E::Context) -> Option<E::Output> {
+        let old_head = self.begin_op();
+        let output = self.record.redo_with(target, ctx);
+        self.end_op(old_head);
+        output
+    }
+}
+
+impl<E: EditRef, S: Slot> History<E, S> {
+    /// Pushes the edit to the top of the history and executes its [`EditRef::edit`] method.
+    ///
+    /// Branching, merging, groups, the saved state, and the events works the same as in
+    /// [`History::edit`], except that the edits are merged with [`EditRef::merge`] without the
+    /// metadata. The [hooks](crate::EditHook) are not called, since they only observe [`Edit`] commands.
+    pub fn edit_ref<'a>(&mut self, target: &'a mut E::Target, edit: E) -> E::Output<'a> {
+        let mut entry = Entry::new(edit);
+        let output = entry.edit_ref(target, self.record.clock.now());
+        self.push_with(entry, Some(|a, b, _, _| a.merge(b)));
+        output
+    }
+
+    /// Calls the [`EditRef::undo`] method for the active edit
+    /// and sets the previous one as the new active one.
+    pub fn undo_ref<'a>(&mut self, target: &'a mut E::Target) -> Option<E::Output<'a>> {
+        let old_head = self.begin_op();
+        let output = self.record.undo_ref(target);
+        self.end_op(old_head);
+        output
+    }
+
+    /// Calls the [`EditRef::redo`] method for the active edit
+    /// and sets the next one as the new active one.
+    pub fn redo_ref<'a>(&mut self, target: &'a mut E::Target) -> Option<E::Output<'a>> {
+        let old_head = self.begin_op();
+        let output = self.record.redo_ref(target);
+        self.end_op(old_head);
+        output
+    }
+}
+
+impl<T, Err, E: Edit<Output = Result<T, Err>>, S: Slot> History<E, S> {
+    /// Same as [`History::edit`] but the edit is only pushed if it succeeds.
+    pub fn try_edit(&mut self, target: &mut E::Target, edit: E) -> Result<T, Err> {
+        let mut entry = Entry::new(edit);
+        let output = self
+            .record
+            .hooks
+            .edit(&mut entry, target, self.record.clock.now())?;
+        self.push(entry, true);
+        Ok(output)
+    }
+
+    /// Same as [`History::undo`] but the position is only changed if the undo succeeds.
+    pub fn try_undo(&mut self, target: &mut E::Target) -> Option<Result<T, Err>> {
+        let old_head = self.begin_op();
+        let output = self.record.try_undo(target);
+        self.end_op(old_head);
+        output
+    }
+
+    /// Same as [`History::redo`] but the position is only changed if the redo succeeds.
+    pub fn try_redo(&mut self, target: &mut E::Target) -> Option<Result<T, Err>> {
+        let old_head = self.begin_op();
+        let output = self.record.try_redo(target);
+        self.end_op(old_head);
+        output
+    }
+}
+
+impl<E: Edit, S: Slot> History<E, S> {
+    /// Go to the edit in the history that was made closest to `st` without being after it.
+    ///
+    /// All branches in the history are searched.
+    /// Goes to the start of the current branch if all edits were made after `st`.
+    #[cfg(feature = "std")]
+    pub fn time_travel(&mut self, target: &mut E::Target, st: SystemTime) -> Vec<E::Output> {
+        let time = st
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        self.time_travel_to(target, time)
+    }
+
+    /// Go to the edit in the history that was made closest to `time` without being after it,
+    /// where `time` is the duration since the Unix epoch as reported by the [`Clock`](crate::Clock).
+    ///
+    /// See [`History::time_travel`] for more information.
+    pub fn time_travel_to(&mut self, target: &mut E::Target, time: Duration) -> Vec<E::Output> {
+        let at = self
+            .all_entries()
+            .filter(|(_, entry)| entry.time_of_edit() <= time)
+            .max_by_key(|(_, entry)| entry.time_of_edit())
+            .map_or(self.at(self.root, 0), |(at, _)| at);
+        self.go_to(target, at)
+    }
+}
+
+impl<E: ValidatedEdit, S: Slot> History<E, S> {
+    /// Same as [`History::edit`] but the edit is only applied and pushed if [`ValidatedEdit::validate`] succeeds.
+    ///
+    /// No events are emitted if the validation fails.
+    pub fn edit_validated(
+        &mut self,
+        target: &mut E::Target,
+        edit: E,
+    ) -> Result<E::Output, E::Error> {
+        edit.validate(target)?;
+        Ok(self.edit(target, edit))
+    }
+}
+
+impl<E: RegionEdit, S> History<E, S> {
+    /// Returns the union of the regions affected by the edits between the saved state and the current state.
+    ///
+    /// The edits on the path between the two positions in the tree are used, see [`History::diff`].
+    /// Returns `None` if the target is in the saved state. If there is no saved state
+    /// the edits from the start of the history are used.
+    pub fn dirty_region(&self) -> Option<E::Region> {
+        let saved = self.saved().unwrap_or(self.at(self.root, 0));
+        let diff = self.diff(saved, self.head())?;
+        let undo = diff.undo.into_iter().map(|(_, entry)| entry.as_ref());
+        let redo = diff.redo.into_iter().map(|(_, entry)| entry.as_ref());
+        region::union_of(undo.chain(redo))
     }
 }
 
@@ -421,21 +2534,171 @@ impl<E> Default for History<E> {
 }
 
 impl<E, S> From<Record<E, S>> for History<E, S> {
-    fn from(record: Record<E, S>) -> Self {
-        let mut branches = Slab::new();
+    fn from(mut record: Record<E, S>) -> Self {
+        let mut branches = BranchMap::new();
         let root = branches.insert(Branch::NIL);
+        let savepoints = mem::take(&mut record.savepoints)
+            .into_iter()
+            .map(|(name, index)| (name, At::new(root, index)))
+            .collect();
         History {
             root,
+            generation: 0,
             saved: None,
             record,
             branches,
+            dropped: 0,
+            savepoints,
+            trunk: Trunk::Root,
+            max_branches: None,
+            total_limit: None,
+            visits: 0,
+            reflog: RefLog::default(),
         }
     }
 }
 
+/// Histories are equal if they have the same branches, head, saved state, and savepoints.
+///
+/// The configuration, the slot, and how often the branches have been visited are not compared.
+impl<E: PartialEq, S, T> PartialEq<History<E, T>> for History<E, S> {
+    fn eq(&self, other: &History<E, T>) -> bool {
+        self.root == other.root
+            && self.saved == other.saved
+            && self.record == other.record
+            && self.branches == other.branches
+            && self.savepoints == other.savepoints
+    }
+}
+
 impl<E, F> From<History<E, F>> for Record<E, F> {
     fn from(history: History<E, F>) -> Record<E, F> {
-        history.record
+        let mut record = history.record;
+        record.savepoints = history
+            .savepoints
+            .into_iter()
+            .filter(|(_, at)| at.root == history.root)
+            .map(|(name, at)| (name, at.index))
+            .collect();
+        record
+    }
+}
+
+/// Returns the index the entries of the branch starts at, before compression.
+fn offset_of(parents: &BTreeMap<usize, At>, root: usize, id: usize) -> usize {
+    if id == root {
+        0
+    } else {
+        parents[&id].index
+    }
+}
+
+/// The entries that were undone and redone when going to another position in the history.
+///
+/// The position of an entry is the position of the head right after the entry has been applied,
+/// and is given as it is after the navigation is done.
+///
+/// See [`History::go_to_with_report`] for more information.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Navigation<O> {
+    /// The positions of the entries that were undone, in the order they were undone.
+    pub undone: Vec<At>,
+    /// The positions of the entries that were redone, in the order they were redone.
+    pub redone: Vec<At>,
+    /// The outputs of the undone and redone edits, in the order they were applied.
+    pub outputs: Vec<O>,
+}
+
+/// Collects the outputs, and the positions if needed, when going to another position.
+trait Collect<O> {
+    type Outputs: OutputCombiner<O>;
+
+    fn outputs(&mut self) -> &mut Self::Outputs;
+
+    /// Adds the positions of the undone entries in the branch.
+    fn undone(&mut self, _root: usize, _indices: impl Iterator<Item = usize>) {}
+
+    /// Adds the positions of the redone entries in the branch.
+    fn redone(&mut self, _root: usize, _indices: impl Iterator<Item = usize>) {}
+
+    /// Moves the positions below the split to the new root, the same as in `set_root`.
+    fn reroot(&mut self, _old: usize, _new: At) {}
+}
+
+/// Only collects the outputs.
+struct Outputs<C>(C);
+
+impl<O, C: OutputCombiner<O>> Collect<O> for Outputs<C> {
+    type Outputs = C;
+
+    fn outputs(&mut self) -> &mut C {
+        &mut self.0
+    }
+}
+
+impl<O> Collect<O> for Navigation<O> {
+    type Outputs = Vec<O>;
+
+    fn outputs(&mut self) -> &mut Vec<O> {
+        &mut self.outputs
+    }
+
+    fn undone(&mut self, root: usize, indices: impl Iterator<Item = usize>) {
+        self.undone.extend(indices.map(|i| At::new(root, i)));
+    }
+
+    fn redone(&mut self, root: usize, indices: impl Iterator<Item = usize>) {
+        self.redone.extend(indices.map(|i| At::new(root, i)));
+    }
+
+    fn reroot(&mut self, old: usize, new: At) {
+        self.undone
+            .iter_mut()
+            .chain(&mut self.redone)
+            .filter(|at| at.root == old && at.index <= new.index)
+            .for_each(|at| at.root = new.root);
+    }
+}
+
+impl<O> Default for Navigation<O> {
+    fn default() -> Self {
+        Navigation {
+            undone: Vec::new(),
+            redone: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+}
+
+/// The entries that differ between two histories.
+///
+/// See [`History::compare`] for more information.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Comparison {
+    /// The positions of the entries that are only in the other history.
+    pub added: Vec<At>,
+    /// The positions of the entries that are only in this history.
+    pub removed: Vec<At>,
+}
+
+/// The entries that must be undone and redone to go from one position to another.
+///
+/// The positions are given the same way as in [`Navigation`].
+/// See [`History::diff`] for more information.
+#[derive(Debug)]
+pub struct TreeDiff<'a, E> {
+    /// The entries that must be undone, in the order they would be undone.
+    pub undo: Vec<(At, &'a Entry<E>)>,
+    /// The entries that must be redone, in the order they would be redone.
+    pub redo: Vec<(At, &'a Entry<E>)>,
+}
+
+impl<E> Clone for TreeDiff<'_, E> {
+    fn clone(&self) -> Self {
+        TreeDiff {
+            undo: self.undo.clone(),
+            redo: self.redo.clone(),
+        }
     }
 }
 
@@ -445,12 +2708,24 @@ impl<E, F> From<History<E, F>> for Record<E, F> {
 pub struct Branch<E> {
     parent: At,
     entries: VecDeque<Entry<E>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    label: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    visited: u64,
+}
+
+impl<E: PartialEq> PartialEq for Branch<E> {
+    fn eq(&self, other: &Branch<E>) -> bool {
+        self.parent == other.parent && self.entries == other.entries && self.label == other.label
+    }
 }
 
 impl<E> Branch<E> {
     const NIL: Branch<E> = Branch {
         parent: At::NIL,
         entries: VecDeque::new(),
+        label: None,
+        visited: 0,
     };
 
     /// Returns the parent edit of the branch.
@@ -458,6 +2733,11 @@ impl<E> Branch<E> {
         self.parent
     }
 
+    /// Returns the label of the branch.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
     /// Returns the number of edits in the branch.
     pub fn len(&self) -> usize {
         self.entries.len()
@@ -0,0 +1,141 @@
+use super::Branch;
+use alloc::collections::BTreeMap;
+use core::mem;
+use core::ops::{Index, IndexMut};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The branches of a history by their id.
+///
+/// Unlike a slab the ids of removed branches are not given to new branches
+/// until the map is compacted, so positions that refer to a removed branch
+/// stay invalid instead of silently referring to another branch.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Debug)]
+pub(crate) struct BranchMap<E> {
+    map: BTreeMap<usize, Branch<E>>,
+    /// The lowest id that has not been used.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    next: usize,
+}
+
+impl<E> BranchMap<E> {
+    pub const fn new() -> Self {
+        BranchMap {
+            map: BTreeMap::new(),
+            next: 0,
+        }
+    }
+
+    /// Inserts the branch with a new id, returning the id.
+    pub fn insert(&mut self, branch: Branch<E>) -> usize {
+        let id = self.next_id();
+        self.next = id + 1;
+        self.map.insert(id, branch);
+        id
+    }
+
+    /// Returns the id that is given to the next inserted branch.
+    pub fn next_id(&self) -> usize {
+        // The ids in deserialized maps have not been counted.
+        let last = self.map.last_key_value().map_or(0, |(&id, _)| id + 1);
+        self.next.max(last)
+    }
+
+    /// Gives the branches the ids from `0` in the order of their old ids,
+    /// and allows the unused ids to be given to new branches.
+    ///
+    /// Returns the new id of every branch by its old id.
+    pub fn compact(&mut self) -> BTreeMap<usize, usize> {
+        let map = mem::take(&mut self.map);
+        let ids = map
+            .keys()
+            .enumerate()
+            .map(|(new, &old)| (old, new))
+            .collect();
+        self.map = map.into_values().enumerate().collect();
+        self.next = self.map.len();
+        ids
+    }
+
+    /// Removes the branch with the id.
+    ///
+    /// # Panics
+    /// Panics if the branch does not exist.
+    pub fn remove(&mut self, id: usize) -> Branch<E> {
+        self.map.remove(&id).expect("invalid branch id")
+    }
+
+    /// Removes all branches without allowing their ids to be reused.
+    pub fn clear(&mut self) {
+        if let Some((&id, _)) = self.map.last_key_value() {
+            self.next = self.next.max(id + 1);
+        }
+        self.map.clear();
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        self.map.contains_key(&id)
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Branch<E>> {
+        self.map.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Branch<E>> {
+        self.map.get_mut(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (usize, &Branch<E>)> + ExactSizeIterator {
+        self.map.iter().map(|(&id, branch)| (id, branch))
+    }
+
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = (usize, &mut Branch<E>)> + ExactSizeIterator {
+        self.map.iter_mut().map(|(&id, branch)| (id, branch))
+    }
+}
+
+impl<E: PartialEq> PartialEq for BranchMap<E> {
+    fn eq(&self, other: &BranchMap<E>) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<E> Index<usize> for BranchMap<E> {
+    type Output = Branch<E>;
+
+    fn index(&self, id: usize) -> &Branch<E> {
+        &self.map[&id]
+    }
+}
+
+impl<E> IndexMut<usize> for BranchMap<E> {
+    fn index_mut(&mut self, id: usize) -> &mut Branch<E> {
+        self.map.get_mut(&id).expect("invalid branch id")
+    }
+}
+
+impl<E> IntoIterator for BranchMap<E> {
+    type Item = (usize, Branch<E>);
+    type IntoIter = alloc::collections::btree_map::IntoIter<usize, Branch<E>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter()
+    }
+}
+
+impl<E> FromIterator<(usize, Branch<E>)> for BranchMap<E> {
+    fn from_iter<I: IntoIterator<Item = (usize, Branch<E>)>>(iter: I) -> Self {
+        BranchMap {
+            map: iter.into_iter().collect(),
+            next: 0,
+        }
+    }
+}
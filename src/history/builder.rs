@@ -1,5 +1,10 @@
+use super::Trunk;
+#[cfg(feature = "compress")]
+use crate::compress::{Compressed, Compressor};
 use crate::record::Builder as RecordBuilder;
-use crate::History;
+use crate::{Clock, EditHook, History, MergePolicy, SizedEdit, TimestampMode};
+#[cfg(feature = "compress")]
+use serde::{de::DeserializeOwned, Serialize};
 
 /// Builder for a [`History`].
 ///
@@ -15,12 +20,28 @@ use crate::History;
 /// # history.edit(&mut target, Add('a'));
 /// ```
 #[derive(Debug)]
-pub struct Builder<E, S = ()>(RecordBuilder<E, S>);
+pub struct Builder<E, S = ()> {
+    record: RecordBuilder<E, S>,
+    auto_promote: bool,
+    max_branches: Option<usize>,
+    total_limit: Option<usize>,
+    reflog: usize,
+}
+
+impl<E> Builder<E> {
+    /// Returns a new builder without a slot.
+    pub fn new() -> Builder<E> {
+        Builder::default()
+    }
+}
 
 impl<E, S> Builder<E, S> {
     /// Sets the capacity for the history.
     pub fn capacity(self, capacity: usize) -> Builder<E, S> {
-        Builder(self.0.capacity(capacity))
+        Builder {
+            record: self.record.capacity(capacity),
+            ..self
+        }
     }
 
     /// Sets the `limit` for the history.
@@ -28,28 +49,207 @@ impl<E, S> Builder<E, S> {
     /// # Panics
     /// Panics if `limit` is `0`.
     pub fn limit(self, limit: usize) -> Builder<E, S> {
-        Builder(self.0.limit(limit))
+        Builder {
+            record: self.record.limit(limit),
+            ..self
+        }
     }
 
     /// Sets if the target is initially in a saved state.
     /// By default the target is in a saved state.
     pub fn saved(self, saved: bool) -> Builder<E, S> {
-        Builder(self.0.saved(saved))
+        Builder {
+            record: self.record.saved(saved),
+            ..self
+        }
+    }
+
+    /// Sets the policy that decides if edits are allowed to merge.
+    /// By default the edits are always allowed to merge.
+    pub fn merge_policy(self, merge_policy: MergePolicy<E>) -> Builder<E, S> {
+        Builder {
+            record: self.record.merge_policy(merge_policy),
+            ..self
+        }
+    }
+
+    /// Sets the maximum estimated memory used by the edits in the current branch, in bytes.
+    ///
+    /// See [`RecordBuilder::memory_limit`] for more information.
+    pub fn memory_limit(self, bytes: usize) -> Builder<E, S>
+    where
+        E: SizedEdit,
+    {
+        Builder {
+            record: self.record.memory_limit(bytes),
+            ..self
+        }
+    }
+
+    /// Sets if annulments should cascade to the entries below.
+    ///
+    /// See [`RecordBuilder::cascade_annul`] for more information.
+    pub fn cascade_annul(self, cascade_annul: bool) -> Builder<E, S> {
+        Builder {
+            record: self.record.cascade_annul(cascade_annul),
+            ..self
+        }
+    }
+
+    /// Sets if the events of each operation should be emitted as a single [`Event::Batch`](crate::Event::Batch).
+    ///
+    /// See [`RecordBuilder::coalesce_events`] for more information.
+    pub fn coalesce_events(self, coalesce_events: bool) -> Builder<E, S> {
+        Builder {
+            record: self.record.coalesce_events(coalesce_events),
+            ..self
+        }
+    }
+
+    /// Sets if [`History::undo`] can undo past barriers (off by default).
+    ///
+    /// See [`History::push_barrier`] for more information.
+    pub fn undo_through_barriers(self, on: bool) -> Builder<E, S> {
+        Builder {
+            record: self.record.undo_through_barriers(on),
+            ..self
+        }
+    }
+
+    /// Sets if the limit is allowed to remove the saved state (off by default).
+    ///
+    /// Only protects the saved state in the current root branch.
+    /// See [`RecordBuilder::protect_saved`] for more information.
+    pub fn protect_saved(self, on: bool) -> Builder<E, S> {
+        Builder {
+            record: self.record.protect_saved(on),
+            ..self
+        }
+    }
+
+    /// Sets the clock used for the timestamps of the edits.
+    ///
+    /// See [`RecordBuilder::clock`] for more information.
+    pub fn clock(self, clock: &'static dyn Clock) -> Builder<E, S> {
+        Builder {
+            record: self.record.clock(clock),
+            ..self
+        }
+    }
+
+    /// Sets how the edits are timestamped.
+    ///
+    /// See [`RecordBuilder::timestamps`] for more information.
+    pub fn timestamps(self, mode: TimestampMode) -> Builder<E, S> {
+        Builder {
+            record: self.record.timestamps(mode),
+            ..self
+        }
+    }
+
+    /// Adds a hook that is called before and after the edits are applied, undone, and redone.
+    ///
+    /// See [`RecordBuilder::with_hook`] for more information.
+    pub fn with_hook(self, hook: impl EditHook<E> + 'static) -> Builder<E, S> {
+        Builder {
+            record: self.record.with_hook(hook),
+            ..self
+        }
     }
 
     /// Connects the slot.
-    pub fn connect(self, slot: S) -> Builder<E, S> {
-        Builder(self.0.connect(slot))
+    pub fn connect<T>(self, slot: T) -> Builder<E, T> {
+        Builder {
+            record: self.record.connect(slot),
+            auto_promote: self.auto_promote,
+            max_branches: self.max_branches,
+            total_limit: self.total_limit,
+            reflog: self.reflog,
+        }
+    }
+
+    /// Sets if the current branch is made the trunk every time the history switches branch.
+    /// By default the current branch is always the trunk.
+    ///
+    /// See [`History::promote_branch`] for more information.
+    pub fn auto_promote(self, auto_promote: bool) -> Builder<E, S> {
+        Builder {
+            auto_promote,
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of branches that are kept besides the current root branch.
+    /// By default there is no maximum.
+    ///
+    /// See [`History::set_max_branches`] for more information.
+    pub fn max_branches(self, max_branches: usize) -> Builder<E, S> {
+        Builder {
+            max_branches: Some(max_branches),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of entries that are kept in all branches.
+    /// By default there is no maximum.
+    ///
+    /// See [`History::set_total_limit`] for more information.
+    pub fn total_limit(self, total_limit: usize) -> Builder<E, S> {
+        Builder {
+            total_limit: Some(total_limit),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of head movements that are kept in the reflog.
+    /// By default the reflog is disabled.
+    ///
+    /// See [`History::reflog`] for more information.
+    pub fn reflog(self, limit: usize) -> Builder<E, S> {
+        Builder {
+            reflog: limit,
+            ..self
+        }
     }
 
     /// Builds the history.
     pub fn build(self) -> History<E, S> {
-        History::from(self.0.build())
+        let mut history = History::from(self.record.build());
+        history.max_branches = self.max_branches;
+        history.total_limit = self.total_limit;
+        history.set_reflog_limit(self.reflog);
+        if !self.auto_promote {
+            history.trunk = Trunk::Pinned(None);
+        }
+        history
+    }
+}
+
+#[cfg(feature = "compress")]
+impl<T, C, S> Builder<Compressed<T, C>, S>
+where
+    T: Serialize + DeserializeOwned,
+    C: Compressor,
+{
+    /// Compresses the edits in the current branch that are more than `n` positions behind the head.
+    ///
+    /// See [`RecordBuilder::compress_after`] for more information.
+    pub fn compress_after(self, n: usize) -> Builder<Compressed<T, C>, S> {
+        Builder {
+            record: self.record.compress_after(n),
+            ..self
+        }
     }
 }
 
 impl<E, S> Default for Builder<E, S> {
     fn default() -> Self {
-        Builder(RecordBuilder::default())
+        Builder {
+            record: RecordBuilder::default(),
+            auto_promote: true,
+            max_branches: None,
+            total_limit: None,
+            reflog: 0,
+        }
     }
 }
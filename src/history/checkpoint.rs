@@ -1,18 +1,66 @@
-use crate::{At, Edit, History, Slot};
+use crate::{At, Edit, Entry, Event, History, Slot};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
 use alloc::vec::Vec;
+use core::mem;
 
 #[derive(Debug)]
 enum CheckpointEntry {
     Edit(usize),
+    GoTo { head: At, roots: Vec<usize> },
     Undo,
     Redo,
 }
 
 /// Wraps a [`History`] and gives it checkpoint functionality.
+///
+/// When the checkpoint is cancelled the history is restored to the same tree
+/// it had when the checkpoint was created, including branches, the saved state,
+/// and savepoints. Edits applied through the checkpoint are never merged with the
+/// previous entries, so they can always be removed again, but entries that were
+/// dropped because of the limit while the checkpoint was active can not be restored.
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, History};
+/// let mut target = String::new();
+/// let mut history = History::new();
+/// history.edit(&mut target, Add('a'));
+/// history.edit(&mut target, Add('b'));
+/// let before = history.display().detailed(false).to_string();
+///
+/// let mut checkpoint = history.checkpoint();
+/// checkpoint.undo(&mut target);
+/// checkpoint.edit(&mut target, Add('c'));
+/// assert_eq!(target, "ac");
+/// checkpoint.cancel(&mut target);
+///
+/// assert_eq!(target, "ab");
+/// assert_eq!(history.display().detailed(false).to_string(), before);
+/// ```
 #[derive(Debug)]
 pub struct Checkpoint<'a, E, S> {
     history: &'a mut History<E, S>,
     entries: Vec<CheckpointEntry>,
+    saved: Option<At>,
+    record_saved: Option<usize>,
+    savepoints: BTreeMap<String, At>,
+    start: At,
+    parent: Option<&'a mut Vec<CheckpointEntry>>,
+}
+
+impl<'a, E, S> Checkpoint<'a, E, S> {
+    fn new(history: &'a mut History<E, S>, parent: Option<&'a mut Vec<CheckpointEntry>>) -> Self {
+        Checkpoint {
+            saved: history.saved,
+            record_saved: history.record.saved,
+            savepoints: history.savepoints.clone(),
+            start: history.head(),
+            history,
+            entries: Vec::new(),
+            parent,
+        }
+    }
 }
 
 impl<E, S> Checkpoint<'_, E, S> {
@@ -24,64 +72,159 @@ impl<E, S> Checkpoint<'_, E, S> {
         self.entries.reserve(additional);
     }
 
+    /// Returns a nested checkpoint.
+    ///
+    /// The changes done in the nested checkpoint become part of this checkpoint when it is committed.
+    pub fn checkpoint(&mut self) -> Checkpoint<'_, E, S> {
+        Checkpoint::new(self.history, Some(&mut self.entries))
+    }
+
     /// Commits the changes and consumes the checkpoint.
     pub fn commit(self) {}
+
+    /// Commits the changes and joins the entries that were applied during the checkpoint
+    /// into a single group with the display text, so they are undone and redone as one edit.
+    ///
+    /// The joined entries are the ones between the current head and the closest position it
+    /// has in common with the head when the checkpoint was created. Does nothing more than
+    /// [`Checkpoint::commit`] if there are none.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// let mut checkpoint = history.checkpoint();
+    /// checkpoint.undo(&mut target);
+    /// checkpoint.edit(&mut target, Add('b'));
+    /// checkpoint.edit(&mut target, Add('c'));
+    /// checkpoint.commit_as_single("Add bc");
+    ///
+    /// assert_eq!(history.undo_string().as_deref(), Some("Add bc"));
+    /// history.undo(&mut target);
+    /// assert_eq!(target, "");
+    /// ```
+    pub fn commit_as_single(self, text: impl Into<String>) {
+        let end = self.history.head();
+        let Some(diff) = self.history.diff(self.start, end) else {
+            return;
+        };
+        if !diff.redo.is_empty() {
+            let start = end.index - diff.redo.len();
+            self.history
+                .record
+                .join_range(start, end.index, text.into());
+        }
+    }
 }
 
 impl<E: Edit, S: Slot> Checkpoint<'_, E, S> {
-    /// Calls the [`History::edit`] method.
+    /// Calls the [`History::edit`] method, but the edit is never merged with the previous entry.
     pub fn edit(&mut self, target: &mut E::Target, edit: E) -> E::Output {
         self.entries.push(CheckpointEntry::Edit(self.history.root));
-        self.history.edit(target, edit)
+        let mut entry = Entry::new(edit);
+        let output =
+            self.history
+                .record
+                .hooks
+                .edit(&mut entry, target, self.history.record.clock.now());
+        self.history.push(entry, false);
+        output
     }
 
     /// Calls the [`History::undo`] method.
     pub fn undo(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        let output = self.history.undo(target)?;
         self.entries.push(CheckpointEntry::Undo);
-        self.history.undo(target)
+        Some(output)
     }
 
     /// Calls the [`History::redo`] method.
     pub fn redo(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        let output = self.history.redo(target)?;
         self.entries.push(CheckpointEntry::Redo);
-        self.history.redo(target)
+        Some(output)
+    }
+
+    /// Calls the [`History::go_to`] method.
+    pub fn go_to(&mut self, target: &mut E::Target, at: At) -> Vec<E::Output> {
+        // Store the roots the history switches between so they can be switched back one by one.
+        let head = self.history.head();
+        let mut roots = Vec::new();
+        let mut id = at.root;
+        while let Some(branch) = self.history.branches.get(id).filter(|_| id != head.root) {
+            id = branch.parent.root;
+            roots.push(id);
+        }
+        roots.reverse();
+
+        let outputs = self.history.go_to(target, at);
+        if self.history.head() != head {
+            self.entries.push(CheckpointEntry::GoTo { head, roots });
+        }
+        outputs
     }
 
     /// Cancels the changes and consumes the checkpoint.
-    pub fn cancel(self, target: &mut E::Target) -> Vec<E::Output> {
-        self.entries
-            .into_iter()
-            .rev()
-            .filter_map(|entry| match entry {
+    pub fn cancel(mut self, target: &mut E::Target) -> Vec<E::Output> {
+        self.history.record.socket.begin_op();
+        let mut outputs = Vec::new();
+        for entry in mem::take(&mut self.entries).into_iter().rev() {
+            match entry {
                 CheckpointEntry::Edit(root) => {
-                    let output = self.history.undo(target)?;
-                    if self.history.root == root {
-                        self.history.record.entries.pop_back();
-                    } else {
-                        // If a new root was created when we edited earlier,
-                        // we remove it and append the entries to the previous root.
-                        let mut branch = self.history.branches.remove(root);
-                        debug_assert_eq!(branch.parent, self.history.head());
-
-                        let new = At::new(root, self.history.record.head());
-                        let (_, rm_saved) = self.history.record.rm_tail();
-                        self.history.record.entries.append(&mut branch.entries);
-                        self.history.set_root(new, rm_saved);
+                    let old_head = self.history.head();
+                    let Some(output) = self.history.record.undo_one(target) else {
+                        continue;
+                    };
+                    self.history.emit_head(old_head);
+                    self.history.record.entries.pop_back();
+                    // If a new root was created when we edited earlier,
+                    // we remove it and move the entries back to the previous root.
+                    if self.history.root != root {
+                        self.history.unsplit(root);
                     }
-                    Some(output)
+                    outputs.push(output);
                 }
-                CheckpointEntry::Undo => self.history.redo(target),
-                CheckpointEntry::Redo => self.history.undo(target),
-            })
-            .collect()
+                CheckpointEntry::GoTo { head, roots } => {
+                    for root in roots.into_iter().rev() {
+                        if let Some(branch) = self.history.branches.get(root) {
+                            let at = self.history.at(root, branch.parent.index + 1);
+                            outputs.append(&mut self.history.go_to(target, at));
+                        }
+                    }
+                    outputs.append(&mut self.history.go_to(target, head));
+                }
+                CheckpointEntry::Undo => outputs.extend(self.history.redo(target)),
+                CheckpointEntry::Redo => outputs.extend(self.history.undo(target)),
+            }
+        }
+
+        let was_saved = self.history.is_saved();
+        self.history.saved = self.saved;
+        self.history.record.saved = self.record_saved;
+        self.history.savepoints = mem::take(&mut self.savepoints);
+        let is_saved = self.history.is_saved();
+        self.history
+            .record
+            .socket
+            .emit_if(was_saved != is_saved, || Event::Saved(is_saved));
+        self.history.record.socket.end_op();
+        outputs
+    }
+}
+
+impl<E, S> Drop for Checkpoint<'_, E, S> {
+    fn drop(&mut self) {
+        // Pass the changes on to the parent checkpoint so they are cancelled with it.
+        if let Some(parent) = &mut self.parent {
+            parent.append(&mut self.entries);
+        }
     }
 }
 
 impl<'a, E, S> From<&'a mut History<E, S>> for Checkpoint<'a, E, S> {
     fn from(history: &'a mut History<E, S>) -> Self {
-        Checkpoint {
-            history,
-            entries: Vec::new(),
-        }
+        Checkpoint::new(history, None)
     }
 }
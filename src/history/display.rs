@@ -1,5 +1,9 @@
+use crate::format::Window;
 use crate::{At, Entry, Format, History};
+use alloc::string::ToString;
+use alloc::vec::Vec;
 use core::fmt::{self, Write};
+use core::ops::RangeBounds;
 #[cfg(feature = "std")]
 use std::time::SystemTime;
 
@@ -7,6 +11,9 @@ use std::time::SystemTime;
 pub struct Display<'a, E, S> {
     history: &'a History<E, S>,
     format: Format,
+    window: Window,
+    mode: Mode,
+    stable_ids: bool,
     #[cfg(feature = "std")]
     st_fmt: &'a dyn Fn(SystemTime, SystemTime) -> String,
 }
@@ -39,6 +46,90 @@ impl<'a, E, S> Display<'a, E, S> {
         self
     }
 
+    /// Show only the positions in the range (all by default).
+    ///
+    /// The range is the indices of the positions in the trunk, where index `0` is the state
+    /// before the first edit. When the range, [`Display::reverse`], or [`Display::limit`]
+    /// is used, only the positions in the trunk are shown, without the other branches.
+    pub fn range(&mut self, range: impl RangeBounds<usize>) -> &mut Self {
+        self.window.start = range.start_bound().cloned();
+        self.window.end = range.end_bound().cloned();
+        self
+    }
+
+    /// Show the oldest positions first (off by default).
+    pub fn reverse(&mut self, on: bool) -> &mut Self {
+        self.window.reverse = on;
+        self
+    }
+
+    /// Show at most `n` positions, keeping the most recent ones (all by default).
+    pub fn limit(&mut self, n: usize) -> &mut Self {
+        self.window.limit = Some(n);
+        self
+    }
+
+    /// Show the position each edit had when it was made instead of its current position (off by default).
+    ///
+    /// When the history switches branches the edits are moved to other branches,
+    /// which changes their current position. This only changes the labels in the output,
+    /// so they stay the same when switching branches. The positions used by the history,
+    /// like the one returned by [`History::head`](crate::History::head), still change.
+    pub fn stable_ids(&mut self, on: bool) -> &mut Self {
+        self.stable_ids = on;
+        self
+    }
+
+    /// Show the history as a [DOT](https://graphviz.org/doc/info/lang.html) graph (off by default).
+    ///
+    /// Every position is a node labeled with its edit, and every edge goes
+    /// from the parent position to the child position.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    ///
+    /// let dot = history.display().dot(true).to_string();
+    /// assert!(dot.starts_with("digraph {"));
+    /// assert!(dot.contains("\"0-0\" -> \"0-1\";"));
+    /// ```
+    pub fn dot(&mut self, on: bool) -> &mut Self {
+        self.set_mode(Mode::Dot, on);
+        self
+    }
+
+    /// Show the history as a JSON object (off by default).
+    ///
+    /// The object contains the `head` and `saved` positions and a flat list of `nodes`,
+    /// where each node has its position, the position of its parent, and its edit.
+    /// Positions are objects with a `root` and an `index`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, History};
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    ///
+    /// let json = history.display().json(true).to_string();
+    /// assert!(json.contains(r#"{"at":{"root":0,"index":1},"parent":{"root":0,"index":0},"edit":"Add 'a'"}"#));
+    /// ```
+    pub fn json(&mut self, on: bool) -> &mut Self {
+        self.set_mode(Mode::Json, on);
+        self
+    }
+
+    fn set_mode(&mut self, mode: Mode, on: bool) {
+        if on {
+            self.mode = mode;
+        } else if self.mode == mode {
+            self.mode = Mode::Text;
+        }
+    }
+
     /// Sets the format used to display [`SystemTime`]s.
     ///
     /// The first input parameter is the current system time.
@@ -51,9 +142,122 @@ impl<'a, E, S> Display<'a, E, S> {
         self.st_fmt = st_fmt;
         self
     }
+
+    /// Show the local time of the edits instead of the time since they were made (off by default).
+    ///
+    /// The times are formatted as `14:02:55`. Use [`Display::set_st_fmt`] for other formats.
+    ///
+    /// Requires the `chrono` feature to be enabled.
+    #[cfg(feature = "chrono")]
+    pub fn absolute_time(&mut self, on: bool) -> &mut Self {
+        self.st_fmt = if on {
+            &crate::format::absolute_st_fmt
+        } else {
+            &crate::format::default_st_fmt
+        };
+        self
+    }
 }
 
 impl<E: fmt::Display, S> Display<'_, E, S> {
+    fn id(&self, at: At, entry: Option<&Entry<E>>) -> At {
+        if self.stable_ids {
+            entry.map_or(At::NIL, |e| e.label.unwrap_or(at))
+        } else {
+            at
+        }
+    }
+
+    /// Returns all positions in the tree.
+    fn nodes(&self) -> Vec<Node<'_, E>> {
+        let start = self.history.at(self.history.root, 0);
+        let mut nodes = alloc::vec![(start, None, None)];
+        for (at, entry) in self.history.all_entries() {
+            let parent = self.history.parent_of(at).unwrap_or(start);
+            let parent = self.id(parent, self.history.entry_at(parent));
+            nodes.push((self.id(at, Some(entry)), Some(parent), Some(entry)));
+        }
+        nodes
+    }
+
+    fn fmt_dot(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let head = self.history.head();
+        let head = self.id(head, self.history.entry_at(head));
+        let saved = self
+            .history
+            .saved()
+            .map(|saved| self.id(saved, self.history.entry_at(saved)));
+
+        writeln!(f, "digraph {{")?;
+        let nodes = self.nodes();
+        for &(at, _, entry) in &nodes {
+            write!(f, "    \"{}-{}\" [label=", at.root, at.index)?;
+            match entry {
+                Some(entry) => escape(f, &entry.to_string())?,
+                None => f.write_str("\"\"")?,
+            }
+            if self.format.head && at == head {
+                f.write_str(", style=bold")?;
+            }
+            if self.format.saved && Some(at) == saved {
+                f.write_str(", peripheries=2")?;
+            }
+            writeln!(f, "];")?;
+        }
+        for &(at, parent, _) in &nodes {
+            if let Some(parent) = parent {
+                writeln!(
+                    f,
+                    "    \"{}-{}\" -> \"{}-{}\";",
+                    parent.root, parent.index, at.root, at.index
+                )?;
+            }
+        }
+        writeln!(f, "}}")
+    }
+
+    fn fmt_json(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let json_at = |f: &mut fmt::Formatter, at: At| {
+            write!(f, r#"{{"root":{},"index":{}}}"#, at.root, at.index)
+        };
+
+        f.write_char('{')?;
+        if self.format.head {
+            let head = self.history.head();
+            f.write_str(r#""head":"#)?;
+            json_at(f, self.id(head, self.history.entry_at(head)))?;
+            f.write_char(',')?;
+        }
+        if self.format.saved {
+            f.write_str(r#""saved":"#)?;
+            match self.history.saved() {
+                Some(saved) => json_at(f, self.id(saved, self.history.entry_at(saved)))?,
+                None => f.write_str("null")?,
+            }
+            f.write_char(',')?;
+        }
+        f.write_str(r#""nodes":["#)?;
+        for (i, (at, parent, entry)) in self.nodes().into_iter().enumerate() {
+            if i > 0 {
+                f.write_char(',')?;
+            }
+            f.write_str(r#"{"at":"#)?;
+            json_at(f, at)?;
+            f.write_str(r#","parent":"#)?;
+            match parent {
+                Some(parent) => json_at(f, parent)?,
+                None => f.write_str("null")?,
+            }
+            f.write_str(r#","edit":"#)?;
+            match entry {
+                Some(entry) => escape(f, &entry.to_string())?,
+                None => f.write_str("null")?,
+            }
+            f.write_char('}')?;
+        }
+        f.write_str("]}")
+    }
+
     fn fmt_list(
         &self,
         f: &mut fmt::Formatter,
@@ -63,7 +267,7 @@ impl<E: fmt::Display, S> Display<'_, E, S> {
         #[cfg(feature = "std")] now: SystemTime,
     ) -> fmt::Result {
         self.format.mark(f, level)?;
-        self.format.at(f, at)?;
+        self.format.at(f, self.id(at, entry))?;
 
         #[cfg(feature = "std")]
         if let Some(entry) = entry {
@@ -77,6 +281,17 @@ impl<E: fmt::Display, S> Display<'_, E, S> {
         self.format
             .labels(f, at, self.history.head(), self.history.saved())?;
 
+        // The label of a branch is shown at the end of the branch.
+        let is_tip = || {
+            self.history
+                .children_of(at)
+                .iter()
+                .all(|c| c.root != at.root)
+        };
+        if let Some(label) = self.history.branch_label(at.root).filter(|_| is_tip()) {
+            self.format.branch(f, label)?;
+        }
+
         if let Some(entry) = entry {
             if self.format.detailed {
                 writeln!(f)?;
@@ -94,21 +309,31 @@ impl<E: fmt::Display, S> Display<'_, E, S> {
         &self,
         f: &mut fmt::Formatter,
         at: At,
-        entry: Option<&Entry<E>>,
+        next: Option<At>,
         level: usize,
         #[cfg(feature = "std")] now: SystemTime,
     ) -> fmt::Result {
-        for (i, branch) in self
-            .history
-            .branches()
-            .filter(|(_, branch)| branch.parent == at)
-        {
-            for (j, entry) in branch.entries.iter().enumerate().rev() {
-                let at = At::new(i, j + branch.parent.index + 1);
+        // All children except the next position in the line are shown as branches.
+        for child in self.history.children_of(at) {
+            if Some(child) == next {
+                continue;
+            }
+
+            // Follow the child until the end of its branch.
+            let mut line = alloc::vec![child];
+            let mut last = child;
+            while let Some(&more) = self.history.children_of(last).first() {
+                if more.root != child.root {
+                    break;
+                }
+                line.push(more);
+                last = more;
+            }
+            for (k, &at) in line.iter().enumerate().rev() {
                 self.fmt_graph(
                     f,
                     at,
-                    Some(entry),
+                    line.get(k + 1).copied(),
                     level + 1,
                     #[cfg(feature = "std")]
                     now,
@@ -132,7 +357,7 @@ impl<E: fmt::Display, S> Display<'_, E, S> {
         self.fmt_list(
             f,
             at,
-            entry,
+            self.history.entry_at(at),
             level,
             #[cfg(feature = "std")]
             now,
@@ -145,6 +370,9 @@ impl<'a, E, S> From<&'a History<E, S>> for Display<'a, E, S> {
         Display {
             history,
             format: Format::default(),
+            window: Window::default(),
+            mode: Mode::Text,
+            stable_ids: false,
             #[cfg(feature = "std")]
             st_fmt: &crate::format::default_st_fmt,
         }
@@ -153,27 +381,68 @@ impl<'a, E, S> From<&'a History<E, S>> for Display<'a, E, S> {
 
 impl<E: fmt::Display, S> fmt::Display for Display<'_, E, S> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mode {
+            Mode::Text => {}
+            Mode::Dot => return self.fmt_dot(f),
+            Mode::Json => return self.fmt_json(f),
+        }
+
         #[cfg(feature = "std")]
-        let now = SystemTime::now();
-        let root = self.history.root;
-        for (i, entry) in self.history.record.entries.iter().enumerate().rev() {
-            let at = At::new(root, i + 1);
+        let now = self.history.record.clock.now_st();
+        // The trunk is shown as the main line.
+        let start = self.history.at(self.history.root, 0);
+        let trunk = self.history.path_between(start, self.history.trunk());
+        if !self.window.is_full() {
+            for i in self.window.positions(trunk.len() - 1) {
+                let at = trunk[i];
+                self.fmt_list(
+                    f,
+                    at,
+                    self.history.entry_at(at),
+                    0,
+                    #[cfg(feature = "std")]
+                    now,
+                )?;
+            }
+            return Ok(());
+        }
+        for (i, &at) in trunk.iter().enumerate().rev() {
             self.fmt_graph(
                 f,
                 at,
-                Some(entry),
+                trunk.get(i + 1).copied(),
                 0,
                 #[cfg(feature = "std")]
                 now,
             )?;
         }
-        self.fmt_graph(
-            f,
-            At::new(root, 0),
-            None,
-            0,
-            #[cfg(feature = "std")]
-            now,
-        )
+        Ok(())
+    }
+}
+
+/// A position with its parent and entry.
+type Node<'a, E> = (At, Option<At>, Option<&'a Entry<E>>);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Mode {
+    Text,
+    Dot,
+    Json,
+}
+
+/// Writes the string as a quoted and escaped string that is valid in both DOT and JSON.
+fn escape(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
     }
+    f.write_char('"')
 }
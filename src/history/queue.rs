@@ -62,14 +62,18 @@ impl<E, S> Queue<'_, E, S> {
 impl<E: Edit, S: Slot> Queue<'_, E, S> {
     /// Applies the queued edits.
     pub fn commit(self, target: &mut E::Target) -> Vec<E::Output> {
-        self.entries
+        self.history.record.socket.begin_op();
+        let outputs = self
+            .entries
             .into_iter()
             .filter_map(|entry| match entry {
                 QueueEntry::Edit(edit) => Some(self.history.edit(target, edit)),
                 QueueEntry::Undo => self.history.undo(target),
                 QueueEntry::Redo => self.history.redo(target),
             })
-            .collect()
+            .collect();
+        self.history.record.socket.end_op();
+        outputs
     }
 }
 
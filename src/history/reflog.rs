@@ -0,0 +1,150 @@
+use super::History;
+use crate::At;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+/// A movement of the head in a [`History`].
+///
+/// See [`History::reflog`] for more information.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Movement {
+    /// The position the head moved from, or `None` if the position has been removed.
+    pub from: Option<At>,
+    /// The position the head moved to, or `None` if the position has been removed.
+    pub to: Option<At>,
+    /// The time of the movement, as the duration since the Unix epoch.
+    pub time: Duration,
+}
+
+/// The movements of the head, identified by the labels of the entries.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RefLog {
+    moves: VecDeque<(Mark, Mark, Duration)>,
+    limit: usize,
+    /// The head after the last movement.
+    head: Mark,
+}
+
+/// A position of the head in the reflog.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+enum Mark {
+    /// The start of the history.
+    #[default]
+    Start,
+    /// The entry with the label.
+    Label(At),
+    /// An entry that has been removed and whose label might be given to another entry.
+    Removed,
+}
+
+impl From<Option<At>> for Mark {
+    fn from(label: Option<At>) -> Mark {
+        label.map_or(Mark::Start, Mark::Label)
+    }
+}
+
+impl<E, S> History<E, S> {
+    /// Returns the movements of the head, from the oldest to the newest.
+    ///
+    /// Every operation that moves the head, like edits, undos, redos, and going to
+    /// another branch, is logged when the reflog is enabled with
+    /// [`history::Builder::reflog`](super::Builder::reflog) or [`History::set_reflog_limit`].
+    /// The positions are given as they are now, even if the branches have been switched since.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, At, History};
+    /// let mut target = String::new();
+    /// let mut history = History::builder().reflog(10).build();
+    /// history.edit(&mut target, Add('a'));
+    /// history.edit(&mut target, Add('b'));
+    /// let ab = history.head();
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('c'));
+    ///
+    /// let last = history.reflog().last().unwrap();
+    /// assert_eq!(last.to, Some(history.head()));
+    /// history.go_to(&mut target, ab);
+    /// let last = history.reflog().last().unwrap();
+    /// assert_eq!(last.to, Some(ab));
+    /// assert_eq!(history.reflog().count(), 5);
+    /// ```
+    pub fn reflog(&self) -> impl DoubleEndedIterator<Item = Movement> + ExactSizeIterator + '_ {
+        let start = self.at(self.root, 0);
+        let labels: Vec<_> = self
+            .all_entries()
+            .map(|(at, entry)| (entry.label.unwrap_or(at), at))
+            .collect();
+        let position = move |mark: Mark| match mark {
+            Mark::Start => Some(start),
+            Mark::Label(label) => labels.iter().find(|&&(l, _)| l == label).map(|&(_, at)| at),
+            Mark::Removed => None,
+        };
+        self.reflog
+            .moves
+            .iter()
+            .map(move |&(from, to, time)| Movement {
+                from: position(from),
+                to: position(to),
+                time,
+            })
+    }
+
+    /// Returns the maximum number of movements in the reflog, `0` if it is disabled.
+    pub fn reflog_limit(&self) -> usize {
+        self.reflog.limit
+    }
+
+    /// Sets the maximum number of movements in the reflog, removing the oldest movements if needed.
+    ///
+    /// Setting the limit to `0` disables the reflog.
+    pub fn set_reflog_limit(&mut self, limit: usize) {
+        if self.reflog.limit == 0 {
+            self.reflog.head = self.head_label().into();
+        }
+        self.reflog.limit = limit;
+        let excess = self.reflog.moves.len().saturating_sub(limit);
+        self.reflog.moves.drain(..excess);
+    }
+
+    /// Adds a movement to the reflog if the head has moved since the last movement.
+    pub(super) fn log_head(&mut self) {
+        if self.reflog.limit == 0 {
+            return;
+        }
+        let head = self.head_label().into();
+        if head == self.reflog.head {
+            return;
+        }
+        let time = self.record.clock.now();
+        self.reflog.moves.push_back((self.reflog.head, head, time));
+        self.reflog.head = head;
+        if self.reflog.moves.len() > self.reflog.limit {
+            self.reflog.moves.pop_front();
+        }
+    }
+
+    /// Changes the labels in the reflog with `f`, marking the entries it returns `None` for as removed.
+    pub(super) fn relabel_reflog(&mut self, f: impl Fn(At) -> Option<At>) {
+        let f = |mark: &mut Mark| {
+            if let Mark::Label(label) = *mark {
+                *mark = f(label).map_or(Mark::Removed, Mark::Label);
+            }
+        };
+        for (from, to, _) in &mut self.reflog.moves {
+            f(from);
+            f(to);
+        }
+        f(&mut self.reflog.head);
+    }
+
+    /// Returns the label of the entry at the head, or `None` at the start of the history.
+    fn head_label(&self) -> Option<At> {
+        let head = self.head();
+        let index = head.index.checked_sub(1)?;
+        let entry = self.record.entries.get(index)?;
+        Some(entry.label.unwrap_or(head))
+    }
+}
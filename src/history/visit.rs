@@ -0,0 +1,121 @@
+use super::History;
+use crate::{At, Entry};
+use alloc::vec::Vec;
+
+/// Visits the positions in a [`History`] tree.
+///
+/// Can be used to draw the tree with a custom renderer instead of using the
+/// [`Display`](super::Display) structure. All methods do nothing by default.
+///
+/// See [`History::visit`] for more information.
+pub trait TreeVisitor<E> {
+    /// Called when a branch is entered, before any of its entries.
+    ///
+    /// `parent` is the position the branch splits off from.
+    /// For the current root branch this is its first position.
+    fn enter_branch(&mut self, id: usize, parent: At) {
+        let _ = (id, parent);
+    }
+
+    /// Called for every entry in the branch, in order.
+    fn entry(&mut self, at: At, entry: &Entry<E>, labels: Labels) {
+        let _ = (at, entry, labels);
+    }
+
+    /// Called when a branch is exited, after all of its entries and the branches that split off from it.
+    fn exit_branch(&mut self, id: usize) {
+        let _ = id;
+    }
+}
+
+/// The labels of a position in a [`History`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Labels<'a> {
+    /// If the position is the head.
+    pub head: bool,
+    /// If the position is the saved state.
+    pub saved: bool,
+    /// The names of the savepoints at the position.
+    pub savepoints: Vec<&'a str>,
+    /// The label of the branch, if this is the last position in the branch.
+    pub branch: Option<&'a str>,
+}
+
+impl<E, S> History<E, S> {
+    /// Visits every branch and entry in the history with the visitor.
+    ///
+    /// The tree is visited depth-first starting at the current root branch. The branches
+    /// that split off from a position are visited right after the entry at that position,
+    /// and before the next entry in the same branch.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, At, Entry, History};
+    /// # use undo::history::{Labels, TreeVisitor};
+    /// struct Outline(String);
+    ///
+    /// impl TreeVisitor<Add> for Outline {
+    ///     fn enter_branch(&mut self, _: usize, _: At) {
+    ///         self.0.push('(');
+    ///     }
+    ///
+    ///     fn entry(&mut self, _: At, entry: &Entry<Add>, labels: Labels) {
+    ///         self.0.push(entry.as_ref().0);
+    ///         if labels.head {
+    ///             self.0.push('*');
+    ///         }
+    ///     }
+    ///
+    ///     fn exit_branch(&mut self, _: usize) {
+    ///         self.0.push(')');
+    ///     }
+    /// }
+    ///
+    /// let mut target = String::new();
+    /// let mut history = History::new();
+    /// history.edit(&mut target, Add('a'));
+    /// history.edit(&mut target, Add('b'));
+    /// history.undo(&mut target);
+    /// history.edit(&mut target, Add('c'));
+    ///
+    /// let mut outline = Outline(String::new());
+    /// history.visit(&mut outline);
+    /// assert_eq!(outline.0, "(a(b)c*)");
+    /// ```
+    pub fn visit(&self, visitor: &mut impl TreeVisitor<E>) {
+        let start = self.at(self.root, 0);
+        self.visit_branch(visitor, self.root, start);
+    }
+
+    fn visit_branch(&self, visitor: &mut impl TreeVisitor<E>, id: usize, parent: At) {
+        visitor.enter_branch(id, parent);
+        let mut at = parent;
+        loop {
+            let children = self.children_of(at);
+            // The other branches at the parent belong to the parent branch.
+            let forks = children.iter().filter(|c| at.root == id && c.root != id);
+            for &child in forks {
+                self.visit_branch(visitor, child.root, at);
+            }
+            let Some(&next) = children.iter().find(|c| c.root == id) else {
+                break;
+            };
+            let entry = self.entry_at(next).unwrap();
+            let is_tip = self.children_of(next).iter().all(|c| c.root != id);
+            let labels = Labels {
+                head: next == self.head(),
+                saved: self.saved() == Some(next),
+                savepoints: self
+                    .saved_states()
+                    .filter(|&(_, sp)| self.canonical(sp) == Some(next))
+                    .map(|(name, _)| name)
+                    .collect(),
+                branch: self.branch_label(id).filter(|_| is_tip),
+            };
+            visitor.entry(next, entry, labels);
+            at = next;
+        }
+        visitor.exit_branch(id);
+    }
+}
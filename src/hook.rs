@@ -0,0 +1,153 @@
+use crate::{Edit, Entry};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+use core::time::Duration;
+
+/// Observes the edits before and after they are applied to the target.
+///
+/// Hooks are added with [`record::Builder::with_hook`](crate::record::Builder::with_hook) or
+/// [`history::Builder::with_hook`](crate::history::Builder::with_hook), and are called for
+/// every edit that is applied, undone, or redone by the structure. This allows validation,
+/// logging, and collecting the changed parts of the target without changing the edits.
+///
+/// The hooks are shared when the structure is cloned, so they take `&self`
+/// and use interior mutability to keep state.
+///
+/// # Examples
+/// ```
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// # use std::sync::Arc;
+/// # use undo::{Add, EditHook, Record};
+/// struct Count(Arc<AtomicUsize>);
+///
+/// impl EditHook<Add> for Count {
+///     fn after_edit(&self, _: &Add, _: &String, _: &()) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let count = Arc::new(AtomicUsize::new(0));
+/// let mut target = String::new();
+/// let mut record = Record::builder().with_hook(Count(count.clone())).build();
+/// record.edit(&mut target, Add('a'));
+/// record.edit(&mut target, Add('b'));
+/// record.undo(&mut target);
+/// record.redo(&mut target);
+/// assert_eq!(count.load(Ordering::Relaxed), 3);
+/// ```
+pub trait EditHook<E>: Send + Sync {
+    /// Called before the edit is applied to the target.
+    fn before_edit(&self, edit: &E, target: &E::Target)
+    where
+        E: Edit,
+    {
+        let _ = (edit, target);
+    }
+
+    /// Called after the edit has been applied to the target.
+    fn after_edit(&self, edit: &E, target: &E::Target, output: &E::Output)
+    where
+        E: Edit,
+    {
+        let _ = (edit, target, output);
+    }
+
+    /// Called before the edit is undone.
+    fn before_undo(&self, edit: &E, target: &E::Target)
+    where
+        E: Edit,
+    {
+        let _ = (edit, target);
+    }
+
+    /// Called after the edit has been undone.
+    fn after_undo(&self, edit: &E, target: &E::Target, output: &E::Output)
+    where
+        E: Edit,
+    {
+        let _ = (edit, target, output);
+    }
+
+    /// Called before the edit is redone.
+    ///
+    /// Default implementation calls [`EditHook::before_edit`].
+    fn before_redo(&self, edit: &E, target: &E::Target)
+    where
+        E: Edit,
+    {
+        self.before_edit(edit, target);
+    }
+
+    /// Called after the edit has been redone.
+    ///
+    /// Default implementation calls [`EditHook::after_edit`].
+    fn after_redo(&self, edit: &E, target: &E::Target, output: &E::Output)
+    where
+        E: Edit,
+    {
+        self.after_edit(edit, target, output);
+    }
+}
+
+/// The hooks of a structure.
+pub(crate) struct Hooks<E>(Vec<Arc<dyn EditHook<E>>>);
+
+impl<E> Hooks<E> {
+    pub fn push(&mut self, hook: impl EditHook<E> + 'static) {
+        self.0.push(Arc::new(hook));
+    }
+}
+
+impl<E: Edit> Hooks<E> {
+    pub fn edit(&self, entry: &mut Entry<E>, target: &mut E::Target, now: Duration) -> E::Output {
+        self.0
+            .iter()
+            .for_each(|h| h.before_edit(entry.as_ref(), target));
+        let output = entry.edit(target, now);
+        self.0
+            .iter()
+            .for_each(|h| h.after_edit(entry.as_ref(), target, &output));
+        output
+    }
+
+    pub fn undo(&self, entry: &mut Entry<E>, target: &mut E::Target, now: Duration) -> E::Output {
+        self.0
+            .iter()
+            .for_each(|h| h.before_undo(entry.as_ref(), target));
+        let output = entry.undo(target, now);
+        self.0
+            .iter()
+            .for_each(|h| h.after_undo(entry.as_ref(), target, &output));
+        output
+    }
+
+    pub fn redo(&self, entry: &mut Entry<E>, target: &mut E::Target, now: Duration) -> E::Output {
+        self.0
+            .iter()
+            .for_each(|h| h.before_redo(entry.as_ref(), target));
+        let output = entry.redo(target, now);
+        self.0
+            .iter()
+            .for_each(|h| h.after_redo(entry.as_ref(), target, &output));
+        output
+    }
+}
+
+impl<E> Clone for Hooks<E> {
+    fn clone(&self) -> Self {
+        Hooks(self.0.clone())
+    }
+}
+
+impl<E> Default for Hooks<E> {
+    fn default() -> Self {
+        Hooks(Vec::new())
+    }
+}
+
+impl<E> Debug for Hooks<E> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Hooks({})", self.0.len())
+    }
+}
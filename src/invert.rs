@@ -0,0 +1,96 @@
+use crate::Edit;
+use core::fmt::{self, Display, Formatter};
+
+/// An operation that can be undone by applying its inverse.
+///
+/// Use [`auto_undo`] to turn the operation into an [`Edit`] command.
+pub trait Invertible: Sized {
+    /// The target type.
+    type Target;
+    /// The output type.
+    type Output;
+
+    /// Applies the operation on the target.
+    fn apply(&mut self, target: &mut Self::Target) -> Self::Output;
+
+    /// Returns the operation that reverses this operation.
+    fn inverse(&self) -> Self;
+}
+
+/// An [`Edit`] command that is undone by applying the inverse of the operation.
+///
+/// # Examples
+/// ```
+/// # use undo::{auto_undo, Invertible, Record};
+/// struct Move(i32);
+///
+/// impl Invertible for Move {
+///     type Target = i32;
+///     type Output = ();
+///
+///     fn apply(&mut self, target: &mut i32) {
+///         *target += self.0;
+///     }
+///
+///     fn inverse(&self) -> Self {
+///         Move(-self.0)
+///     }
+/// }
+///
+/// let mut target = 0;
+/// let mut record = Record::new();
+/// record.edit(&mut target, auto_undo(Move(5)));
+/// record.edit(&mut target, auto_undo(Move(-2)));
+/// assert_eq!(target, 3);
+///
+/// record.undo(&mut target);
+/// assert_eq!(target, 5);
+/// record.undo(&mut target);
+/// assert_eq!(target, 0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Inverted<I>(I);
+
+impl<I> Inverted<I> {
+    /// Creates a new `Inverted` from the operation.
+    pub const fn new(op: I) -> Self {
+        Inverted(op)
+    }
+
+    /// Returns the operation.
+    pub fn into_inner(self) -> I {
+        self.0
+    }
+}
+
+impl<I> AsRef<I> for Inverted<I> {
+    fn as_ref(&self) -> &I {
+        &self.0
+    }
+}
+
+impl<I: Invertible> Edit for Inverted<I> {
+    type Target = I::Target;
+    type Output = I::Output;
+
+    fn edit(&mut self, target: &mut Self::Target) -> Self::Output {
+        self.0.apply(target)
+    }
+
+    fn undo(&mut self, target: &mut Self::Target) -> Self::Output {
+        self.0.inverse().apply(target)
+    }
+}
+
+impl<I: Display> Display for Inverted<I> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Creates an [`Edit`] command from the operation that is undone by applying its inverse.
+///
+/// See [`Inverted`] for more information.
+pub const fn auto_undo<I: Invertible>(op: I) -> Inverted<I> {
+    Inverted(op)
+}
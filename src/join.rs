@@ -0,0 +1,111 @@
+use crate::{Edit, Merged};
+use core::fmt::{self, Display, Formatter};
+
+/// Two [`Edit`] commands joined together.
+///
+/// Can be used to build more complex edits from simpler ones.
+/// The edits must be [`Clone`] so a join can be merged without changing it
+/// if the two edits do not merge the same way.
+/// Use the [`join!`](crate::join!) macro to join more than two edits.
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, Join, Record};
+/// let mut target = String::new();
+/// let mut record = Record::new();
+///
+/// record.edit(&mut target, Join::new(Add('a'), Add('b')));
+/// assert_eq!(target, "ab");
+///
+/// record.undo(&mut target);
+/// assert_eq!(target, "");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Join<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A, B> Join<A, B> {
+    /// Creates a new `Join` from `a` and `b`.
+    pub const fn new(a: A, b: B) -> Self {
+        Join { a, b }
+    }
+}
+
+impl<A, B> Edit for Join<A, B>
+where
+    A: Edit + Clone,
+    B: Edit<Target = A::Target> + Clone,
+{
+    type Target = A::Target;
+    type Output = (A::Output, B::Output);
+
+    fn edit(&mut self, target: &mut Self::Target) -> Self::Output {
+        let a = self.a.edit(target);
+        let b = self.b.edit(target);
+        (a, b)
+    }
+
+    fn undo(&mut self, target: &mut Self::Target) -> Self::Output {
+        let b = self.b.undo(target);
+        let a = self.a.undo(target);
+        (a, b)
+    }
+
+    fn redo(&mut self, target: &mut Self::Target) -> Self::Output {
+        let a = self.a.redo(target);
+        let b = self.b.redo(target);
+        (a, b)
+    }
+
+    /// Merges `a` with the other `a` and `b` with the other `b`.
+    ///
+    /// This requires `b` and the other `a` to be independent of each other.
+    /// The edits are merged into clones, so if `a` and `b` do not merge
+    /// the same way the join is left unchanged and the other join is returned.
+    fn merge(&mut self, other: Self) -> Merged<Self> {
+        let mut a = self.a.clone();
+        let mut b = self.b.clone();
+        match (a.merge(other.a.clone()), b.merge(other.b.clone())) {
+            (Merged::Yes, Merged::Yes) => {
+                *self = Join { a, b };
+                Merged::Yes
+            }
+            (Merged::Annul, Merged::Annul) => Merged::Annul,
+            _ => Merged::No(other),
+        }
+    }
+}
+
+impl<A: Display, B: Display> Display for Join<A, B> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} & {}", self.a, self.b)
+    }
+}
+
+/// Joins multiple edits together.
+///
+/// `join!(a, b, c)` is the same as `Join::new(a, Join::new(b, c))`.
+///
+/// # Examples
+/// ```
+/// # use undo::{join, Add, Record};
+/// let mut target = String::new();
+/// let mut record = Record::new();
+///
+/// record.edit(&mut target, join!(Add('a'), Add('b'), Add('c')));
+/// assert_eq!(target, "abc");
+///
+/// record.undo(&mut target);
+/// assert_eq!(target, "");
+/// ```
+#[macro_export]
+macro_rules! join {
+    ($a:expr, $b:expr $(,)?) => {
+        $crate::Join::new($a, $b)
+    };
+    ($a:expr, $($rest:expr),+ $(,)?) => {
+        $crate::Join::new($a, $crate::join!($($rest),+))
+    };
+}
@@ -13,8 +13,26 @@
 //!   by implementing the [`merge`](Edit::merge) method on the edit. This allows smaller edits to be used to build
 //!   more complex operations, or smaller incremental changes to be merged into larger changes that can be undone and
 //!   redone in a single step.
+//! * [`EditWithCtx`] works like [`Edit`] but passes a context to the edits in addition to the target.
+//! * [`EditRef`] works like [`Edit`] but the outputs can borrow from the target.
+//! * [`ValidatedEdit`] checks the edits against the target before they are applied.
+//! * [`Any`] allows different edit types to be used in the same structure.
+//! * [`FromFn`] and [`Join`] can be used to build edits from functions and other edits.
+//!   [`Diff`] works like [`FromFn`] but only stores the difference between the old and new target.
+//! * [`Invertible`] operations can be used as edits with [`auto_undo`], which undoes them by applying their inverse.
+//! * The [`collections`] module provides ready-made edits for vectors, maps, and strings.
 //! * [`Record`] provides basic stack based undo-redo functionality.
 //! * [`History`] provides full tree based undo-redo functionality.
+//! * [`OwnedRecord`] works like [`Record`] but owns the target.
+//! * [`SyncRecord`] works like [`Record`] but can be shared between threads.
+//! * [`Document`] owns the target and a [`History`], and saves the target with a callback.
+//! * [`UndoStack`] allows [`Record`]s and [`History`]s with different edit types to be used as trait objects.
+//! * [`OutputCombiner`] decides how the outputs of the edits are combined when navigating,
+//!   so for example [`Ignore`] can be used to avoid collecting them.
+//! * [`EditHook`] observes the edits before and after they are applied, undone, and redone.
+//! * [`RegionEdit`] reports the parts of the target changed by the edits, so only those parts
+//!   have to be redrawn. See [`RegionTracker`] and [`Record::dirty_region`].
+//! * [`Group`] manages multiple [`Record`]s or [`History`]s where one of them is active.
 //! * Queue and checkpoint functionality is supported for both [`Record`] and [`History`].
 //! * The target can be marked as saved to disk and the user will be notified when it changes.
 //! * The amount of changes being tracked can be configured by the user so only the `N` most recent changes are stored.
@@ -27,12 +45,23 @@
 //!
 //! # Cargo Feature Flags
 //!
-//! | Name    | Default | Enables | Description                                                     |
-//! |---------|---------|---------|-----------------------------------------------------------------|
-//! | std     | ✓       | alloc   | Enables the standard library.                                   |
-//! | alloc   |         |         | Enables the `alloc` crate.                                      |
-//! | colored |         |         | Enables colored output when visualizing the display structures. |
-//! | serde   |         |         | Enables serialization and deserialization.                      |
+//! | Name      | Default | Enables    | Description                                                     |
+//! |-----------|---------|------------|-----------------------------------------------------------------|
+//! | std       | ✓       | alloc      | Enables the standard library.                                   |
+//! | alloc     |         |            | Enables the `alloc` crate.                                      |
+//! | colored   |         |            | Enables colored output when visualizing the display structures. |
+//! | async     |         |            | Enables [`AsyncEdit`] for edits that need to be awaited.        |
+//! | serde     |         |            | Enables serialization and deserialization.                      |
+//! | tokio     |         | std        | Implements [`Slot`] for tokio's unbounded sender.               |
+//! | crossbeam |         | std        | Implements [`Slot`] for crossbeam's sender.                     |
+//! | futures   |         | std        | Implements [`Slot`] for the futures unbounded sender.           |
+//! | compress  |         | std, serde | Enables [`Compressed`] edits that are stored compressed.        |
+//! | text      |         | alloc      | Enables [`TextEdit`] for editing text.                          |
+//! | proptest  |         | std        | Enables proptest strategies in the [`testing`] module.          |
+//! | chrono    |         | std        | Enables showing absolute local times in the display structures. |
+//! | wasm      |         | std        | Enables [`JsClock`] and [`Slot`] for JavaScript functions.      |
+//! | log       |         | alloc      | Enables the [`slot`] that reports the events to `log`.          |
+//! | tracing   |         | alloc      | Enables the [`slot`] that reports the events to `tracing`.      |
 
 #![doc(html_root_url = "https://docs.rs/undo")]
 #![deny(missing_docs)]
@@ -49,28 +78,106 @@ pub struct ReadmeDocTest;
 #[cfg(feature = "alloc")]
 mod add;
 #[cfg(feature = "alloc")]
+mod any;
+#[cfg(feature = "alloc")]
+mod clock;
+#[cfg(feature = "alloc")]
+pub mod collections;
+mod combine;
+#[cfg(feature = "compress")]
+mod compress;
+#[cfg(feature = "alloc")]
+mod diff;
+#[cfg(feature = "std")]
+mod document;
+#[cfg(feature = "alloc")]
 mod entry;
 #[cfg(feature = "alloc")]
 mod format;
+mod from_fn;
+#[cfg(feature = "alloc")]
+mod group;
 #[cfg(feature = "alloc")]
 pub mod history;
 #[cfg(feature = "alloc")]
+mod hook;
+mod invert;
+mod join;
+#[cfg(feature = "alloc")]
+mod owned;
+#[cfg(feature = "alloc")]
+mod policy;
+#[cfg(feature = "alloc")]
 pub mod record;
+mod region;
+#[cfg(all(feature = "alloc", feature = "serde"))]
+pub mod schema;
+#[cfg(all(feature = "alloc", any(feature = "log", feature = "tracing")))]
+pub mod slot;
 #[cfg(feature = "alloc")]
 mod socket;
+#[cfg(feature = "alloc")]
+mod stack;
+#[cfg(feature = "alloc")]
+mod stats;
+#[cfg(feature = "std")]
+mod sync;
+#[cfg(feature = "alloc")]
+pub mod testing;
+#[cfg(feature = "text")]
+mod text;
 
 #[doc(hidden)]
 #[cfg(feature = "alloc")]
 pub use add::Add;
 #[cfg(feature = "alloc")]
-pub use entry::Entry;
+pub use any::Any;
+#[cfg(feature = "wasm")]
+pub use clock::JsClock;
+#[cfg(feature = "alloc")]
+pub use clock::{Clock, TimestampMode};
+#[cfg(feature = "std")]
+pub use clock::{MonotonicClock, SystemClock};
+pub use combine::{Ignore, Last, OutputCombiner};
+#[cfg(feature = "compress")]
+pub use compress::{Compressed, Compressor, Deflate};
+#[cfg(feature = "alloc")]
+pub use diff::{Diff, Differ};
+#[cfg(feature = "std")]
+pub use document::Document;
+#[cfg(feature = "alloc")]
+pub use entry::{Entry, EntryMeta};
+pub use from_fn::{FromFn, TryFromFn};
+#[cfg(feature = "alloc")]
+pub use group::Group;
 #[cfg(feature = "alloc")]
 pub use history::History;
 #[cfg(feature = "alloc")]
+pub use hook::EditHook;
+pub use invert::{auto_undo, Inverted, Invertible};
+pub use join::Join;
+#[cfg(feature = "alloc")]
+pub use owned::{OwnedRecord, TargetMut};
+#[cfg(feature = "alloc")]
+pub use policy::{BatchPolicy, MergePolicy, Truncate};
+#[cfg(feature = "alloc")]
 pub use record::Record;
+#[cfg(feature = "std")]
+pub use region::RegionTracker;
+pub use region::{Region, RegionEdit};
+#[cfg(feature = "alloc")]
+pub use socket::{Event, MultiSlot, Slot, SlotId, UiState};
 #[cfg(feature = "alloc")]
-pub use socket::{Event, Slot};
+pub use stack::UndoStack;
+#[cfg(feature = "alloc")]
+pub use stats::Stats;
+#[cfg(feature = "std")]
+pub use sync::SyncRecord;
+#[cfg(feature = "text")]
+pub use text::{TextBuffer, TextEdit};
 
+#[cfg(feature = "async")]
+use core::future::Future;
 #[cfg(feature = "alloc")]
 use format::Format;
 #[cfg(feature = "serde")]
@@ -103,6 +210,338 @@ pub trait Edit {
     {
         Merged::No(other)
     }
+
+    /// Works like [`Edit::merge`] but also gets the metadata of the entries the edits are in.
+    ///
+    /// This allows the merge to depend on the time between the edits,
+    /// or on the metadata added with [`Record::edit_with_meta`].
+    /// The default implementation uses the [`Edit::merge`] implementation.
+    ///
+    /// # Examples
+    /// ```
+    /// # use core::time::Duration;
+    /// # use undo::{Edit, EntryMeta, Merged, Record};
+    /// struct Type(String);
+    ///
+    /// impl Edit for Type {
+    ///     type Target = String;
+    ///     type Output = ();
+    ///
+    ///     fn edit(&mut self, target: &mut String) {
+    ///         target.push_str(&self.0);
+    ///     }
+    ///
+    ///     fn undo(&mut self, target: &mut String) {
+    ///         target.truncate(target.len() - self.0.len());
+    ///     }
+    ///
+    ///     fn merge_with_meta(&mut self, other: Self, a: &EntryMeta, b: &EntryMeta) -> Merged<Self> {
+    ///         // Only merge keystrokes that are typed within 500ms.
+    ///         if b.time_of_edit() - a.time_of_edit() < Duration::from_millis(500) {
+    ///             self.0.push_str(&other.0);
+    ///             Merged::Yes
+    ///         } else {
+    ///             Merged::No(other)
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Type("a".into()));
+    /// record.edit(&mut target, Type("b".into()));
+    /// assert_eq!(record.len(), 1);
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn merge_with_meta(
+        &mut self,
+        other: Self,
+        self_meta: &EntryMeta,
+        other_meta: &EntryMeta,
+    ) -> Merged<Self>
+    where
+        Self: Sized,
+    {
+        let _ = (self_meta, other_meta);
+        self.merge(other)
+    }
+
+    /// Returns `true` if the edit gives the same result when applied before or after `other`.
+    ///
+    /// Used by [`Record::undo_at`] to undo edits that are not at the top of the stack.
+    /// The default implementation returns `false`.
+    fn commute(&self, other: &Self) -> bool
+    where
+        Self: Sized,
+    {
+        let _ = other;
+        false
+    }
+}
+
+/// Base functionality for edit commands that need to be awaited.
+///
+/// Works the same as [`Edit`], and is used with methods like
+/// [`Record::edit_async`] and [`History::edit_async`].
+///
+/// # Examples
+/// ```
+/// # use undo::{AsyncEdit, Record};
+/// struct Push(char);
+///
+/// impl AsyncEdit for Push {
+///     type Target = String;
+///     type Output = ();
+///
+///     async fn edit(&mut self, target: &mut String) {
+///         target.push(self.0);
+///     }
+///
+///     async fn undo(&mut self, target: &mut String) {
+///         self.0 = target.pop().unwrap();
+///     }
+/// }
+///
+/// async fn run() {
+///     let mut target = String::new();
+///     let mut record = Record::new();
+///     record.edit_async(&mut target, Push('a')).await;
+///     record.undo_async(&mut target).await;
+///     assert_eq!(target, "");
+/// }
+/// ```
+#[cfg(feature = "async")]
+pub trait AsyncEdit {
+    /// The target type.
+    type Target;
+    /// The output type.
+    type Output;
+
+    /// Applies the edit command on the target.
+    fn edit(&mut self, target: &mut Self::Target) -> impl Future<Output = Self::Output>;
+
+    /// Restores the state of the target as it was before the edit was applied.
+    fn undo(&mut self, target: &mut Self::Target) -> impl Future<Output = Self::Output>;
+
+    /// Reapplies the edit on the target.
+    ///
+    /// The default implementation uses the [`AsyncEdit::edit`] implementation.
+    fn redo(&mut self, target: &mut Self::Target) -> impl Future<Output = Self::Output> {
+        self.edit(target)
+    }
+
+    /// Used for manual merging of edits. See [`Merged`] for more information.
+    fn merge(&mut self, other: Self) -> Merged<Self>
+    where
+        Self: Sized,
+    {
+        Merged::No(other)
+    }
+}
+
+/// Base functionality for edit commands that need a context in addition to the target.
+///
+/// Works the same as [`Edit`], but the context is passed to every call, which is useful
+/// for edits that need access to shared resources like caches or database handles.
+/// Used with methods like [`Record::edit_with`] and [`History::edit_with`].
+///
+/// # Examples
+/// ```
+/// # use undo::{EditWithCtx, Record};
+/// struct Push(char);
+///
+/// impl EditWithCtx for Push {
+///     type Target = String;
+///     type Context = Vec<char>;
+///     type Output = ();
+///
+///     fn edit(&mut self, target: &mut String, log: &mut Vec<char>) {
+///         target.push(self.0);
+///         log.push(self.0);
+///     }
+///
+///     fn undo(&mut self, target: &mut String, log: &mut Vec<char>) {
+///         self.0 = target.pop().unwrap();
+///         log.pop();
+///     }
+/// }
+///
+/// let mut target = String::new();
+/// let mut log = Vec::new();
+/// let mut record = Record::new();
+/// record.edit_with(&mut target, &mut log, Push('a'));
+/// record.edit_with(&mut target, &mut log, Push('b'));
+/// assert_eq!(log, ['a', 'b']);
+/// record.undo_with(&mut target, &mut log);
+/// assert_eq!(log, ['a']);
+/// ```
+pub trait EditWithCtx {
+    /// The target type.
+    type Target;
+    /// The context type.
+    type Context;
+    /// The output type.
+    type Output;
+
+    /// Applies the edit command on the target.
+    fn edit(&mut self, target: &mut Self::Target, ctx: &mut Self::Context) -> Self::Output;
+
+    /// Restores the state of the target as it was before the edit was applied.
+    fn undo(&mut self, target: &mut Self::Target, ctx: &mut Self::Context) -> Self::Output;
+
+    /// Reapplies the edit on the target.
+    ///
+    /// The default implementation uses the [`EditWithCtx::edit`] implementation.
+    fn redo(&mut self, target: &mut Self::Target, ctx: &mut Self::Context) -> Self::Output {
+        self.edit(target, ctx)
+    }
+
+    /// Used for manual merging of edits. See [`Merged`] for more information.
+    fn merge(&mut self, other: Self) -> Merged<Self>
+    where
+        Self: Sized,
+    {
+        Merged::No(other)
+    }
+}
+
+/// Base functionality for edit commands with outputs that borrow the target.
+///
+/// Works the same as [`Edit`], but the output can borrow from the target, which avoids
+/// cloning large values just to return them. Used with methods like [`Record::edit_ref`]
+/// and [`History::edit_ref`].
+///
+/// # Examples
+/// ```
+/// # use undo::{EditRef, Record};
+/// struct Insert(String);
+///
+/// impl EditRef for Insert {
+///     type Target = String;
+///     type Output<'a> = &'a str;
+///
+///     fn edit<'a>(&mut self, target: &'a mut String) -> &'a str {
+///         let start = target.len();
+///         target.push_str(&self.0);
+///         &target[start..]
+///     }
+///
+///     fn undo<'a>(&mut self, target: &'a mut String) -> &'a str {
+///         target.truncate(target.len() - self.0.len());
+///         target
+///     }
+/// }
+///
+/// let mut target = String::new();
+/// let mut record = Record::new();
+/// assert_eq!(record.edit_ref(&mut target, Insert("ab".into())), "ab");
+/// assert_eq!(record.edit_ref(&mut target, Insert("cd".into())), "cd");
+/// assert_eq!(record.undo_ref(&mut target), Some("ab"));
+/// assert_eq!(record.redo_ref(&mut target), Some("cd"));
+/// ```
+pub trait EditRef {
+    /// The target type.
+    type Target;
+    /// The output type, which can borrow from the target.
+    type Output<'a>
+    where
+        Self::Target: 'a;
+
+    /// Applies the edit command on the target.
+    fn edit<'a>(&mut self, target: &'a mut Self::Target) -> Self::Output<'a>;
+
+    /// Restores the state of the target as it was before the edit was applied.
+    fn undo<'a>(&mut self, target: &'a mut Self::Target) -> Self::Output<'a>;
+
+    /// Reapplies the edit on the target.
+    ///
+    /// The default implementation uses the [`EditRef::edit`] implementation.
+    fn redo<'a>(&mut self, target: &'a mut Self::Target) -> Self::Output<'a> {
+        self.edit(target)
+    }
+
+    /// Used for manual merging of edits. See [`Merged`] for more information.
+    fn merge(&mut self, other: Self) -> Merged<Self>
+    where
+        Self: Sized,
+    {
+        Merged::No(other)
+    }
+}
+
+/// Checks an edit command against the target before it is applied.
+///
+/// Used with methods like [`Record::edit_validated`] and [`History::edit_validated`]
+/// that only apply and push the edit if it is valid, so edits that would fail halfway
+/// never leave the target in a half-applied state.
+///
+/// # Examples
+/// ```
+/// # use undo::{Edit, Record, ValidatedEdit};
+/// struct Remove(usize, char);
+///
+/// impl Edit for Remove {
+///     type Target = Vec<char>;
+///     type Output = char;
+///
+///     fn edit(&mut self, target: &mut Vec<char>) -> char {
+///         self.1 = target.remove(self.0);
+///         self.1
+///     }
+///
+///     fn undo(&mut self, target: &mut Vec<char>) -> char {
+///         target.insert(self.0, self.1);
+///         self.1
+///     }
+/// }
+///
+/// impl ValidatedEdit for Remove {
+///     type Error = &'static str;
+///
+///     fn validate(&self, target: &Vec<char>) -> Result<(), &'static str> {
+///         if self.0 < target.len() {
+///             Ok(())
+///         } else {
+///             Err("index out of bounds")
+///         }
+///     }
+/// }
+///
+/// let mut target = vec!['a'];
+/// let mut record = Record::new();
+/// assert_eq!(record.edit_validated(&mut target, Remove(1, ' ')), Err("index out of bounds"));
+/// assert_eq!(record.len(), 0);
+/// assert_eq!(record.edit_validated(&mut target, Remove(0, ' ')), Ok('a'));
+/// record.undo(&mut target);
+/// assert_eq!(target, ['a']);
+/// ```
+pub trait ValidatedEdit: Edit {
+    /// The error type.
+    type Error;
+
+    /// Returns an error if the edit can not be applied to the target.
+    fn validate(&self, target: &Self::Target) -> Result<(), Self::Error>;
+}
+
+/// Estimates the memory used by an edit command.
+///
+/// Used by [`record::Builder::memory_limit`] and [`history::Builder::memory_limit`]
+/// to limit the structures by the memory used by the edits instead of the number of edits.
+///
+/// # Examples
+/// ```
+/// # use undo::SizedEdit;
+/// struct Paste(Vec<u8>);
+///
+/// impl SizedEdit for Paste {
+///     fn size_hint(&self) -> usize {
+///         size_of::<Self>() + self.0.capacity()
+///     }
+/// }
+/// ```
+pub trait SizedEdit {
+    /// Returns the estimated number of bytes used by the edit.
+    fn size_hint(&self) -> usize;
 }
 
 /// Says if the [`Edit`] command have been merged with another command.
@@ -127,10 +566,16 @@ pub enum Merged<E> {
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct At {
-    /// The root branch.
+    /// The id of the branch.
+    ///
+    /// The ids of removed branches are not reused by the same history
+    /// until it is compacted, see [`History::is_valid`].
     pub root: usize,
     /// The index of edit.
     pub index: usize,
+    /// The generation of the branch ids, which changes when the history is compacted.
+    #[cfg_attr(feature = "serde", serde(default))]
+    generation: u32,
 }
 
 #[cfg(feature = "alloc")]
@@ -138,8 +583,27 @@ impl At {
     const NIL: At = At::new(0, 0);
 
     /// Creates a new `At` with the provided root and index.
+    ///
+    /// The position is in the first generation of branch ids, so it is only valid in
+    /// histories that have not been compacted. Use [`History::at`] to create a position
+    /// in the current generation of a history.
     pub const fn new(root: usize, index: usize) -> At {
-        At { root, index }
+        At::with_generation(root, index, 0)
+    }
+
+    pub(crate) const fn with_generation(root: usize, index: usize, generation: u32) -> At {
+        At {
+            root,
+            index,
+            generation,
+        }
+    }
+
+    /// Returns the generation of the branch ids the position belongs to.
+    ///
+    /// See [`History::compact`] for more information.
+    pub const fn generation(&self) -> u32 {
+        self.generation
     }
 
     const fn no_root(index: usize) -> At {
@@ -0,0 +1,149 @@
+use crate::socket::Slot;
+use crate::{Edit, Record};
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A [`Record`] that owns its target.
+///
+/// Edits, undos, and redos are applied to the owned target,
+/// so the target does not need to be passed to every call.
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, OwnedRecord};
+/// let mut record = OwnedRecord::new(String::new());
+/// record.edit(Add('a'));
+/// record.edit(Add('b'));
+/// assert_eq!(record.target(), "ab");
+///
+/// record.undo();
+/// assert_eq!(record.target(), "a");
+/// assert_eq!(record.into_target(), "a");
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct OwnedRecord<T, E, S = ()> {
+    target: T,
+    record: Record<E, S>,
+}
+
+impl<T, E> OwnedRecord<T, E> {
+    /// Returns a new owned record with the target.
+    pub fn new(target: T) -> OwnedRecord<T, E> {
+        OwnedRecord::from_parts(target, Record::new())
+    }
+}
+
+impl<T, E, S> OwnedRecord<T, E, S> {
+    /// Returns an owned record from the target and the record.
+    ///
+    /// The record should be in the state that the target is in.
+    pub fn from_parts(target: T, record: Record<E, S>) -> OwnedRecord<T, E, S> {
+        OwnedRecord { target, record }
+    }
+
+    /// Returns the target and the record.
+    pub fn into_parts(self) -> (T, Record<E, S>) {
+        (self.target, self.record)
+    }
+
+    /// Returns the target.
+    pub fn into_target(self) -> T {
+        self.target
+    }
+
+    /// Returns a reference to the target.
+    pub fn target(&self) -> &T {
+        &self.target
+    }
+
+    /// Returns a reference to the record.
+    pub fn record(&self) -> &Record<E, S> {
+        &self.record
+    }
+
+    /// Returns a mutable reference to the record.
+    pub fn record_mut(&mut self) -> &mut Record<E, S> {
+        &mut self.record
+    }
+}
+
+impl<T, E, S: Slot> OwnedRecord<T, E, S> {
+    /// Returns a guard with mutable access to the target.
+    ///
+    /// Changes made through the guard are not recorded, so the edits in the record
+    /// and the saved state are cleared when the guard is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, OwnedRecord};
+    /// let mut record = OwnedRecord::new(String::new());
+    /// record.edit(Add('a'));
+    /// record.target_mut().push('b');
+    /// assert_eq!(record.target(), "ab");
+    /// assert!(!record.record().can_undo());
+    /// ```
+    pub fn target_mut(&mut self) -> TargetMut<'_, T, E, S> {
+        TargetMut { owned: self }
+    }
+}
+
+impl<T, E: Edit<Target = T>, S: Slot> OwnedRecord<T, E, S> {
+    /// Pushes the edit on top of the record and executes its [`Edit::edit`] method.
+    pub fn edit(&mut self, edit: E) -> E::Output {
+        self.record.edit(&mut self.target, edit)
+    }
+
+    /// Calls the [`Edit::undo`] method for the active edit and sets
+    /// the previous one as the new active one.
+    pub fn undo(&mut self) -> Option<E::Output> {
+        self.record.undo(&mut self.target)
+    }
+
+    /// Calls the [`Edit::redo`] method for the active edit and sets
+    /// the next one as the new active one.
+    pub fn redo(&mut self) -> Option<E::Output> {
+        self.record.redo(&mut self.target)
+    }
+
+    /// Repeatedly calls [`Edit::undo`] or [`Edit::redo`] until the edit at `index` is reached.
+    pub fn go_to(&mut self, index: usize) -> Vec<E::Output> {
+        self.record.go_to(&mut self.target, index)
+    }
+}
+
+impl<T: Default, E> Default for OwnedRecord<T, E> {
+    fn default() -> Self {
+        OwnedRecord::new(T::default())
+    }
+}
+
+/// Mutable access to the target of an [`OwnedRecord`].
+///
+/// See [`OwnedRecord::target_mut`] for more information.
+pub struct TargetMut<'a, T, E, S: Slot> {
+    owned: &'a mut OwnedRecord<T, E, S>,
+}
+
+impl<T, E, S: Slot> Deref for TargetMut<'_, T, E, S> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.owned.target
+    }
+}
+
+impl<T, E, S: Slot> DerefMut for TargetMut<'_, T, E, S> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.owned.target
+    }
+}
+
+impl<T, E, S: Slot> Drop for TargetMut<'_, T, E, S> {
+    fn drop(&mut self) {
+        self.owned.record.clear();
+        self.owned.record.clear_saved();
+    }
+}
@@ -0,0 +1,114 @@
+use crate::Entry;
+use core::fmt::{self, Debug, Formatter};
+use core::time::Duration;
+
+/// Decides if a new edit is allowed to merge with the previous edit.
+///
+/// The policy is checked before [`Edit::merge`](crate::Edit::merge) is called
+/// when an edit is pushed to a [`Record`](crate::Record) or [`History`](crate::History).
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use undo::{Edit, Merged, MergePolicy, Record};
+/// # struct Append(String);
+/// # impl Edit for Append {
+/// #     type Target = String;
+/// #     type Output = ();
+/// #     fn edit(&mut self, target: &mut String) { target.push_str(&self.0); }
+/// #     fn undo(&mut self, target: &mut String) { target.truncate(target.len() - self.0.len()); }
+/// #     fn merge(&mut self, other: Self) -> Merged<Self> { self.0.push_str(&other.0); Merged::Yes }
+/// # }
+/// let mut target = String::new();
+/// let mut record = Record::builder()
+///     .merge_policy(MergePolicy::Never)
+///     .build();
+///
+/// record.edit(&mut target, Append("a".into()));
+/// record.edit(&mut target, Append("b".into()));
+/// assert_eq!(record.len(), 2);
+/// ```
+#[derive(Default)]
+pub enum MergePolicy<E> {
+    /// Always try to merge the edits. This is the default.
+    #[default]
+    Always,
+    /// Never merge the edits.
+    Never,
+    /// Only try to merge the edits if the new edit was applied
+    /// within the duration of the previous edit.
+    ///
+    /// The times are taken from the [`Clock`](crate::Clock) of the structure.
+    WithinDuration(Duration),
+    /// Only try to merge the edits if the function returns `true`.
+    ///
+    /// The function is called with the previous edit and the new edit.
+    With(fn(&E, &E) -> bool),
+}
+
+impl<E> MergePolicy<E> {
+    /// Returns `true` if `next` is allowed to merge with `prev`.
+    pub(crate) fn allows(&self, prev: &Entry<E>, next: &Entry<E>) -> bool {
+        match self {
+            MergePolicy::Always => true,
+            MergePolicy::Never => false,
+            MergePolicy::WithinDuration(duration) => next
+                .time_of_edit()
+                .checked_sub(prev.time_of_edit())
+                .is_some_and(|elapsed| elapsed <= *duration),
+            MergePolicy::With(f) => f(prev.as_ref(), next.as_ref()),
+        }
+    }
+}
+
+impl<E> Clone for MergePolicy<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for MergePolicy<E> {}
+
+impl<E> Debug for MergePolicy<E> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            MergePolicy::Always => f.write_str("Always"),
+            MergePolicy::Never => f.write_str("Never"),
+            MergePolicy::WithinDuration(duration) => {
+                f.debug_tuple("WithinDuration").field(duration).finish()
+            }
+            MergePolicy::With(_) => f.debug_tuple("With").finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Decides what happens to the entries that do not fit when the limit is lowered.
+///
+/// Used by [`Record::set_limit`](crate::Record::set_limit) and
+/// [`History::set_limit`](crate::History::set_limit).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Truncate {
+    /// Remove the oldest entries until the entries fit.
+    ///
+    /// Only entries that have been applied to the target are removed from the front.
+    /// If that is not enough, the most recently undone entries are removed instead.
+    Front,
+    /// Do not change the limit if there are more entries than the new limit.
+    RejectIfSmaller,
+}
+
+/// Decides how the edits are pushed by [`Record::edit_many`](crate::Record::edit_many).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BatchPolicy {
+    /// Push every edit the same way as [`Record::edit`](crate::Record::edit). This is the default.
+    #[default]
+    Continue,
+    /// Stop after an edit annuls the previous edit.
+    ///
+    /// The edits after it are not applied.
+    StopOnAnnul,
+    /// Try to merge every edit with the previous edit, ignoring the [`MergePolicy`].
+    ///
+    /// Edits are still not merged into the saved state.
+    MergeAggressively,
+}
@@ -3,22 +3,50 @@
 mod builder;
 mod checkpoint;
 mod display;
+mod entries;
+#[cfg(feature = "std")]
+pub mod journal;
 mod queue;
+mod scope;
+mod script;
+mod snapshot;
 
 pub use builder::Builder;
 pub use checkpoint::Checkpoint;
 pub use display::Display;
+pub use entries::Entries;
 pub use queue::Queue;
+pub use scope::Scope;
+pub use script::Script;
 
+use crate::clock::ClockRef;
+use crate::entry::MergeFn;
+use crate::hook::Hooks;
+use crate::region;
+#[cfg(feature = "serde")]
+use crate::schema::{self, EntryDe, RecordDe};
 use crate::socket::{Slot, Socket};
-use crate::{Edit, Entry, Event, Merged};
-use alloc::collections::VecDeque;
+#[cfg(feature = "async")]
+use crate::AsyncEdit;
+use crate::{
+    BatchPolicy, Edit, EditRef, EditWithCtx, Entry, Event, MergePolicy, Merged, OutputCombiner,
+    RegionEdit, SizedEdit, Stats, Truncate, ValidatedEdit,
+};
+use alloc::collections::{BTreeMap, VecDeque};
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
 use core::num::NonZeroUsize;
+use core::ops::{Bound, RangeBounds};
+use core::time::Duration;
+use entries::{drain_front, partition_point};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use snapshot::Snapshot;
+#[cfg(feature = "std")]
+use std::time::SystemTime;
 
 /// A linear record of [`Edit`] commands.
 ///
@@ -58,12 +86,59 @@ use serde::{Deserialize, Serialize};
 /// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
-pub struct Record<E, S = ()> {
+pub struct Record<E, S = (), B = VecDeque<Entry<E>>> {
     limit: NonZeroUsize,
-    index: usize,
+    pub(crate) index: usize,
     pub(crate) saved: Option<usize>,
     pub(crate) socket: Socket<S>,
-    pub(crate) entries: VecDeque<Entry<E>>,
+    pub(crate) entries: B,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) meta: BTreeMap<String, String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) savepoints: BTreeMap<String, usize>,
+    #[cfg_attr(feature = "serde", serde(skip, default = "MergePolicy::default"))]
+    pub(crate) merge_policy: MergePolicy<E>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) group: Option<OpenGroup>,
+    #[cfg_attr(feature = "serde", serde(skip, default = "Option::default"))]
+    pub(crate) memory_limit: Option<MemoryLimit<E>>,
+    #[cfg_attr(feature = "serde", serde(default = "Option::default"))]
+    pub(crate) discarded: Option<Vec<Discarded<E, B>>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) cascade_annul: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) clock: ClockRef,
+    #[cfg_attr(feature = "serde", serde(skip, default = "Hooks::default"))]
+    pub(crate) hooks: Hooks<E>,
+    #[cfg(feature = "compress")]
+    #[serde(skip, default = "Option::default")]
+    pub(crate) compress_after: Option<CompressAfter<E>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) stats: Stats,
+    /// The sequence number of the last pushed entry.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) seq: usize,
+    #[cfg_attr(feature = "serde", serde(skip, default = "Option::default"))]
+    pub(crate) snapshot: Option<Snapshot<E>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) through_barriers: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) protect_saved: bool,
+}
+
+/// The memory limit in bytes and the function that estimates the size of an edit.
+pub(crate) type MemoryLimit<E> = (usize, fn(&E) -> usize);
+
+/// The number of positions behind the head to keep uncompressed and the function that compresses an edit.
+#[cfg(feature = "compress")]
+pub(crate) type CompressAfter<E> = (usize, fn(&mut E));
+
+/// The group that is currently being built by [`Record::begin_group`].
+#[derive(Clone, Debug)]
+pub(crate) struct OpenGroup {
+    depth: usize,
+    start: usize,
+    text: String,
 }
 
 impl<E> Record<E> {
@@ -71,14 +146,59 @@ impl<E> Record<E> {
     pub fn new() -> Record<E> {
         Record::builder().build()
     }
-}
 
-impl<E, S> Record<E, S> {
     /// Returns a new record builder.
-    pub fn builder() -> Builder<E, S> {
-        Builder::default()
+    ///
+    /// The slot type is set when [`Builder::connect`] is called.
+    pub fn builder() -> Builder<E> {
+        Builder::new()
+    }
+
+    /// Creates a record from the serializable format.
+    ///
+    /// See the [`schema`](crate::schema) module for more information.
+    ///
+    /// # Errors
+    /// Returns an error if the format version is not supported or the data is not consistent.
+    #[cfg(feature = "serde")]
+    pub fn from_serializable(de: RecordDe<E>) -> Result<Record<E>, schema::Error> {
+        schema::check_version(de.version)?;
+        let len = de.entries.len();
+        let limit = NonZeroUsize::new(de.limit).ok_or(schema::Error::Invalid("limit is zero"))?;
+        if len > limit.get() {
+            return Err(schema::Error::Invalid("more entries than the limit"));
+        }
+        if de.index > len || de.saved > Some(len) || de.savepoints.values().any(|&i| i > len) {
+            return Err(schema::Error::Invalid("index out of bounds"));
+        }
+        let seq = de.entries.iter().map(|e| e.seq).max().unwrap_or(0);
+        Ok(Record {
+            limit,
+            index: de.index,
+            saved: de.saved,
+            socket: Socket::default(),
+            entries: de.entries.into_iter().map(Entry::from).collect(),
+            meta: de.meta,
+            savepoints: de.savepoints,
+            merge_policy: MergePolicy::Always,
+            group: None,
+            memory_limit: None,
+            cascade_annul: false,
+            stats: Stats::default(),
+            #[cfg(feature = "compress")]
+            compress_after: None,
+            discarded: None,
+            clock: ClockRef::default(),
+            hooks: Hooks::default(),
+            seq,
+            snapshot: None,
+            through_barriers: false,
+            protect_saved: false,
+        })
     }
+}
 
+impl<E, S, B: Entries<E>> Record<E, S, B> {
     /// Reserves capacity for at least `additional` more edits.
     ///
     /// # Panics
@@ -112,6 +232,30 @@ impl<E, S> Record<E, S> {
         self.limit.get()
     }
 
+    /// Returns the memory limit of the record in bytes, if it has one.
+    ///
+    /// See [`Builder::memory_limit`] for more information.
+    pub fn memory_limit(&self) -> Option<usize> {
+        self.memory_limit.map(|(limit, _)| limit)
+    }
+
+    /// Returns the estimated memory used by the edits in the record in bytes.
+    ///
+    /// The estimate is the sum of [`SizedEdit::size_hint`] for all edits.
+    pub fn memory_usage(&self) -> usize
+    where
+        E: SizedEdit,
+    {
+        self.entries.iter().map(|e| e.as_ref().size_hint()).sum()
+    }
+
+    /// Returns an iterator over the entries that have been discarded by pushing new edits.
+    ///
+    /// This is always empty unless [`Builder::keep_discarded`] is enabled.
+    pub fn discarded(&self) -> impl Iterator<Item = &Discarded<E, B>> {
+        self.discarded.iter().flatten()
+    }
+
     /// Sets how the event should be handled when the state changes.
     pub fn connect(&mut self, slot: S) -> Option<S> {
         self.socket.connect(Some(slot))
@@ -122,9 +266,24 @@ impl<E, S> Record<E, S> {
         self.socket.disconnect()
     }
 
+    /// Returns a mutable reference to the slot if it exists.
+    pub fn slot_mut(&mut self) -> Option<&mut S> {
+        self.socket.slot_mut()
+    }
+
     /// Returns `true` if the record can undo.
+    ///
+    /// Returns `false` at a barrier, see [`Record::push_barrier`].
     pub fn can_undo(&self) -> bool {
-        self.index > 0
+        self.index > 0 && (self.through_barriers || !self.at_barrier())
+    }
+
+    /// Returns `true` if there is a barrier at the current position.
+    fn at_barrier(&self) -> bool {
+        self.index
+            .checked_sub(1)
+            .and_then(|i| self.entries.get(i))
+            .is_some_and(|e| e.barrier.is_some())
     }
 
     /// Returns `true` if the record can redo.
@@ -142,6 +301,46 @@ impl<E, S> Record<E, S> {
         self.saved
     }
 
+    /// Returns the index of the savepoint with the `name`.
+    pub fn savepoint(&self, name: &str) -> Option<usize> {
+        self.savepoints.get(name).copied()
+    }
+
+    /// Returns an iterator over the names and indices of the savepoints.
+    pub fn saved_states(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.savepoints.iter().map(|(k, &v)| (k.as_str(), v))
+    }
+
+    /// Marks the current index as a savepoint with the `name`,
+    /// returning the previous index of the savepoint if any.
+    ///
+    /// Savepoints are independent of the saved state, and are kept until the edits
+    /// they refer to are removed from the record.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    /// record.save_as("a");
+    /// record.edit(&mut target, Add('b'));
+    /// record.save_as("b");
+    ///
+    /// record.revert_to(&mut target, "a");
+    /// assert_eq!(target, "a");
+    /// record.revert_to(&mut target, "b");
+    /// assert_eq!(target, "ab");
+    /// ```
+    pub fn save_as(&mut self, name: impl Into<String>) -> Option<usize> {
+        self.savepoints.insert(name.into(), self.index)
+    }
+
+    /// Removes the savepoint with the `name`, returning its index if it existed.
+    pub fn remove_savepoint(&mut self, name: &str) -> Option<usize> {
+        self.savepoints.remove(name)
+    }
+
     /// Returns the current index in the record.
     pub fn head(&self) -> usize {
         self.index
@@ -152,28 +351,310 @@ impl<E, S> Record<E, S> {
         self.entries.get(index)
     }
 
+    /// Returns a mutable reference to the entry at the index.
+    ///
+    /// See [`Record::get_edit_mut`] for how the edit in the entry can be changed.
+    pub fn get_entry_mut(&mut self, index: usize) -> Option<&mut Entry<E>> {
+        self.entries.get_mut(index)
+    }
+
+    /// Returns a mutable reference to the edit at the index.
+    ///
+    /// This can be used to update data in the edit that does not affect the target,
+    /// like the text returned by its [`Display`](core::fmt::Display) implementation.
+    /// The edit must still be able to undo and redo the same change to the target,
+    /// otherwise the target will get out of sync with the record.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    ///
+    /// let edit = record.get_edit_mut(0).unwrap();
+    /// assert_eq!(*edit, Add('a'));
+    /// ```
+    pub fn get_edit_mut(&mut self, index: usize) -> Option<&mut E> {
+        self.entries.get_mut(index).map(Entry::as_mut)
+    }
+
     /// Returns an iterator over the entries.
     pub fn entries(&self) -> impl Iterator<Item = &Entry<E>> {
         self.entries.iter()
     }
 
-    /// Returns a queue.
-    pub fn queue(&mut self) -> Queue<E, S> {
-        Queue::from(self)
+    /// Returns the number of entries that can be redone.
+    pub fn redo_len(&self) -> usize {
+        self.len() - self.index
     }
 
-    /// Returns a checkpoint.
-    pub fn checkpoint(&mut self) -> Checkpoint<E, S> {
-        Checkpoint::from(self)
+    /// Returns an iterator over the entries that can be redone,
+    /// in the order they are redone.
+    pub fn redoable_entries(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &Entry<E>> + ExactSizeIterator {
+        self.entries.iter().skip(self.index)
+    }
+
+    /// Returns an iterator over the edits that can be redone,
+    /// in the order they are redone.
+    ///
+    /// Can be used to show the upcoming redo steps in a menu.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// record.edit(&mut target, Add('c'));
+    /// record.undo(&mut target);
+    /// record.undo(&mut target);
+    ///
+    /// assert_eq!(record.redo_len(), 2);
+    /// let menu: Vec<_> = record.redoable_edits().map(ToString::to_string).collect();
+    /// assert_eq!(menu, ["Add 'b'", "Add 'c'"]);
+    /// ```
+    pub fn redoable_edits(&self) -> impl DoubleEndedIterator<Item = &E> + ExactSizeIterator {
+        self.redoable_entries().map(Entry::as_ref)
+    }
+
+    /// Returns the index of the entry with the sequence number, see [`Entry::seq`].
+    ///
+    /// Returns `None` if the entry has been removed or merged into another entry.
+    pub fn find_seq(&self, seq: usize) -> Option<usize> {
+        self.entries.iter().position(|e| e.seq == seq)
+    }
+
+    /// Returns the statistics of the record.
+    ///
+    /// See [`Stats`] for more information.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            earliest: self.entries().map(Entry::time_of_edit).min(),
+            latest: self.entries().map(Entry::time_of_latest).max(),
+            branches: 1,
+            ..self.stats
+        }
+    }
+
+    /// Resets the counts in the statistics of the record.
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats::default();
+    }
+
+    /// Returns an iterator over the entries that are undone or redone when going from
+    /// the index `from` to the index `to`, in the order they are undone or redone.
+    ///
+    /// The indices are clamped to the length of the record.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// record.edit(&mut target, Add('c'));
+    ///
+    /// let redone: Vec<_> = record.entries_between(1, 3).map(|e| *e.as_ref()).collect();
+    /// assert_eq!(redone, [Add('b'), Add('c')]);
+    /// let undone: Vec<_> = record.entries_between(3, 1).map(|e| *e.as_ref()).collect();
+    /// assert_eq!(undone, [Add('c'), Add('b')]);
+    /// ```
+    pub fn entries_between(
+        &self,
+        from: usize,
+        to: usize,
+    ) -> impl ExactSizeIterator<Item = &Entry<E>> {
+        let from = from.min(self.len());
+        let to = to.min(self.len());
+        let (start, n) = (from.min(to), from.abs_diff(to));
+        (0..n).map(move |i| {
+            let i = if from <= to {
+                start + i
+            } else {
+                start + n - 1 - i
+            };
+            self.entries.get(i).unwrap()
+        })
+    }
+
+    /// Returns an iterator over the entries that have been undone or redone since
+    /// the target was saved, in the order they were undone or redone.
+    ///
+    /// The entries were redone if the saved index is less than the [head](Record::head),
+    /// and undone otherwise. Returns `None` if the record has no saved state.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    /// record.set_saved();
+    /// record.edit(&mut target, Add('b'));
+    /// record.edit(&mut target, Add('c'));
+    ///
+    /// let unsaved: Vec<_> = record.unsaved_entries().unwrap().map(|e| *e.as_ref()).collect();
+    /// assert_eq!(unsaved, [Add('b'), Add('c')]);
+    ///
+    /// record.undo(&mut target);
+    /// record.undo(&mut target);
+    /// record.undo(&mut target);
+    /// let unsaved: Vec<_> = record.unsaved_entries().unwrap().map(|e| *e.as_ref()).collect();
+    /// assert_eq!(unsaved, [Add('a')]);
+    /// ```
+    pub fn unsaved_entries(&self) -> Option<impl ExactSizeIterator<Item = &Entry<E>>> {
+        self.saved
+            .map(|saved| self.entries_between(saved, self.index))
+    }
+
+    /// Sets the metadata `value` for the `key`, returning the previous value if any.
+    ///
+    /// The metadata is not affected by [`Record::clear`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut record = Record::<Add>::new();
+    /// record.set_meta("id", "document-1");
+    /// assert_eq!(record.get_meta("id"), Some("document-1"));
+    /// ```
+    pub fn set_meta(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        self.meta.insert(key.into(), value.into())
+    }
+
+    /// Returns the metadata value for the `key`.
+    pub fn get_meta(&self, key: &str) -> Option<&str> {
+        self.meta.get(key).map(String::as_str)
+    }
+
+    /// Removes and returns the metadata value for the `key`.
+    pub fn remove_meta(&mut self, key: &str) -> Option<String> {
+        self.meta.remove(key)
+    }
+
+    /// Returns an iterator over the metadata key-value pairs.
+    pub fn meta(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.meta.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Starts a group, where all edits until the matching [`Record::end_group`]
+    /// are undone and redone as a single edit.
+    ///
+    /// The `text` is returned by [`Record::undo_string`] and [`Record::redo_string`]
+    /// for the group. Groups can be nested, in which case the nested groups
+    /// become part of the outermost group and their text is ignored.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.begin_group("Add abc");
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// record.edit(&mut target, Add('c'));
+    /// record.end_group();
+    ///
+    /// assert_eq!(record.undo_string().as_deref(), Some("Add abc"));
+    /// record.undo(&mut target);
+    /// assert_eq!(target, "");
+    /// record.redo(&mut target);
+    /// assert_eq!(target, "abc");
+    /// ```
+    pub fn begin_group(&mut self, text: impl Into<String>) {
+        match &mut self.group {
+            Some(group) => group.depth += 1,
+            None => {
+                self.group = Some(OpenGroup {
+                    depth: 0,
+                    start: self.index,
+                    text: text.into(),
+                })
+            }
+        }
+    }
+
+    /// Ends the group started by the last call to [`Record::begin_group`].
+    ///
+    /// Returns `false` if there is no group to end.
+    pub fn end_group(&mut self) -> bool {
+        match &mut self.group {
+            Some(group) if group.depth > 0 => group.depth -= 1,
+            Some(_) => self.group = None,
+            None => return false,
+        }
+        true
+    }
+
+    /// Joins the entries in `start..end` into a single group with the display text.
+    pub(crate) fn join_range(&mut self, start: usize, end: usize, text: String) {
+        let mut text = Some(text);
+        for (i, entry) in self.entries.iter_mut().enumerate().take(end).skip(start) {
+            entry.joined = i > start;
+            entry.group = text.take();
+        }
+    }
+
+    /// Returns the index of the first entry in the group that contains the entry at `i`.
+    fn group_start(&self, mut i: usize) -> usize {
+        while i > 0 && self.entries.get(i).is_some_and(|e| e.joined) {
+            i -= 1;
+        }
+        i
+    }
+
+    /// Returns the index after the last entry in the group that contains the entry at `i`.
+    fn group_end(&self, mut i: usize) -> usize {
+        i += 1;
+        while self.entries.get(i).is_some_and(|e| e.joined) {
+            i += 1;
+        }
+        i
+    }
+
+    /// Returns a deep copy of the record without a slot.
+    pub(crate) fn fork<T>(&self) -> Record<E, T, B>
+    where
+        E: Clone,
+        B: Clone,
+    {
+        Record {
+            limit: self.limit,
+            index: self.index,
+            saved: self.saved,
+            socket: Socket::default(),
+            entries: self.entries.clone(),
+            meta: self.meta.clone(),
+            savepoints: self.savepoints.clone(),
+            merge_policy: self.merge_policy,
+            group: self.group.clone(),
+            memory_limit: self.memory_limit,
+            cascade_annul: self.cascade_annul,
+            #[cfg(feature = "compress")]
+            compress_after: self.compress_after,
+            discarded: self.discarded.clone(),
+            clock: self.clock,
+            hooks: self.hooks.clone(),
+            stats: self.stats,
+            seq: self.seq,
+            snapshot: self.snapshot.clone(),
+            through_barriers: self.through_barriers,
+            protect_saved: self.protect_saved,
+        }
     }
 
     /// Returns a structure for configurable formatting of the record.
-    pub fn display(&self) -> Display<E, S> {
+    pub fn display(&self) -> Display<'_, E, S, B> {
         Display::from(self)
     }
 
     /// Remove all elements after the index.
-    pub(crate) fn rm_tail(&mut self) -> (VecDeque<Entry<E>>, Option<usize>) {
+    pub(crate) fn rm_tail(&mut self) -> (B, Option<usize>) {
         // Remove the saved state if it will be split off.
         let rm_saved = if self.saved > Some(self.index) {
             self.saved.take()
@@ -181,198 +662,1905 @@ impl<E, S> Record<E, S> {
             None
         };
 
+        let index = self.index;
+        self.savepoints.retain(|_, i| *i <= index);
         let tail = self.entries.split_off(self.index);
         (tail, rm_saved)
     }
+
+    /// Converts the record into the serializable format.
+    ///
+    /// The slot and the merge policy are not included.
+    /// See the [`schema`](crate::schema) module for more information.
+    #[cfg(feature = "serde")]
+    pub fn into_serializable(self) -> RecordDe<E> {
+        RecordDe {
+            version: schema::VERSION,
+            limit: self.limit.get(),
+            index: self.index,
+            saved: self.saved,
+            entries: self.entries.into_iter().map(EntryDe::from).collect(),
+            meta: self.meta,
+            savepoints: self.savepoints,
+        }
+    }
 }
 
-impl<E, S: Slot> Record<E, S> {
-    /// Marks the target as currently being in a saved.
-    pub fn set_saved(&mut self) {
-        let was_saved = self.is_saved();
-        self.saved = Some(self.index);
-        self.socket.emit_if(!was_saved, || Event::Saved(true));
+impl<E, S, B: Entries<E>> Record<E, S, B> {
+    /// Returns a queue.
+    pub fn queue(&mut self) -> Queue<'_, E, S, B> {
+        Queue::from(self)
     }
 
-    /// Clears the saved state of the target.
-    pub fn clear_saved(&mut self) {
-        let was_saved = self.is_saved();
-        self.saved = None;
-        self.socket.emit_if(was_saved, || Event::Saved(false));
+    /// Returns a checkpoint.
+    pub fn checkpoint(&mut self) -> Checkpoint<'_, E, S, B> {
+        Checkpoint::from(self)
     }
 
-    /// Removes all edits from the record without undoing them.
-    pub fn clear(&mut self) {
-        let old_index = self.index;
-        let could_undo = self.can_undo();
-        let could_redo = self.can_redo();
-        self.entries.clear();
-        self.saved = self.is_saved().then_some(0);
-        self.index = 0;
-        self.socket.emit_if(could_undo, || Event::Undo(false));
-        self.socket.emit_if(could_redo, || Event::Redo(false));
-        self.socket.emit_if(old_index != 0, || Event::Index(0));
+    /// Returns a wrapper that writes every change to the journal before it is applied.
+    #[cfg(feature = "std")]
+    pub fn journal<J>(&mut self, journal: J) -> journal::Journaled<'_, E, S, J, B> {
+        journal::Journaled::new(self, journal)
     }
-}
 
-impl<E: Edit, S: Slot> Record<E, S> {
-    /// Pushes the edit on top of the record and executes its [`Edit::edit`] method.
-    pub fn edit(&mut self, target: &mut E::Target, edit: E) -> E::Output {
-        let (output, _, _, _) = self.edit_and_push(target, Entry::new(edit));
-        output
+    /// Returns a scope that records the edits into a nested record.
+    ///
+    /// See [`Scope`] for more information.
+    pub fn scope<'a>(&'a mut self, target: &'a mut E::Target) -> Scope<'a, E, S, B>
+    where
+        E: Edit,
+        S: Slot,
+    {
+        Scope::new(self, target)
     }
+}
 
-    pub(crate) fn edit_and_push(
-        &mut self,
-        target: &mut E::Target,
-        mut entry: Entry<E>,
-    ) -> (E::Output, bool, VecDeque<Entry<E>>, Option<usize>) {
-        let output = entry.edit(target);
-        let (merged_or_annulled, tail, rm_saved) = self.push(entry);
-        (output, merged_or_annulled, tail, rm_saved)
+impl<E, S: Slot, B: Entries<E>> Record<E, S, B> {
+    /// Holds back the events until [`Record::end_batch`] is called,
+    /// where they are emitted as a single [`Event::Batch`].
+    ///
+    /// Batches can be nested, in which case the events are emitted when the outermost batch ends.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::sync::mpsc;
+    /// # use undo::{Add, Event, Record};
+    /// let (sender, receiver) = mpsc::channel();
+    /// let mut target = String::new();
+    /// let mut record = Record::builder().connect(sender).build();
+    ///
+    /// record.begin_batch();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// assert_eq!(receiver.try_iter().count(), 0);
+    ///
+    /// record.end_batch();
+    /// assert_eq!(
+    ///     receiver.try_iter().collect::<Vec<_>>(),
+    ///     [Event::Batch(vec![
+    ///         Event::Undo(true),
+    ///         Event::Saved(false),
+    ///         Event::Index(2),
+    ///         Event::IndexChanged { old: 0, new: 2 },
+    ///     ])]
+    /// );
+    /// ```
+    pub fn begin_batch(&mut self) {
+        self.socket.begin_batch();
     }
 
-    pub(crate) fn redo_and_push(
-        &mut self,
-        target: &mut E::Target,
-        mut entry: Entry<E>,
-    ) -> (E::Output, bool, VecDeque<Entry<E>>, Option<usize>) {
-        let output = entry.redo(target);
-        let (merged_or_annulled, tail, rm_saved) = self.push(entry);
-        (output, merged_or_annulled, tail, rm_saved)
+    /// Ends the batch started by [`Record::begin_batch`], emitting the held back
+    /// events if it is the outermost batch.
+    pub fn end_batch(&mut self) {
+        self.socket.end_batch();
     }
 
-    fn push(&mut self, entry: Entry<E>) -> (bool, VecDeque<Entry<E>>, Option<usize>) {
-        let old_index = self.index;
-        let could_undo = self.can_undo();
-        let could_redo = self.can_redo();
+    /// Marks the target as currently being in a saved.
+    ///
+    /// Clears the snapshot of the previous saved state, use [`Record::set_saved_with`]
+    /// to store a new one.
+    pub fn set_saved(&mut self) {
         let was_saved = self.is_saved();
+        self.saved = Some(self.index);
+        if let Some(snapshot) = &mut self.snapshot {
+            snapshot.clear();
+        }
+        self.socket.emit_if(!was_saved, || Event::Saved(true));
+    }
 
-        let (tail, rm_saved) = self.rm_tail();
-        // Try to merge unless the target is in a saved state.
-        let merged = match self.entries.back_mut() {
-            Some(last) if !was_saved => last.merge(entry),
-            _ => Merged::No(entry),
-        };
-
-        let merged_or_annulled = match merged {
-            Merged::Yes => true,
-            Merged::Annul => {
-                self.entries.pop_back();
-                self.index -= 1;
-                true
-            }
-            Merged::No(entry) => {
-                // If limit is reached, pop off the first edit command.
-                if self.limit() == self.index {
-                    self.entries.pop_front();
-                    self.saved = self.saved.and_then(|saved| saved.checked_sub(1));
-                } else {
-                    self.index += 1;
-                }
-                self.entries.push_back(entry);
-                false
-            }
+    /// Puts a barrier at the current position with the label,
+    /// returning `false` if there is nothing to undo.
+    ///
+    /// [`Record::undo`] stops at barriers, so edits with effects that can not be undone,
+    /// like sending an email, can not be stepped over by accident.
+    /// Use [`Record::set_undo_through_barriers`] to allow undoing past them.
+    /// Methods that go to a position, like [`Record::go_to`], are not stopped by barriers.
+    /// The entry before a barrier is never merged with the next edit.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    /// record.push_barrier("sent");
+    /// record.edit(&mut target, Add('b'));
+    ///
+    /// record.undo(&mut target);
+    /// assert!(!record.can_undo());
+    /// assert_eq!(record.undo(&mut target), None);
+    /// assert_eq!(target, "a");
+    ///
+    /// record.set_undo_through_barriers(true);
+    /// record.undo(&mut target);
+    /// assert_eq!(target, "");
+    /// ```
+    pub fn push_barrier(&mut self, label: impl Into<String>) -> bool {
+        let could_undo = self.can_undo();
+        let Some(entry) = self
+            .index
+            .checked_sub(1)
+            .and_then(|i| self.entries.get_mut(i))
+        else {
+            return false;
         };
+        entry.barrier = Some(label.into());
+        let can_undo = self.can_undo();
+        self.socket
+            .emit_if(could_undo != can_undo, || Event::Undo(can_undo));
+        true
+    }
 
-        self.socket.emit_if(could_redo, || Event::Redo(false));
-        self.socket.emit_if(!could_undo, || Event::Undo(true));
-        self.socket.emit_if(was_saved, || Event::Saved(false));
+    /// Sets if [`Record::undo`] can undo past barriers, see [`Record::push_barrier`].
+    pub fn set_undo_through_barriers(&mut self, on: bool) {
+        let could_undo = self.can_undo();
+        self.through_barriers = on;
+        let can_undo = self.can_undo();
         self.socket
-            .emit_if(old_index != self.index, || Event::Index(self.index));
-        (merged_or_annulled, tail, rm_saved)
+            .emit_if(could_undo != can_undo, || Event::Undo(can_undo));
     }
 
-    /// Calls the [`Edit::undo`] method for the active edit and sets
-    /// the previous one as the new active one.
-    pub fn undo(&mut self, target: &mut E::Target) -> Option<E::Output> {
-        self.can_undo().then(|| {
-            let old_index = self.index;
-            let was_saved = self.is_saved();
-            let output = self.entries[self.index - 1].undo(target);
-            self.index -= 1;
-            let is_saved = self.is_saved();
-            self.socket.emit_if(old_index == 1, || Event::Undo(false));
-            self.socket
-                .emit_if(old_index == self.entries.len(), || Event::Redo(true));
-            self.socket
-                .emit_if(was_saved != is_saved, || Event::Saved(is_saved));
-            self.socket.emit(|| Event::Index(self.index));
-            output
-        })
+    /// Clears the saved state of the target.
+    pub fn clear_saved(&mut self) {
+        let was_saved = self.is_saved();
+        self.saved = None;
+        if let Some(snapshot) = &mut self.snapshot {
+            snapshot.clear();
+        }
+        self.socket.emit_if(was_saved, || Event::Saved(false));
     }
 
-    /// Calls the [`Edit::redo`] method for the active edit and sets
-    /// the next one as the new active one.
-    pub fn redo(&mut self, target: &mut E::Target) -> Option<E::Output> {
-        self.can_redo().then(|| {
-            let old_index = self.index;
-            let was_saved = self.is_saved();
-            let output = self.entries[self.index].redo(target);
-            self.index += 1;
-            let is_saved = self.is_saved();
-            self.socket.emit_if(old_index == 0, || Event::Undo(true));
-            self.socket
-                .emit_if(old_index == self.len() - 1, || Event::Redo(false));
-            self.socket
-                .emit_if(was_saved != is_saved, || Event::Saved(is_saved));
-            self.socket.emit(|| Event::Index(self.index));
-            output
-        })
+    /// Pushes the entry on top of the record without executing its edit.
+    ///
+    /// This is useful when the target already reflects the edit, like when importing
+    /// edits from another source. The entry is never merged with the previous entry.
+    ///
+    /// # Examples
+    /// ```
+    /// # use core::time::Duration;
+    /// # use undo::{Add, Entry, Record};
+    /// let mut target = String::from("a");
+    /// let mut record = Record::new();
+    /// let entry = Entry::new(Add('a')).with_time_of_edit(Duration::from_secs(1));
+    /// record.push_unexecuted(entry);
+    /// assert_eq!(record.get_entry(0).unwrap().time_of_edit(), Duration::from_secs(1));
+    ///
+    /// record.undo(&mut target);
+    /// assert_eq!(target, "");
+    /// ```
+    pub fn push_unexecuted(&mut self, entry: Entry<E>) {
+        self.push_and_keep(entry, None);
     }
 
-    /// Revert the changes done to the target since the saved state.
-    pub fn revert(&mut self, target: &mut E::Target) -> Vec<E::Output> {
-        self.saved
-            .map_or_else(Vec::new, |saved| self.go_to(target, saved))
+    /// Sets the limit of the record, returning the number of removed entries.
+    ///
+    /// If there are more entries than the new limit, `truncate` decides which
+    /// entries are removed. Returns `None` and keeps the old limit
+    /// if the limit was rejected by [`Truncate::RejectIfSmaller`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use core::num::NonZeroUsize;
+    /// # use undo::{Add, Record, Truncate};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// record.edit(&mut target, Add('c'));
+    ///
+    /// let limit = NonZeroUsize::new(2).unwrap();
+    /// assert_eq!(record.set_limit(limit, Truncate::RejectIfSmaller), None);
+    /// assert_eq!(record.set_limit(limit, Truncate::Front), Some(1));
+    /// assert_eq!(record.len(), 2);
+    ///
+    /// record.undo(&mut target);
+    /// record.undo(&mut target);
+    /// assert_eq!(target, "a");
+    /// ```
+    pub fn set_limit(&mut self, limit: NonZeroUsize, truncate: Truncate) -> Option<usize> {
+        let (front, back) = self.truncate_to(limit, truncate)?;
+        Some(front + back)
     }
 
-    /// Repeatedly calls [`Edit::undo`] or [`Edit::redo`] until the edit at `index` is reached.
-    pub fn go_to(&mut self, target: &mut E::Target, index: usize) -> Vec<E::Output> {
-        if self.index == index || index > self.len() {
-            return Vec::new();
+    /// Sets the limit, returning the number of entries removed from the front and the back.
+    pub(crate) fn truncate_to(
+        &mut self,
+        limit: NonZeroUsize,
+        truncate: Truncate,
+    ) -> Option<(usize, usize)> {
+        let excess = self.len().saturating_sub(limit.get());
+        if excess > 0 && truncate == Truncate::RejectIfSmaller {
+            return None;
         }
 
+        let old_index = self.index;
         let could_undo = self.can_undo();
         let could_redo = self.can_redo();
         let was_saved = self.is_saved();
-        // Temporarily remove slot so they are not called each iteration.
-        let slot = self.socket.disconnect();
-        // Decide if we need to undo or redo to reach index.
-        let undo_or_redo = if index > self.index {
-            Record::redo
-        } else {
-            Record::undo
-        };
 
-        let capacity = self.index.abs_diff(index);
-        let mut outputs = Vec::with_capacity(capacity);
-        while self.index != index {
-            let output = undo_or_redo(self, target).unwrap();
-            outputs.push(output);
+        // Only remove the entries in front of the index, since the entries after
+        // the index can not be redone if the entries they depend on are removed.
+        let front = self.protect_front(excess.min(self.index));
+        let mut back = excess - excess.min(self.index);
+        if let Some(saved) = self.saved.filter(|_| self.protect_saved) {
+            back = back.min(self.len() - saved);
         }
+        let excess = front + back;
+        self.limit = limit;
+        drain_front(&mut self.entries, front);
+        self.entries.split_off(self.entries.len() - back);
+        self.index -= front;
+        if let Some(group) = &mut self.group {
+            group.start = group.start.saturating_sub(front);
+        }
+
+        let len = self.entries.len();
+        let shift = |i: usize| i.checked_sub(front).filter(|&i| i <= len);
+        self.saved = self.saved.and_then(shift);
+        self.savepoints
+            .retain(|_, i| shift(*i).map(|new| *i = new).is_some());
+        self.remap_discarded(shift);
 
         let can_undo = self.can_undo();
         let can_redo = self.can_redo();
         let is_saved = self.is_saved();
-        self.socket.connect(slot);
-        self.socket
-            .emit_if(could_undo != can_undo, || Event::Undo(can_undo));
+        self.socket.begin_op();
         self.socket
             .emit_if(could_redo != can_redo, || Event::Redo(can_redo));
+        self.socket
+            .emit_if(could_undo != can_undo, || Event::Undo(can_undo));
         self.socket
             .emit_if(was_saved != is_saved, || Event::Saved(is_saved));
-        self.socket.emit(|| Event::Index(self.index));
+        self.emit_index(old_index);
+        self.stats.dropped += excess;
+        self.socket.emit_if(excess > 0, || Event::Dropped(excess));
+        self.socket.end_op();
+        Some((front, back))
+    }
 
+    /// Removes the entries in the range without undoing them,
+    /// returning an iterator over the removed entries.
+    ///
+    /// Only entries that are not needed by the remaining entries can be removed,
+    /// so the range must either start at the front and only contain applied entries,
+    /// or end at the back and only contain entries that can be redone.
+    /// The saved state and savepoints in the removed range are cleared.
+    /// Returns an empty iterator and does nothing if this is not the case.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// record.edit(&mut target, Add('c'));
+    ///
+    /// let drained: Vec<_> = record.drain(..2).map(|e| *e.as_ref()).collect();
+    /// assert_eq!(drained, [Add('a'), Add('b')]);
+    /// assert_eq!(record.len(), 1);
+    /// assert_eq!(record.head(), 1);
+    /// ```
+    pub fn drain(&mut self, range: impl RangeBounds<usize>) -> impl Iterator<Item = Entry<E>> {
+        let (start, end) = range_of(range, self.len());
+        let front = start == 0 && end <= self.index;
+        let back = start >= self.index && end == self.len();
+        if start >= end || end > self.len() || !(front || back) {
+            return B::default().into_iter();
+        }
+
+        let old_index = self.index;
+        let could_undo = self.can_undo();
+        let could_redo = self.can_redo();
+        let was_saved = self.is_saved();
+
+        let drained = if front {
+            let drained = drain_front(&mut self.entries, end);
+            self.index -= end;
+            if let Some(group) = &mut self.group {
+                group.start = group.start.saturating_sub(end);
+            }
+            let shift = |i: usize| i.checked_sub(end);
+            self.saved = self.saved.and_then(shift);
+            self.savepoints
+                .retain(|_, i| shift(*i).map(|new| *i = new).is_some());
+            self.remap_discarded(shift);
+            drained
+        } else {
+            let drained = self.entries.split_off(start);
+            let keep = |i: usize| (i <= start).then_some(i);
+            self.saved = self.saved.and_then(keep);
+            self.savepoints.retain(|_, i| keep(*i).is_some());
+            self.remap_discarded(keep);
+            drained
+        };
+
+        let can_undo = self.can_undo();
+        let can_redo = self.can_redo();
+        let is_saved = self.is_saved();
+        self.socket.begin_op();
+        self.socket
+            .emit_if(could_undo != can_undo, || Event::Undo(can_undo));
+        self.socket
+            .emit_if(could_redo != can_redo, || Event::Redo(can_redo));
+        self.socket
+            .emit_if(was_saved != is_saved, || Event::Saved(is_saved));
+        self.emit_index(old_index);
+        self.socket.end_op();
+        drained.into_iter()
+    }
+
+    /// Removes all edits from the record without undoing them.
+    pub fn clear(&mut self) {
+        let old_index = self.index;
+        let could_undo = self.can_undo();
+        let could_redo = self.can_redo();
+        self.entries.clear();
+        self.saved = self.is_saved().then_some(0);
+        self.savepoints.retain(|_, i| *i == old_index);
+        self.savepoints.values_mut().for_each(|i| *i = 0);
+        self.remap_discarded(|_| None);
+        self.index = 0;
+        self.socket.begin_op();
+        self.socket.emit_if(could_undo, || Event::Undo(false));
+        self.socket.emit_if(could_redo, || Event::Redo(false));
+        self.emit_index(old_index);
+        self.socket.end_op();
+    }
+
+    /// Splits the record into two at the index, returning a new record with the entries
+    /// from the index and onward.
+    ///
+    /// The entries that have been applied to the target stay applied in both records,
+    /// so the index of this record is moved to `index` if it was after it.
+    /// The saved state and savepoints are moved to the record that contains them.
+    /// The returned record has the same limit and merge policy, but no slot or metadata.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the length of the record.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// record.edit(&mut target, Add('c'));
+    ///
+    /// let mut tail = record.split_off(1);
+    /// assert_eq!(record.len(), 1);
+    /// assert_eq!(tail.len(), 2);
+    /// assert_eq!(tail.head(), 2);
+    ///
+    /// record.append(&mut tail);
+    /// assert_eq!(record.len(), 3);
+    /// assert_eq!(record.head(), 3);
+    /// ```
+    pub fn split_off(&mut self, index: usize) -> Record<E, S, B> {
+        assert!(index <= self.len(), "index out of bounds");
+        let old_index = self.index;
+        let could_undo = self.can_undo();
+        let could_redo = self.can_redo();
+        let was_saved = self.is_saved();
+
+        let mut entries = self.entries.split_off(index);
+        // The group of the first entry is split in two.
+        if let Some(first) = entries.get_mut(0) {
+            first.joined = false;
+        }
+        let mut savepoints = BTreeMap::new();
+        self.savepoints.retain(|name, &mut i| {
+            if i > index {
+                savepoints.insert(name.clone(), i - index);
+            }
+            i <= index
+        });
+        let tail = Record {
+            limit: self.limit,
+            index: self.index.saturating_sub(index),
+            saved: self.saved.and_then(|saved| saved.checked_sub(index)),
+            socket: Socket::default(),
+            entries,
+            meta: BTreeMap::new(),
+            savepoints,
+            merge_policy: self.merge_policy,
+            group: None,
+            memory_limit: self.memory_limit,
+            cascade_annul: self.cascade_annul,
+            #[cfg(feature = "compress")]
+            compress_after: self.compress_after,
+            discarded: self.discarded.as_mut().map(|discarded| {
+                discarded
+                    .extract_if(.., |d| d.index > index)
+                    .map(|d| Discarded {
+                        index: d.index - index,
+                        pd: PhantomData,
+                        ..d
+                    })
+                    .collect()
+            }),
+            clock: self.clock,
+            hooks: self.hooks.clone(),
+            stats: Stats::default(),
+            seq: self.seq,
+            snapshot: self.snapshot.as_ref().map(|_| Snapshot::default()),
+            through_barriers: self.through_barriers,
+            protect_saved: self.protect_saved,
+        };
+        self.index = self.index.min(index);
+        self.saved = self.saved.filter(|&saved| saved <= index);
+
+        let can_undo = self.can_undo();
+        let can_redo = self.can_redo();
+        let is_saved = self.is_saved();
+        self.socket.begin_op();
+        self.socket
+            .emit_if(could_redo != can_redo, || Event::Redo(can_redo));
+        self.socket
+            .emit_if(could_undo != can_undo, || Event::Undo(can_undo));
+        self.socket
+            .emit_if(was_saved != is_saved, || Event::Saved(is_saved));
+        self.emit_index(old_index);
+        self.socket.end_op();
+        tail
+    }
+
+    /// Moves all entries from `other` to the end of this record, leaving `other` empty.
+    ///
+    /// If all entries in this record have been applied to the target, the index is moved
+    /// past the entries that have been applied in `other`. The saved state and savepoints
+    /// in `other` are only kept if this record does not have them.
+    /// If the limit is reached, the oldest entries are removed like [`Truncate::Front`].
+    pub fn append<T: Slot>(&mut self, other: &mut Record<E, T, B>) {
+        let old_index = self.index;
+        let could_undo = self.can_undo();
+        let could_redo = self.can_redo();
+        let was_saved = self.is_saved();
+
+        let len = self.len();
+        if self.index == len {
+            self.index += other.index;
+        }
+        self.saved = self.saved.or(other.saved.take().map(|saved| saved + len));
+        for (name, i) in mem::take(&mut other.savepoints) {
+            self.savepoints.entry(name).or_insert(i + len);
+        }
+        if let (Some(discarded), Some(other)) = (&mut self.discarded, &mut other.discarded) {
+            discarded.extend(other.drain(..).map(|d| Discarded {
+                index: d.index + len,
+                pd: PhantomData,
+                ..d
+            }));
+        }
+        // The appended entries are numbered after the entries in the record.
+        for entry in other.entries.iter_mut() {
+            self.seq += 1;
+            entry.seq = self.seq;
+        }
+        self.entries.append(&mut other.entries);
+        other.clear();
+
+        let can_undo = self.can_undo();
+        let can_redo = self.can_redo();
+        let is_saved = self.is_saved();
+        self.socket.begin_op();
+        self.socket
+            .emit_if(could_redo != can_redo, || Event::Redo(can_redo));
+        self.socket
+            .emit_if(could_undo != can_undo, || Event::Undo(can_undo));
+        self.socket
+            .emit_if(was_saved != is_saved, || Event::Saved(is_saved));
+        self.emit_index(old_index);
+        self.truncate_to(self.limit, Truncate::Front);
+        self.socket.end_op();
+    }
+
+    /// Pushes the entry, merging it with the previous entry using `merge` if it is provided.
+    pub(crate) fn push_with(
+        &mut self,
+        entry: Entry<E>,
+        merge: Option<MergeFn<E>>,
+    ) -> (bool, B, Option<usize>) {
+        let mut entry = entry;
+        if let Some(group) = &mut self.group {
+            if self.index > group.start {
+                entry.joined = true;
+            } else {
+                group.start = self.index;
+                entry.group = Some(group.text.clone());
+            }
+        }
+
+        let old_index = self.index;
+        let could_undo = self.can_undo();
+        let could_redo = self.can_redo();
+        let was_saved = self.is_saved();
+
+        let (tail, rm_saved) = self.rm_tail();
+        // Try to merge unless the target is in a saved state.
+        let merged = match (self.entries.back_mut(), merge) {
+            (Some(last), Some(merge)) if !was_saved && self.merge_policy.allows(last, &entry) => {
+                last.merge_with(entry, merge)
+            }
+            _ => Merged::No(entry),
+        };
+
+        let mut dropped = 0;
+        self.stats.edits += 1;
+        self.socket.begin_op();
+        let merged_or_annulled = match merged {
+            Merged::Yes => {
+                self.stats.merges += 1;
+                self.socket.emit(|| Event::Merged(self.index));
+                true
+            }
+            Merged::Annul => {
+                self.socket.emit(|| Event::Annulled(self.index));
+                self.entries.pop_back();
+                self.index -= 1;
+                self.stats.annulled += 1;
+                if let Some(merge) = merge.filter(|_| self.cascade_annul) {
+                    self.cascade(merge);
+                }
+                true
+            }
+            Merged::No(mut entry) => {
+                // Entries that are moved back from a branch keep their number.
+                if entry.seq == 0 {
+                    self.seq += 1;
+                    entry.seq = self.seq;
+                }
+                self.entries.push_back(entry);
+                self.index += 1;
+                // If a limit is reached, pop off the first edit commands.
+                dropped = self.excess();
+                if dropped > 0 {
+                    drain_front(&mut self.entries, dropped);
+                    self.index -= dropped;
+                    if let Some(group) = &mut self.group {
+                        group.start = group.start.saturating_sub(dropped);
+                    }
+                    self.saved = self.saved.and_then(|saved| saved.checked_sub(dropped));
+                    self.savepoints
+                        .retain(|_, i| i.checked_sub(dropped).map(|new| *i = new).is_some());
+                    self.remap_discarded(|i| i.checked_sub(dropped));
+                }
+                false
+            }
+        };
+        let rm_saved = rm_saved.and_then(|saved| saved.checked_sub(dropped));
+        self.stats.dropped += dropped;
+
+        self.socket.emit_if(could_redo, || Event::Redo(false));
+        self.socket.emit_if(!could_undo, || Event::Undo(true));
+        self.socket.emit_if(was_saved, || Event::Saved(false));
+        self.emit_index(old_index);
+        self.socket.emit_if(dropped > 0, || Event::Dropped(dropped));
+        self.socket.end_op();
+        #[cfg(feature = "compress")]
+        self.compress_old();
+        (merged_or_annulled, tail, rm_saved)
+    }
+
+    /// Merges the top entry with the entry before it for as long as they annul each other.
+    fn cascade(&mut self, merge: MergeFn<E>) {
+        let is_free = |e: &Entry<E>| !e.joined && e.group.is_none();
+        while self.index >= 2
+            && self.saved.is_none_or(|i| i + 1 < self.index)
+            && self.savepoints.values().all(|&i| i + 1 < self.index)
+        {
+            let top = self.entries.pop_back().unwrap();
+            let prev = self.entries.back_mut().unwrap();
+            if !is_free(&top) || !is_free(prev) || !self.merge_policy.allows(prev, &top) {
+                self.entries.push_back(top);
+                return;
+            }
+            match prev.merge_with(top, merge) {
+                Merged::Annul => {
+                    self.socket.emit(|| Event::Annulled(self.index));
+                    self.socket.emit(|| Event::Annulled(self.index - 1));
+                    self.entries.pop_back();
+                    self.index -= 2;
+                    self.stats.annulled += 1;
+                }
+                Merged::Yes => {
+                    self.index -= 1;
+                    self.stats.merges += 1;
+                    self.socket.emit(|| Event::Merged(self.index));
+                    return;
+                }
+                Merged::No(top) => {
+                    self.entries.push_back(top);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Pushes the entry and keeps the discarded tail if the record keeps discarded entries.
+    fn push_and_keep(&mut self, entry: Entry<E>, merge: Option<MergeFn<E>>) {
+        let old_index = self.index;
+        let (merged, entries, saved) = self.push_with(entry, merge);
+        if let Some(discarded) = self.discarded.as_mut().filter(|_| !entries.is_empty()) {
+            let dropped = if merged {
+                0
+            } else {
+                old_index + 1 - self.index
+            };
+            discarded.push(Discarded {
+                index: old_index - dropped,
+                entries,
+                // The saved index has already been shifted by the dropped entries.
+                saved: saved.map(|saved| saved - (old_index - dropped)),
+                pd: PhantomData,
+            });
+        }
+    }
+
+    /// Moves the discarded entries to the index returned by `f`,
+    /// or removes them if it returns `None`.
+    fn remap_discarded(&mut self, f: impl Fn(usize) -> Option<usize>) {
+        if let Some(discarded) = &mut self.discarded {
+            discarded.retain_mut(|d| f(d.index).map(|new| d.index = new).is_some());
+        }
+    }
+
+    /// Returns the number of entries that must be removed from the front
+    /// to be within the limits, never counting the last entry.
+    fn excess(&self) -> usize {
+        let mut excess = self.len().saturating_sub(self.limit());
+        if let Some((limit, size_of)) = self.memory_limit {
+            let mut size: usize = self
+                .entries
+                .iter()
+                .skip(excess)
+                .map(|e| size_of(e.as_ref()))
+                .sum();
+            while size > limit && excess + 1 < self.len() {
+                size -= size_of(self.entries.get(excess).unwrap().as_ref());
+                excess += 1;
+            }
+        }
+        self.protect_front(excess)
+    }
+
+    /// Returns how many of the `n` first entries can be removed without removing the saved state.
+    pub(crate) fn protect_front(&self, n: usize) -> usize {
+        match self.saved {
+            Some(saved) if self.protect_saved => n.min(saved),
+            _ => n,
+        }
+    }
+
+    fn undone(&mut self) {
+        let old_index = self.index;
+        let was_saved = self.is_saved();
+        self.index -= 1;
+        self.stats.undos += 1;
+        let is_saved = self.is_saved();
+        self.socket.begin_op();
+        self.socket.emit_if(old_index == 1, || Event::Undo(false));
+        self.socket
+            .emit_if(old_index == self.entries.len(), || Event::Redo(true));
+        self.socket
+            .emit_if(was_saved != is_saved, || Event::Saved(is_saved));
+        self.emit_index(old_index);
+        self.socket.end_op();
+    }
+
+    fn redone(&mut self) {
+        let old_index = self.index;
+        let was_saved = self.is_saved();
+        self.index += 1;
+        self.stats.redos += 1;
+        let is_saved = self.is_saved();
+        self.socket.begin_op();
+        self.socket.emit_if(old_index == 0, || Event::Undo(true));
+        self.socket
+            .emit_if(old_index == self.len() - 1, || Event::Redo(false));
+        self.socket
+            .emit_if(was_saved != is_saved, || Event::Saved(is_saved));
+        self.emit_index(old_index);
+        self.socket.end_op();
+        #[cfg(feature = "compress")]
+        self.compress_old();
+    }
+
+    /// Compresses the entry that has just moved far enough behind the head.
+    #[cfg(feature = "compress")]
+    fn compress_old(&mut self) {
+        if let Some((n, compress)) = self.compress_after {
+            let entry = self
+                .index
+                .checked_sub(n + 1)
+                .and_then(|i| self.entries.get_mut(i));
+            if let Some(entry) = entry {
+                compress(entry.as_mut());
+            }
+        }
+    }
+
+    /// Emits [`Event::Index`] and [`Event::IndexChanged`] if the index has changed.
+    pub(crate) fn emit_index(&mut self, old_index: usize) {
+        let index = self.index;
+        if old_index != index {
+            self.socket.emit(|| Event::Index(index));
+            self.socket.emit(|| Event::IndexChanged {
+                old: old_index,
+                new: index,
+            });
+        }
+    }
+}
+
+impl<E: Edit, S: Slot, B: Entries<E>> Record<E, S, B> {
+    /// Pushes the edit on top of the record and executes its [`Edit::edit`] method.
+    pub fn edit(&mut self, target: &mut E::Target, edit: E) -> E::Output {
+        let mut entry = Entry::new(edit);
+        let output = self.hooks.edit(&mut entry, target, self.clock.now());
+        self.push_and_keep(entry, Some(E::merge_with_meta));
+        output
+    }
+
+    /// Same as [`Record::edit`] but also stores the metadata in the entry.
+    ///
+    /// The metadata can be read with [`Entry::meta`] and [`Entry::get_meta`].
+    /// Entries with different metadata are never merged.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit_with_meta(&mut target, Add('a'), [("user", "alice"), ("category", "typing")]);
+    ///
+    /// let entry = record.get_entry(0).unwrap();
+    /// assert_eq!(entry.get_meta("user"), Some("alice"));
+    /// ```
+    pub fn edit_with_meta<K: Into<String>, V: Into<String>>(
+        &mut self,
+        target: &mut E::Target,
+        edit: E,
+        meta: impl IntoIterator<Item = (K, V)>,
+    ) -> E::Output {
+        let mut entry = Entry::new(edit);
+        entry.meta = meta
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect();
+        let output = self.hooks.edit(&mut entry, target, self.clock.now());
+        self.push_and_keep(entry, Some(E::merge_with_meta));
+        output
+    }
+
+    /// Applies the edits to the target without pushing them on the record.
+    ///
+    /// This is used for edits that were made elsewhere, like by another user in a
+    /// collaborative editor, which should not be undone locally. The entries in the record
+    /// are kept, so the edits must not conflict with them. Since the target no longer matches
+    /// any state in the record, the saved state is cleared, and [`Event::Adopted`]
+    /// is emitted with the number of adopted edits.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    /// record.adopt(&mut target, [Add('b'), Add('c')]);
+    /// assert_eq!(target, "abc");
+    /// assert_eq!(record.len(), 1);
+    /// assert!(!record.is_saved());
+    /// ```
+    pub fn adopt(
+        &mut self,
+        target: &mut E::Target,
+        edits: impl IntoIterator<Item = E>,
+    ) -> Vec<E::Output> {
+        let outputs: Vec<_> = edits
+            .into_iter()
+            .map(|edit| {
+                let mut entry = Entry::new(edit);
+                self.hooks.edit(&mut entry, target, self.clock.now())
+            })
+            .collect();
+        if !outputs.is_empty() {
+            self.socket.begin_op();
+            self.clear_saved();
+            self.socket.emit(|| Event::Adopted(outputs.len()));
+            self.socket.end_op();
+        }
+        outputs
+    }
+
+    /// Pushes the edits on top of the record and executes their [`Edit::edit`] methods,
+    /// returning the outputs of the applied edits.
+    ///
+    /// The events are held back and emitted as a single batch at the end,
+    /// see [`Record::begin_batch`]. The policy decides how the edits are merged
+    /// and if the remaining edits are applied after an edit is annulled.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, BatchPolicy, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// let outputs = record.edit_many(&mut target, "abc".chars().map(Add), BatchPolicy::Continue);
+    /// assert_eq!(outputs.len(), 3);
+    /// assert_eq!(target, "abc");
+    /// assert_eq!(record.len(), 3);
+    /// ```
+    pub fn edit_many(
+        &mut self,
+        target: &mut E::Target,
+        edits: impl IntoIterator<Item = E>,
+        policy: BatchPolicy,
+    ) -> Vec<E::Output> {
+        let merge_policy = match policy {
+            BatchPolicy::MergeAggressively => {
+                mem::replace(&mut self.merge_policy, MergePolicy::Always)
+            }
+            _ => self.merge_policy,
+        };
+        self.socket.begin_batch();
+        let mut outputs = Vec::new();
+        for edit in edits {
+            let old_index = self.index;
+            outputs.push(self.edit(target, edit));
+            // The index only goes down if the edit was annulled.
+            if policy == BatchPolicy::StopOnAnnul && self.index < old_index {
+                break;
+            }
+        }
+        self.socket.end_batch();
+        self.merge_policy = merge_policy;
+        outputs
+    }
+
+    pub(crate) fn edit_and_push(
+        &mut self,
+        target: &mut E::Target,
+        mut entry: Entry<E>,
+    ) -> (E::Output, bool, B, Option<usize>) {
+        let output = self.hooks.edit(&mut entry, target, self.clock.now());
+        let (merged_or_annulled, tail, rm_saved) = self.push(entry, true);
+        (output, merged_or_annulled, tail, rm_saved)
+    }
+
+    pub(crate) fn redo_and_push(
+        &mut self,
+        target: &mut E::Target,
+        mut entry: Entry<E>,
+    ) -> (E::Output, bool, B, Option<usize>) {
+        let output = self.hooks.redo(&mut entry, target, self.clock.now());
+        // Entries that are redone have already been merged.
+        let (merged_or_annulled, tail, rm_saved) = self.push(entry, false);
+        // The entry was redone and not edited.
+        self.stats.edits -= 1;
+        self.stats.redos += 1;
+        (output, merged_or_annulled, tail, rm_saved)
+    }
+
+    pub(crate) fn push(&mut self, entry: Entry<E>, merge: bool) -> (bool, B, Option<usize>) {
+        self.push_with(entry, merge.then_some(E::merge_with_meta))
+    }
+
+    /// Merges the entries in the range into a single entry using [`Edit::merge`].
+    ///
+    /// This allows edits to be merged after they have been pushed,
+    /// so a single undo will revert all the edits in the range.
+    /// Entries that can not be merged, or that the [`MergePolicy`] does not allow to be merged,
+    /// are kept, and entries are never merged across a barrier. The saved state
+    /// is cleared if it is between two of the entries in the range.
+    ///
+    /// Returns `true` if the range was merged into a single entry or annulled.
+    /// Returns `false` and does nothing if the range is out of bounds
+    /// or the current position is between two of the entries in the range.
+    pub fn merge_range(&mut self, range: impl RangeBounds<usize>) -> bool {
+        let (start, end) = range_of(range, self.len());
+        self.merge_range_with(start, end, self.merge_policy)
+    }
+
+    /// Merges the entries in `start..end` if the policy allows it, see [`Record::merge_range`].
+    pub(crate) fn merge_range_with(
+        &mut self,
+        start: usize,
+        end: usize,
+        policy: MergePolicy<E>,
+    ) -> bool {
+        if start >= end || end > self.len() || (start < self.index && self.index < end) {
+            return false;
+        }
+
+        let old_index = self.index;
+        let could_undo = self.can_undo();
+        let could_redo = self.can_redo();
+
+        let mut tail = self.entries.split_off(end);
+        let mut range = self.entries.split_off(start);
+        compress_entries(&mut range, start, policy, |_| false);
+        let removed = end - start - range.len();
+        let merged = range.len() <= 1;
+        self.entries.append(&mut range);
+        self.entries.append(&mut tail);
+
+        if self.index >= end {
+            self.index -= removed;
+        }
+        let remap = |i: usize| match i {
+            i if i <= start => Some(i),
+            i if i >= end => Some(i - removed),
+            _ => None,
+        };
+        self.saved = self.saved.and_then(remap);
+        self.savepoints
+            .retain(|_, i| remap(*i).map(|new| *i = new).is_some());
+        self.remap_discarded(remap);
+
+        let can_undo = self.can_undo();
+        let can_redo = self.can_redo();
+        self.socket.begin_op();
+        self.socket
+            .emit_if(could_undo != can_undo, || Event::Undo(can_undo));
+        self.socket
+            .emit_if(could_redo != can_redo, || Event::Redo(can_redo));
+        self.emit_index(old_index);
+        self.socket.end_op();
+        merged
+    }
+
+    /// Merges the two most recently applied entries into a single entry using [`Edit::merge`].
+    ///
+    /// This is useful when it is first known after the fact that the edits belong together.
+    /// The [`MergePolicy`] is not used, since the entries are merged on request,
+    /// but the entries are never merged across a barrier.
+    /// Returns `true` if the entries were merged or annulled, see [`Record::merge_range`].
+    pub fn merge_top(&mut self) -> bool {
+        self.index >= 2 && self.merge_range_with(self.index - 2, self.index, MergePolicy::Always)
+    }
+
+    /// Calls the [`Edit::undo`] method for the active edit and sets
+    /// the previous one as the new active one.
+    ///
+    /// If the edit is part of a group, all edits in the group are undone
+    /// and the output of the first edit in the group is returned.
+    pub fn undo(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        if !self.can_undo() {
+            return None;
+        }
+        let start = self.group_start(self.index - 1);
+        if start + 1 == self.index {
+            self.undo_one(target)
+        } else {
+            self.go_to(target, start).pop()
+        }
+    }
+
+    /// Undoes a single entry, even if it is part of a group.
+    pub(crate) fn undo_one(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        (self.index > 0).then(|| {
+            let entry = self.entries.get_mut(self.index - 1).unwrap();
+            let output = self.hooks.undo(entry, target, self.clock.now());
+            self.undone();
+            output
+        })
+    }
+
+    /// Undoes the applied entry at `index` even if it is not the active one.
+    ///
+    /// The entry must [commute](Edit::commute) with every entry applied after it.
+    /// It is then moved to the top of the applied entries and undone,
+    /// so it can be redone with [`Record::redo`]. The saved state and savepoints
+    /// between the entry and the current position are cleared,
+    /// since those states no longer exist.
+    ///
+    /// # Errors
+    /// Returns an error and does nothing if the entry is not applied, is part of a
+    /// group, or does not commute with an entry applied after it.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Edit, Record};
+    /// # use undo::record::UndoAtError;
+    /// enum Op {
+    ///     Add(i32),
+    ///     Mul(i32),
+    /// }
+    ///
+    /// impl Edit for Op {
+    ///     type Target = i32;
+    ///     type Output = ();
+    ///
+    ///     fn edit(&mut self, target: &mut i32) {
+    ///         match *self {
+    ///             Op::Add(n) => *target += n,
+    ///             Op::Mul(n) => *target *= n,
+    ///         }
+    ///     }
+    ///
+    ///     fn undo(&mut self, target: &mut i32) {
+    ///         match *self {
+    ///             Op::Add(n) => *target -= n,
+    ///             Op::Mul(n) => *target /= n,
+    ///         }
+    ///     }
+    ///
+    ///     fn commute(&self, other: &Op) -> bool {
+    ///         matches!((self, other), (Op::Add(_), Op::Add(_)) | (Op::Mul(_), Op::Mul(_)))
+    ///     }
+    /// }
+    ///
+    /// let mut target = 0;
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Op::Add(1));
+    /// record.edit(&mut target, Op::Add(2));
+    /// record.edit(&mut target, Op::Mul(3));
+    /// assert_eq!(target, 9);
+    ///
+    /// assert_eq!(record.undo_at(&mut target, 0), Err(UndoAtError::Conflict(2)));
+    /// record.undo(&mut target);
+    /// record.undo_at(&mut target, 0).unwrap();
+    /// assert_eq!(target, 2);
+    /// record.redo(&mut target);
+    /// assert_eq!(target, 3);
+    /// ```
+    pub fn undo_at(
+        &mut self,
+        target: &mut E::Target,
+        index: usize,
+    ) -> Result<E::Output, UndoAtError> {
+        let end = self.index;
+        if index >= end {
+            return Err(UndoAtError::OutOfBounds);
+        }
+        let grouped = |i: usize| self.entries.get(i).is_some_and(|e| e.joined);
+        if self.group.is_some() || grouped(index) || grouped(index + 1) || grouped(end) {
+            return Err(UndoAtError::Grouped);
+        }
+        let edit = self.entries.get(index).unwrap().as_ref();
+        let entries = self.entries.iter().take(end).skip(index + 1);
+        if let Some(j) = (index + 1..)
+            .zip(entries)
+            .find_map(|(j, e)| (!edit.commute(e.as_ref())).then_some(j))
+        {
+            return Err(UndoAtError::Conflict(j));
+        }
+
+        let entry = self.entries.remove(index).unwrap();
+        self.entries.insert(end - 1, entry);
+        // The states between the entry and the current position no longer exist.
+        let remap = |i: usize| (i <= index || i >= end).then_some(i);
+        self.saved = self.saved.and_then(remap);
+        self.savepoints.retain(|_, i| remap(*i).is_some());
+        self.remap_discarded(remap);
+        Ok(self.undo_one(target).unwrap())
+    }
+
+    /// Moves the entry that can be redone at `from` to `to`, shifting the entries between them.
+    ///
+    /// The entry must commute with every entry it is moved past, see [`Edit::commute`].
+    /// Only the entries that can be redone can be moved, since the target does not
+    /// need to change when they are reordered. The saved state and savepoints
+    /// between the two positions are cleared, since those states no longer exist.
+    ///
+    /// # Errors
+    /// Returns an error and does nothing if either index is not an entry that can be redone,
+    /// if the entries are in a group, or if the entry does not commute with another entry.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// # use undo::record::MoveError;
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// record.edit(&mut target, Add('c'));
+    /// record.undo(&mut target);
+    /// record.undo(&mut target);
+    ///
+    /// // Add does not commute with other edits.
+    /// assert_eq!(record.move_edit(2, 1), Err(MoveError::Conflict(1)));
+    /// assert_eq!(record.move_edit(0, 1), Err(MoveError::OutOfBounds));
+    /// ```
+    pub fn move_edit(&mut self, from: usize, to: usize) -> Result<(), MoveError> {
+        let (lo, hi) = (from.min(to), from.max(to));
+        self.check_move(lo, hi)?;
+        let edit = self.entries.get(from).unwrap().as_ref();
+        self.check_commute(edit, from, lo..=hi)?;
+
+        let entry = self.entries.remove(from).unwrap();
+        self.entries.insert(to, entry);
+        self.forget_between(lo, hi);
+        Ok(())
+    }
+
+    /// Swaps the entries that can be redone at `i` and `j`.
+    ///
+    /// Both entries must commute with every entry between them and with each other,
+    /// see [`Record::move_edit`] for more information.
+    ///
+    /// # Errors
+    /// Returns an error and does nothing if the entries can not be swapped.
+    pub fn swap(&mut self, i: usize, j: usize) -> Result<(), MoveError> {
+        let (lo, hi) = (i.min(j), i.max(j));
+        self.check_move(lo, hi)?;
+        let first = self.entries.get(lo).unwrap().as_ref();
+        self.check_commute(first, lo, lo..=hi)?;
+        let last = self.entries.get(hi).unwrap().as_ref();
+        self.check_commute(last, hi, lo + 1..hi)?;
+        if lo == hi {
+            return Ok(());
+        }
+
+        let last = self.entries.remove(hi).unwrap();
+        let first = self.entries.remove(lo).unwrap();
+        self.entries.insert(lo, last);
+        self.entries.insert(hi, first);
+        self.forget_between(lo, hi);
+        Ok(())
+    }
+
+    /// Checks that the entries from `lo` to `hi` can be redone and are not grouped.
+    fn check_move(&self, lo: usize, hi: usize) -> Result<(), MoveError> {
+        if lo < self.index || hi >= self.len() {
+            return Err(MoveError::OutOfBounds);
+        }
+        let grouped = |i: usize| self.entries.get(i).is_some_and(|e| e.joined);
+        if (lo..=hi + 1).any(grouped) {
+            return Err(MoveError::Grouped);
+        }
+        Ok(())
+    }
+
+    /// Checks that the edit at `at` commutes with the other entries at the indices.
+    fn check_commute(
+        &self,
+        edit: &E,
+        at: usize,
+        range: impl Iterator<Item = usize>,
+    ) -> Result<(), MoveError> {
+        let conflict = range
+            .filter(|&j| j != at)
+            .find(|&j| !edit.commute(self.entries.get(j).unwrap().as_ref()));
+        conflict.map_or(Ok(()), |j| Err(MoveError::Conflict(j)))
+    }
+
+    /// Clears the saved state and savepoints after `lo` and up to `hi`.
+    fn forget_between(&mut self, lo: usize, hi: usize) {
+        let remap = |i: usize| (i <= lo || i > hi).then_some(i);
+        self.saved = self.saved.and_then(remap);
+        self.savepoints.retain(|_, i| remap(*i).is_some());
+        self.remap_discarded(remap);
+    }
+
+    /// Calls the [`Edit::redo`] method for the active edit and sets
+    /// the next one as the new active one.
+    ///
+    /// If the edit is part of a group, all edits in the group are redone
+    /// and the output of the last edit in the group is returned.
+    pub fn redo(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        if !self.can_redo() {
+            return None;
+        }
+        let end = self.group_end(self.index);
+        if end == self.index + 1 {
+            self.redo_one(target)
+        } else {
+            self.go_to(target, end).pop()
+        }
+    }
+
+    /// Redoes a single entry, even if it is part of a group.
+    pub(crate) fn redo_one(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        self.can_redo().then(|| {
+            let entry = self.entries.get_mut(self.index).unwrap();
+            let output = self.hooks.redo(entry, target, self.clock.now());
+            self.redone();
+            output
+        })
+    }
+
+    /// Calls [`Record::undo`] up to `n` times, stopping early if there are no more edits to undo.
+    ///
+    /// Groups are undone as a single edit, and the undo stops at a barrier.
+    /// The events are only emitted once, after all the edits have been undone.
+    /// The returned vector contains the output of each successful [`Record::undo`] call.
+    pub fn undo_n(&mut self, target: &mut E::Target, n: usize) -> Vec<E::Output> {
+        self.socket.begin_op();
+        let outputs = (0..n).map_while(|_| self.undo(target)).collect();
+        self.socket.end_op();
+        outputs
+    }
+
+    /// Calls [`Record::redo`] up to `n` times, stopping early if there are no more edits to redo.
+    ///
+    /// Groups are redone as a single edit, and the redo stops at a barrier.
+    /// The events are only emitted once, after all the edits have been redone.
+    /// The returned vector contains the output of each successful [`Record::redo`] call.
+    pub fn redo_n(&mut self, target: &mut E::Target, n: usize) -> Vec<E::Output> {
+        self.socket.begin_op();
+        let outputs = (0..n).map_while(|_| self.redo(target)).collect();
+        self.socket.end_op();
+        outputs
+    }
+
+    /// Undoes all the edits in the record.
+    ///
+    /// The events are only emitted once, after all the edits have been undone.
+    /// This is the same as calling [`Record::go_to`] with index 0.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// assert_eq!(record.undo_all(&mut target).len(), 2);
+    /// assert_eq!(target, "");
+    /// assert_eq!(record.redo_all(&mut target).len(), 2);
+    /// assert_eq!(target, "ab");
+    /// ```
+    pub fn undo_all(&mut self, target: &mut E::Target) -> Vec<E::Output> {
+        self.go_to(target, 0)
+    }
+
+    /// Redoes all the edits in the record.
+    ///
+    /// The events are only emitted once, after all the edits have been redone.
+    /// This is the same as calling [`Record::go_to`] with the length of the record.
+    pub fn redo_all(&mut self, target: &mut E::Target) -> Vec<E::Output> {
+        self.go_to(target, self.len())
+    }
+
+    /// Marks the target as currently being in a saved state.
+    ///
+    /// If [`Builder::snapshot_on_save`] is enabled, a clone of the target is also stored
+    /// so [`Record::revert`] can restore it after the saved state has been removed.
+    /// Otherwise this is the same as [`Record::set_saved`].
+    pub fn set_saved_with(&mut self, target: &E::Target)
+    where
+        E::Target: Clone + Send + Sync + 'static,
+    {
+        self.set_saved();
+        if let Some(snapshot) = &mut self.snapshot {
+            snapshot.store(target);
+        }
+    }
+
+    /// Revert the changes done to the target since the saved state.
+    ///
+    /// If the saved state has been removed, like when it was discarded by a new edit
+    /// or dropped because of the limit, the target is restored from the snapshot
+    /// stored by [`Record::set_saved_with`] instead. Since the edits in the record no longer
+    /// apply to the restored target, the record is [cleared](Record::clear) and
+    /// [`Event::SnapshotRestored`] is emitted. Does nothing if there is no snapshot.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::builder().snapshot_on_save(true).build();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// record.set_saved_with(&target);
+    ///
+    /// record.undo(&mut target);
+    /// record.edit(&mut target, Add('c'));
+    /// assert_eq!(record.saved(), None);
+    ///
+    /// record.revert(&mut target);
+    /// assert_eq!(target, "ab");
+    /// assert!(record.is_saved());
+    /// ```
+    pub fn revert(&mut self, target: &mut E::Target) -> Vec<E::Output> {
+        self.revert_with(target)
+    }
+
+    /// Same as [`Record::revert`] but combines the outputs with the [`OutputCombiner`].
+    pub fn revert_with<C: OutputCombiner<E::Output>>(&mut self, target: &mut E::Target) -> C {
+        match self.saved {
+            Some(saved) => self.go_to_with(target, saved),
+            None => {
+                self.restore_snapshot(target);
+                C::default()
+            }
+        }
+    }
+
+    /// Restores the target from the snapshot of the saved state and clears the record.
+    fn restore_snapshot(&mut self, target: &mut E::Target) {
+        let Some(snapshot) = self.snapshot.take() else {
+            return;
+        };
+        if snapshot.restore(target) {
+            self.socket.begin_op();
+            self.clear();
+            self.savepoints.clear();
+            self.set_saved();
+            self.socket.emit(|| Event::SnapshotRestored);
+            self.socket.end_op();
+        }
+        self.snapshot = Some(snapshot);
+    }
+
+    /// Go to the savepoint with the `name`.
+    ///
+    /// Does nothing if there is no savepoint with the `name`.
+    pub fn revert_to(&mut self, target: &mut E::Target, name: &str) -> Vec<E::Output> {
+        self.savepoint(name)
+            .map_or_else(Vec::new, |index| self.go_to(target, index))
+    }
+
+    /// Same as [`Record::go_to`] but returns an error if `index` is out of bounds.
+    ///
+    /// Returns `Ok` with no outputs if the record is already at `index`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// # use undo::record::GoToError;
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    ///
+    /// assert_eq!(record.try_go_to(&mut target, 1), Ok(vec![]));
+    /// assert_eq!(record.try_go_to(&mut target, 2), Err(GoToError::OutOfBounds));
+    /// assert_eq!(record.try_go_to(&mut target, 0).unwrap().len(), 1);
+    /// ```
+    pub fn try_go_to(
+        &mut self,
+        target: &mut E::Target,
+        index: usize,
+    ) -> Result<Vec<E::Output>, GoToError> {
+        if index > self.len() {
+            return Err(GoToError::OutOfBounds);
+        }
+        Ok(self.go_to(target, index))
+    }
+
+    /// Repeatedly calls [`Edit::undo`] or [`Edit::redo`] until the edit at `index` is reached.
+    pub fn go_to(&mut self, target: &mut E::Target, index: usize) -> Vec<E::Output> {
+        self.go_to_with(target, index)
+    }
+
+    /// Same as [`Record::go_to`] but combines the outputs with the [`OutputCombiner`].
+    ///
+    /// See [`OutputCombiner`] for more information.
+    pub fn go_to_with<C: OutputCombiner<E::Output>>(
+        &mut self,
+        target: &mut E::Target,
+        index: usize,
+    ) -> C {
+        let mut outputs = C::default();
+        self.go_to_into(target, index, &mut outputs);
+        outputs
+    }
+
+    /// Goes to the index and adds the outputs to `outputs`.
+    pub(crate) fn go_to_into(
+        &mut self,
+        target: &mut E::Target,
+        index: usize,
+        outputs: &mut impl OutputCombiner<E::Output>,
+    ) {
+        if self.index == index || index > self.len() {
+            return;
+        }
+
+        let old_index = self.index;
+        let could_undo = self.can_undo();
+        let could_redo = self.can_redo();
+        let was_saved = self.is_saved();
+        // Temporarily remove slot so they are not called each iteration.
+        let slot = self.socket.disconnect();
+        // Decide if we need to undo or redo to reach index.
+        let undo_or_redo = if index > self.index {
+            Self::redo_one
+        } else {
+            Self::undo_one
+        };
+
+        outputs.reserve(self.index.abs_diff(index));
+        while self.index != index {
+            let output = undo_or_redo(self, target).unwrap();
+            outputs.push(output);
+        }
+
+        let can_undo = self.can_undo();
+        let can_redo = self.can_redo();
+        let is_saved = self.is_saved();
+        self.socket.connect(slot);
+        self.socket.begin_op();
+        self.socket
+            .emit_if(could_undo != can_undo, || Event::Undo(can_undo));
+        self.socket
+            .emit_if(could_redo != can_redo, || Event::Redo(can_redo));
+        self.socket
+            .emit_if(was_saved != is_saved, || Event::Saved(is_saved));
+        self.emit_index(old_index);
+        self.socket.end_op();
+    }
+
+    /// Restores the discarded entries at `i` by going to the index they were discarded at
+    /// and swapping them with the entries after it.
+    ///
+    /// The entries that are swapped out are kept at `i` instead, so they can be restored later.
+    /// The restored entries can then be redone. Returns the outputs of the edits
+    /// that were undone to reach the index, or an empty vector if `i` is out of bounds.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::builder().keep_discarded(true).build();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// record.undo(&mut target);
+    /// record.edit(&mut target, Add('c'));
+    /// assert_eq!(record.discarded().count(), 1);
+    ///
+    /// record.restore_discarded(&mut target, 0);
+    /// record.redo(&mut target);
+    /// assert_eq!(target, "ab");
+    /// ```
+    pub fn restore_discarded(&mut self, target: &mut E::Target, i: usize) -> Vec<E::Output> {
+        let Some(index) = self.discarded().nth(i).map(|d| d.index) else {
+            return Vec::new();
+        };
+        self.socket.begin_op();
+        let outputs = self.go_to(target, index);
+
+        let could_redo = self.can_redo();
+        let was_saved = self.is_saved();
+        let (entries, saved) = self.rm_tail();
+        let discarded = self.discarded.as_mut().unwrap();
+        let restored = if entries.is_empty() {
+            discarded.remove(i)
+        } else {
+            let saved = saved.map(|saved| saved - index);
+            mem::replace(
+                &mut discarded[i],
+                Discarded {
+                    index,
+                    entries,
+                    saved,
+                    pd: PhantomData,
+                },
+            )
+        };
+        self.entries.extend(restored.entries);
+        self.saved = self.saved.or(restored.saved.map(|saved| saved + index));
+
+        let can_redo = self.can_redo();
+        let is_saved = self.is_saved();
+        self.socket
+            .emit_if(could_redo != can_redo, || Event::Redo(can_redo));
+        self.socket
+            .emit_if(was_saved != is_saved, || Event::Saved(is_saved));
+        self.socket.end_op();
+        outputs
+    }
+}
+
+#[cfg(feature = "async")]
+impl<E: AsyncEdit, S: Slot, B: Entries<E>> Record<E, S, B> {
+    /// Pushes the edit on top of the record and awaits its [`AsyncEdit::edit`] method.
+    ///
+    /// Merging, groups, the saved state, and the events works the same as in [`Record::edit`],
+    /// except that the edits are merged with [`AsyncEdit::merge`] without the metadata.
+    /// The [hooks](crate::EditHook) are not called, since they only observe [`Edit`] commands.
+    pub async fn edit_async(&mut self, target: &mut E::Target, edit: E) -> E::Output {
+        let mut entry = Entry::new(edit);
+        let output = entry.edit_async(target, self.clock.now()).await;
+        self.push_and_keep(entry, Some(|a, b, _, _| a.merge(b)));
+        output
+    }
+
+    /// Awaits the [`AsyncEdit::undo`] method for the active edit and sets
+    /// the previous one as the new active one.
+    ///
+    /// Groups are undone the same way as in [`Record::undo`].
+    pub async fn undo_async(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        if !self.can_undo() {
+            return None;
+        }
+        let start = self.group_start(self.index - 1);
+        self.socket.begin_op();
+        let mut output = None;
+        while self.index > start {
+            let entry = self.entries.get_mut(self.index - 1).unwrap();
+            output = Some(entry.undo_async(target, self.clock.now()).await);
+            self.undone();
+        }
+        self.socket.end_op();
+        output
+    }
+
+    /// Awaits the [`AsyncEdit::redo`] method for the active edit and sets
+    /// the next one as the new active one.
+    ///
+    /// Groups are redone the same way as in [`Record::redo`].
+    pub async fn redo_async(&mut self, target: &mut E::Target) -> Option<E::Output> {
+        if !self.can_redo() {
+            return None;
+        }
+        let end = self.group_end(self.index);
+        self.socket.begin_op();
+        let mut output = None;
+        while self.index < end {
+            let entry = self.entries.get_mut(self.index).unwrap();
+            output = Some(entry.redo_async(target, self.clock.now()).await);
+            self.redone();
+        }
+        self.socket.end_op();
+        output
+    }
+}
+
+impl<E: EditWithCtx, S: Slot, B: Entries<E>> Record<E, S, B> {
+    /// Pushes the edit on top of the record and executes its [`EditWithCtx::edit`] method.
+    ///
+    /// Merging, groups, the saved state, and the events works the same as in [`Record::edit`],
+    /// except that the edits are merged with [`EditWithCtx::merge`] without the metadata.
+    /// The [hooks](crate::EditHook) are not called, since they only observe [`Edit`] commands.
+    pub fn edit_with(
+        &mut self,
+        target: &mut E::Target,
+        ctx: &mut E::Context,
+        edit: E,
+    ) -> E::Output {
+        let mut entry = Entry::new(edit);
+        let output = entry.edit_with(target, ctx, self.clock.now());
+        self.push_and_keep(entry, Some(|a, b, _, _| a.merge(b)));
+        output
+    }
+
+    /// Calls the [`EditWithCtx::undo`] method for the active edit and sets
+    /// the previous one as the new active one.
+    ///
+    /// Groups are undone the same way as in [`Record::undo`].
+    pub fn undo_with(&mut self, target: &mut E::Target, ctx: &mut E::Context) -> Option<E::Output> {
+        if !self.can_undo() {
+            return None;
+        }
+        let start = self.group_start(self.index - 1);
+        self.socket.begin_op();
+        let mut output = None;
+        while self.index > start {
+            let entry = self.entries.get_mut(self.index - 1).unwrap();
+            output = Some(entry.undo_with(target, ctx, self.clock.now()));
+            self.undone();
+        }
+        self.socket.end_op();
+        output
+    }
+
+    /// Calls the [`EditWithCtx::redo`] method for the active edit and sets
+    /// the next one as the new active one.
+    ///
+    /// Groups are redone the same way as in [`Record::redo`].
+    pub fn redo_with(&mut self, target: &mut E::Target, ctx: &mut E::Context) -> Option<E::Output> {
+        if !self.can_redo() {
+            return None;
+        }
+        let end = self.group_end(self.index);
+        self.socket.begin_op();
+        let mut output = None;
+        while self.index < end {
+            let entry = self.entries.get_mut(self.index).unwrap();
+            output = Some(entry.redo_with(target, ctx, self.clock.now()));
+            self.redone();
+        }
+        self.socket.end_op();
+        output
+    }
+}
+
+impl<E: EditRef, S: Slot, B: Entries<E>> Record<E, S, B> {
+    /// Pushes the edit on top of the record and executes its [`EditRef::edit`] method.
+    ///
+    /// Merging, groups, the saved state, and the events works the same as in [`Record::edit`],
+    /// except that the edits are merged with [`EditRef::merge`] without the metadata.
+    /// The [hooks](crate::EditHook) are not called, since they only observe [`Edit`] commands.
+    pub fn edit_ref<'a>(&mut self, target: &'a mut E::Target, edit: E) -> E::Output<'a> {
+        let mut entry = Entry::new(edit);
+        let output = entry.edit_ref(target, self.clock.now());
+        self.push_and_keep(entry, Some(|a, b, _, _| a.merge(b)));
+        output
+    }
+
+    /// Calls the [`EditRef::undo`] method for the active edit and sets
+    /// the previous one as the new active one.
+    ///
+    /// Groups are undone the same way as in [`Record::undo`].
+    pub fn undo_ref<'a>(&mut self, target: &'a mut E::Target) -> Option<E::Output<'a>> {
+        if !self.can_undo() {
+            return None;
+        }
+        let start = self.group_start(self.index - 1);
+        self.socket.begin_op();
+        // Only the output of the last call can borrow the target.
+        while self.index > start + 1 {
+            let entry = self.entries.get_mut(self.index - 1).unwrap();
+            entry.undo_ref(&mut *target, self.clock.now());
+            self.undone();
+        }
+        let entry = self.entries.get_mut(start).unwrap();
+        let output = entry.undo_ref(target, self.clock.now());
+        self.undone();
+        self.socket.end_op();
+        Some(output)
+    }
+
+    /// Calls the [`EditRef::redo`] method for the active edit and sets
+    /// the next one as the new active one.
+    ///
+    /// Groups are redone the same way as in [`Record::redo`].
+    pub fn redo_ref<'a>(&mut self, target: &'a mut E::Target) -> Option<E::Output<'a>> {
+        if !self.can_redo() {
+            return None;
+        }
+        let end = self.group_end(self.index);
+        self.socket.begin_op();
+        // Only the output of the last call can borrow the target.
+        while self.index + 1 < end {
+            let entry = self.entries.get_mut(self.index).unwrap();
+            entry.redo_ref(&mut *target, self.clock.now());
+            self.redone();
+        }
+        let entry = self.entries.get_mut(end - 1).unwrap();
+        let output = entry.redo_ref(target, self.clock.now());
+        self.redone();
+        self.socket.end_op();
+        Some(output)
+    }
+}
+
+impl<T, Err, E: Edit<Output = Result<T, Err>>, S: Slot, B: Entries<E>> Record<E, S, B> {
+    /// Same as [`Record::edit`] but the edit is only pushed if it succeeds.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Record, TryFromFn};
+    /// let mut target = 0;
+    /// let mut record = Record::new();
+    /// let add = |n: i32| {
+    ///     TryFromFn::new(move |t: &mut i32| {
+    ///         *t = t.checked_add(n).ok_or("overflow")?;
+    ///         Ok::<_, &str>(())
+    ///     })
+    /// };
+    ///
+    /// record.try_edit(&mut target, add(1)).unwrap();
+    /// assert!(record.try_edit(&mut target, add(i32::MAX)).is_err());
+    /// assert_eq!(target, 1);
+    /// assert_eq!(record.len(), 1);
+    /// ```
+    pub fn try_edit(&mut self, target: &mut E::Target, edit: E) -> Result<T, Err> {
+        let mut entry = Entry::new(edit);
+        let output = self.hooks.edit(&mut entry, target, self.clock.now())?;
+        self.push_and_keep(entry, Some(E::merge_with_meta));
+        Ok(output)
+    }
+
+    /// Same as [`Record::undo`] but the position is only changed if the undo succeeds.
+    ///
+    /// Groups are undone the same way as in [`Record::undo`], stopping at the first edit that fails.
+    /// The edits in the group that were undone before it stay undone.
+    pub fn try_undo(&mut self, target: &mut E::Target) -> Option<Result<T, Err>> {
+        if !self.can_undo() {
+            return None;
+        }
+        let start = self.group_start(self.index - 1);
+        self.socket.begin_op();
+        let mut output = None;
+        while self.index > start {
+            let entry = self.entries.get_mut(self.index - 1).unwrap();
+            let result = self.hooks.undo(entry, target, self.clock.now());
+            let failed = result.is_err();
+            output = Some(result);
+            if failed {
+                break;
+            }
+            self.undone();
+        }
+        self.socket.end_op();
+        output
+    }
+
+    /// Same as [`Record::redo`] but the position is only changed if the redo succeeds.
+    ///
+    /// Groups are redone the same way as in [`Record::redo`], stopping at the first edit that fails.
+    /// The edits in the group that were redone before it stay redone.
+    pub fn try_redo(&mut self, target: &mut E::Target) -> Option<Result<T, Err>> {
+        if !self.can_redo() {
+            return None;
+        }
+        let end = self.group_end(self.index);
+        self.socket.begin_op();
+        let mut output = None;
+        while self.index < end {
+            let entry = self.entries.get_mut(self.index).unwrap();
+            let result = self.hooks.redo(entry, target, self.clock.now());
+            let failed = result.is_err();
+            output = Some(result);
+            if failed {
+                break;
+            }
+            self.redone();
+        }
+        self.socket.end_op();
+        output
+    }
+}
+
+impl<E: Edit, S: Slot, B: Entries<E>> Record<E, S, B> {
+    /// Go back or forward in the record to the edit that was made closest to `st`
+    /// without being after it.
+    ///
+    /// Goes to the start of the record if all edits were made after `st`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::time::SystemTime;
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    ///
+    /// record.time_travel(&mut target, SystemTime::UNIX_EPOCH);
+    /// assert_eq!(target, "");
+    /// record.time_travel(&mut target, SystemTime::now());
+    /// assert_eq!(target, "ab");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn time_travel(&mut self, target: &mut E::Target, st: SystemTime) -> Vec<E::Output> {
+        let time = st
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        self.time_travel_to(target, time)
+    }
+
+    /// Go back or forward in the record to the edit that was made closest to `time`
+    /// without being after it, where `time` is the duration since the Unix epoch
+    /// as reported by the [`Clock`](crate::Clock) of the record.
+    ///
+    /// Goes to the start of the record if all edits were made after `time`.
+    pub fn time_travel_to(&mut self, target: &mut E::Target, time: Duration) -> Vec<E::Output> {
+        let index = partition_point(&self.entries, |e| e.time_of_edit() <= time);
+        self.go_to(target, index)
+    }
+}
+
+impl<E: Edit, S: Slot, B: Entries<E>> Record<E, S, B> {
+    /// Pushes the edits in the script on top of the record and executes their
+    /// [`Edit::edit`] methods, returning the outputs of the edits.
+    ///
+    /// The edits are pushed as if [`Record::edit`] was called for each of them,
+    /// so they can merge according to the merge policy of the record.
+    ///
+    /// See [`Script`] for more information.
+    pub fn apply_script(&mut self, target: &mut E::Target, script: Script<E>) -> Vec<E::Output> {
+        self.socket.begin_op();
+        let outputs = script
+            .into_iter()
+            .map(|edit| self.edit(target, edit))
+            .collect();
+        self.socket.end_op();
         outputs
     }
 }
 
-impl<E: fmt::Display, S> Record<E, S> {
+impl<E: ValidatedEdit, S: Slot, B: Entries<E>> Record<E, S, B> {
+    /// Same as [`Record::edit`] but the edit is only applied and pushed if [`ValidatedEdit::validate`] succeeds.
+    ///
+    /// No events are emitted if the validation fails.
+    pub fn edit_validated(
+        &mut self,
+        target: &mut E::Target,
+        edit: E,
+    ) -> Result<E::Output, E::Error> {
+        edit.validate(target)?;
+        Ok(self.edit(target, edit))
+    }
+}
+
+impl<E: RegionEdit, S, B: Entries<E>> Record<E, S, B> {
+    /// Returns the union of the regions affected by the edits between the saved state and the current state.
+    ///
+    /// Returns `None` if the target is in the saved state. If there is no saved state
+    /// the edits from the start of the record are used.
+    ///
+    /// # Examples
+    /// ```
+    /// # use core::ops::Range;
+    /// # use undo::{Edit, Record, RegionEdit};
+    /// struct Push(usize);
+    ///
+    /// impl Edit for Push {
+    ///     type Target = Vec<usize>;
+    ///     type Output = ();
+    ///
+    ///     fn edit(&mut self, target: &mut Vec<usize>) {
+    ///         target.push(self.0);
+    ///     }
+    ///
+    ///     fn undo(&mut self, target: &mut Vec<usize>) {
+    ///         target.pop();
+    ///     }
+    /// }
+    ///
+    /// impl RegionEdit for Push {
+    ///     type Region = Range<usize>;
+    ///
+    ///     fn affected(&self) -> Range<usize> {
+    ///         self.0..self.0 + 1
+    ///     }
+    /// }
+    ///
+    /// let mut target = Vec::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Push(0));
+    /// record.set_saved();
+    /// assert_eq!(record.dirty_region(), None);
+    ///
+    /// record.edit(&mut target, Push(1));
+    /// record.edit(&mut target, Push(2));
+    /// assert_eq!(record.dirty_region(), Some(1..3));
+    /// record.go_to(&mut target, 0);
+    /// assert_eq!(record.dirty_region(), Some(0..1));
+    /// ```
+    pub fn dirty_region(&self) -> Option<E::Region> {
+        let saved = self.saved.unwrap_or(0);
+        region::union_of(self.entries_between(saved, self.index).map(Entry::as_ref))
+    }
+}
+
+impl<E: Clone, S, B: Entries<E>> Record<E, S, B> {
+    /// Returns a script of the edits that have been applied to the target, in the order they were applied.
+    ///
+    /// Applying the script to the initial state of the target gives the current state of the target.
+    /// See [`Script`] for more information.
+    pub fn script(&self) -> Script<E> {
+        self.entries
+            .iter()
+            .take(self.index)
+            .map(|e| e.as_ref().clone())
+            .collect()
+    }
+}
+
+impl<E: fmt::Display, S, B: Entries<E>> Record<E, S, B> {
     /// Returns the string of the edit which will be undone
     /// in the next call to [`Record::undo`].
     pub fn undo_string(&self) -> Option<String> {
-        self.index.checked_sub(1).and_then(|i| self.string_at(i))
+        self.index
+            .checked_sub(1)
+            .and_then(|i| self.string_at(self.group_start(i)))
     }
 
     /// Returns the string of the edit which will be redone
@@ -382,12 +2570,242 @@ impl<E: fmt::Display, S> Record<E, S> {
     }
 
     fn string_at(&self, i: usize) -> Option<String> {
-        self.entries.get(i).map(|e| e.to_string())
+        self.entries
+            .get(i)
+            .map(|e| e.group.clone().unwrap_or_else(|| e.to_string()))
+    }
+}
+
+/// Merges consecutive entries that starts at `offset` if the merge policy allows it,
+/// unless the state between them is `pinned`.
+///
+/// Returns a map from the old positions to the new positions, where the
+/// first element is the position `offset`.
+pub(crate) fn compress_entries<E: Edit, B: Entries<E>>(
+    entries: &mut B,
+    offset: usize,
+    policy: MergePolicy<E>,
+    pinned: impl Fn(usize) -> bool,
+) -> Vec<usize> {
+    let old = mem::take(entries);
+    let mut map = Vec::with_capacity(old.len() + 1);
+    map.push(offset);
+    for (i, entry) in old.into_iter().enumerate() {
+        let (label, barrier) = (entry.label, entry.barrier.clone());
+        let merged = match entries.back_mut() {
+            Some(last) if !pinned(offset + i) && policy.allows(last, &entry) => last.merge(entry),
+            _ => Merged::No(entry),
+        };
+        match merged {
+            // The merged entry now ends where the later entry ended.
+            Merged::Yes => {
+                let last = entries.back_mut().unwrap();
+                last.label = label;
+                last.barrier = barrier;
+            }
+            Merged::Annul => {
+                entries.pop_back();
+                if let Some(last) = entries.back_mut().filter(|_| barrier.is_some()) {
+                    last.barrier = barrier;
+                }
+            }
+            Merged::No(entry) => entries.push_back(entry),
+        }
+        map.push(offset + entries.len());
+    }
+    map
+}
+
+/// Returns the start and end of the range, where `len` is used if the range is unbounded.
+pub(crate) fn range_of(range: impl RangeBounds<usize>, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    (start, end)
+}
+
+/// The error returned by [`Record::undo_at`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UndoAtError {
+    /// The entry at the index has not been applied.
+    OutOfBounds,
+    /// The entry is part of a group with other entries.
+    Grouped,
+    /// The entry does not commute with the applied entry at the index.
+    Conflict(usize),
+}
+
+impl fmt::Display for UndoAtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UndoAtError::OutOfBounds => f.write_str("the entry has not been applied"),
+            UndoAtError::Grouped => f.write_str("the entry is part of a group"),
+            UndoAtError::Conflict(index) => {
+                write!(f, "the entry conflicts with the entry at index {index}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UndoAtError {}
+
+/// The error returned by [`Record::try_go_to`] and [`History::try_go_to`](crate::History::try_go_to).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GoToError {
+    /// The position is past the end of the branch.
+    OutOfBounds,
+    /// The branch with the id does not exist.
+    UnknownBranch(usize),
+}
+
+impl fmt::Display for GoToError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GoToError::OutOfBounds => f.write_str("the position is out of bounds"),
+            GoToError::UnknownBranch(id) => write!(f, "the branch {id} does not exist"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GoToError {}
+
+/// The error returned by [`Record::move_edit`] and [`Record::swap`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MoveError {
+    /// The entry at the index can not be redone.
+    OutOfBounds,
+    /// The entry is part of a group with other entries.
+    Grouped,
+    /// The entry does not commute with the entry at the index.
+    Conflict(usize),
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::OutOfBounds => f.write_str("the entry can not be redone"),
+            MoveError::Grouped => f.write_str("the entry is part of a group"),
+            MoveError::Conflict(index) => {
+                write!(f, "the entry conflicts with the entry at index {index}")
+            }
+        }
     }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for MoveError {}
+
 impl<E> Default for Record<E> {
     fn default() -> Record<E> {
         Record::new()
     }
 }
+
+/// Consumes the record and returns the entries, from the oldest to the newest.
+impl<E, S, B: Entries<E>> IntoIterator for Record<E, S, B> {
+    type Item = Entry<E>;
+    type IntoIter = B::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+/// Returns a record with the edits pushed as unexecuted entries, see [`Record::push_unexecuted`].
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, Record};
+/// let mut target = String::from("ab");
+/// let mut record: Record<_> = [Add('a'), Add('b')].into_iter().collect();
+/// assert_eq!(record.head(), 2);
+/// record.extend([Add('c')]);
+/// target.push('c');
+///
+/// record.undo(&mut target);
+/// assert_eq!(target, "ab");
+/// let edits: Vec<_> = record.into_iter().map(|entry| *entry.as_ref()).collect();
+/// assert_eq!(edits, [Add('a'), Add('b'), Add('c')]);
+/// ```
+impl<E, B: Entries<E>> FromIterator<E> for Record<E, (), B> {
+    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+        let mut record = Record::builder().build_with();
+        record.extend(iter);
+        record
+    }
+}
+
+/// Pushes the edits as unexecuted entries, see [`Record::push_unexecuted`].
+impl<E, S: Slot, B: Entries<E>> Extend<E> for Record<E, S, B> {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        self.socket.begin_op();
+        for edit in iter {
+            self.push_unexecuted(Entry::new(edit));
+        }
+        self.socket.end_op();
+    }
+}
+
+/// Records are equal if they have the same entries, index, saved state, and savepoints.
+///
+/// The configuration and the slot are not compared.
+impl<E, S, T, B: PartialEq> PartialEq<Record<E, T, B>> for Record<E, S, B> {
+    fn eq(&self, other: &Record<E, T, B>) -> bool {
+        self.entries == other.entries
+            && self.index == other.index
+            && self.saved == other.saved
+            && self.savepoints == other.savepoints
+    }
+}
+
+/// Entries that were discarded from a [`Record`] when a new edit was pushed.
+///
+/// See [`Builder::keep_discarded`] for more information.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct Discarded<E, B = VecDeque<Entry<E>>> {
+    index: usize,
+    entries: B,
+    saved: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pd: PhantomData<fn() -> E>,
+}
+
+impl<E, B: Entries<E>> Discarded<E, B> {
+    /// Returns the index in the record the entries were discarded from.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the number of discarded edits.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no discarded edits.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the edit at the index.
+    pub fn get_entry(&self, index: usize) -> Option<&Entry<E>> {
+        self.entries.get(index)
+    }
+
+    /// Returns an iterator over the discarded edits.
+    pub fn entries(&self) -> impl Iterator<Item = &Entry<E>> {
+        self.entries.iter()
+    }
+}
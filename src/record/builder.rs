@@ -1,8 +1,17 @@
-use super::Socket;
-use crate::Record;
-use alloc::collections::VecDeque;
+#[cfg(feature = "compress")]
+use super::CompressAfter;
+use super::{Entries, MemoryLimit, Snapshot, Socket};
+use crate::clock::ClockRef;
+#[cfg(feature = "compress")]
+use crate::compress::{Compressed, Compressor};
+use crate::hook::Hooks;
+use crate::{Clock, EditHook, Entry, MergePolicy, Record, SizedEdit, Stats, TimestampMode};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
 use core::marker::PhantomData;
 use core::num::NonZeroUsize;
+#[cfg(feature = "compress")]
+use serde::{de::DeserializeOwned, Serialize};
 
 /// Builder for a [`Record`].
 ///
@@ -22,10 +31,32 @@ pub struct Builder<E, S = ()> {
     capacity: usize,
     limit: NonZeroUsize,
     saved: bool,
+    entries: Vec<Entry<E>>,
+    index: Option<usize>,
+    saved_at: Option<usize>,
+    merge_policy: MergePolicy<E>,
+    memory_limit: Option<MemoryLimit<E>>,
+    #[cfg(feature = "compress")]
+    compress_after: Option<CompressAfter<E>>,
+    keep_discarded: bool,
+    cascade_annul: bool,
+    coalesce_events: bool,
+    snapshot_on_save: bool,
+    through_barriers: bool,
+    protect_saved: bool,
+    clock: ClockRef,
+    hooks: Hooks<E>,
     socket: Socket<S>,
     pd: PhantomData<E>,
 }
 
+impl<E> Builder<E> {
+    /// Returns a new builder without a slot.
+    pub fn new() -> Builder<E> {
+        Builder::default()
+    }
+}
+
 impl<E, S> Builder<E, S> {
     /// Sets the capacity for the record.
     pub fn capacity(mut self, capacity: usize) -> Builder<E, S> {
@@ -49,30 +80,333 @@ impl<E, S> Builder<E, S> {
         self
     }
 
-    /// Connects the slot.
-    pub fn connect(mut self, slot: S) -> Builder<E, S> {
-        self.socket = Socket::new(slot);
+    /// Sets the edits the record starts with, without executing them.
+    ///
+    /// This can be used to rebuild a record from data that is persisted by the application.
+    /// By default all the edits are applied, use [`Builder::index`] to set how many of them
+    /// are applied to the target. The limit is not applied to these edits.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::from("ab");
+    /// let mut record = Record::builder()
+    ///     .entries([Add('a'), Add('b'), Add('c')])
+    ///     .index(2)
+    ///     .saved_at(1)
+    ///     .build();
+    ///
+    /// record.redo(&mut target);
+    /// assert_eq!(target, "abc");
+    /// record.revert(&mut target);
+    /// assert_eq!(target, "a");
+    /// ```
+    pub fn entries(mut self, edits: impl IntoIterator<Item = E>) -> Builder<E, S> {
+        self.entries = edits.into_iter().map(Entry::new).collect();
+        self
+    }
+
+    /// Sets the number of edits from [`Builder::entries`] that are applied to the target.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the number of edits when the record is built.
+    pub fn index(mut self, index: usize) -> Builder<E, S> {
+        self.index = Some(index);
+        self
+    }
+
+    /// Sets the position of the saved state in the edits from [`Builder::entries`].
+    ///
+    /// This replaces the saved state set with [`Builder::saved`], which marks the initial position as saved.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the number of edits when the record is built.
+    pub fn saved_at(mut self, index: usize) -> Builder<E, S> {
+        self.saved_at = Some(index);
+        self
+    }
+
+    /// Sets the policy that decides if edits are allowed to merge.
+    /// By default the edits are always allowed to merge.
+    pub fn merge_policy(mut self, merge_policy: MergePolicy<E>) -> Builder<E, S> {
+        self.merge_policy = merge_policy;
         self
     }
 
+    /// Sets the maximum estimated memory used by the edits, in bytes.
+    ///
+    /// The memory used by each edit is estimated with [`SizedEdit::size_hint`].
+    /// When the limit is reached the oldest edits are removed, the same as
+    /// when the `limit` is reached, but the most recent edit is always kept.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// # let mut target = String::new();
+    /// let mut record = Record::builder().memory_limit(8).build();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// record.edit(&mut target, Add('c'));
+    /// assert_eq!(record.len(), 2);
+    /// assert_eq!(record.memory_usage(), 8);
+    /// ```
+    pub fn memory_limit(mut self, bytes: usize) -> Builder<E, S>
+    where
+        E: SizedEdit,
+    {
+        self.memory_limit = Some((bytes, E::size_hint));
+        self
+    }
+
+    /// Sets if the edits that are discarded when a new edit is pushed should be kept.
+    ///
+    /// The discarded edits can be restored with [`Record::restore_discarded`].
+    /// By default the discarded edits are dropped.
+    pub fn keep_discarded(mut self, keep_discarded: bool) -> Builder<E, S> {
+        self.keep_discarded = keep_discarded;
+        self
+    }
+
+    /// Sets if annulments should cascade to the entries below.
+    ///
+    /// When an edit annuls the previous edit with [`Merged::Annul`](crate::Merged::Annul), the new top edit
+    /// is merged with the edit before it, and this is repeated for as long as the edits
+    /// annul each other. Edits in groups, and edits around the saved state or a savepoint,
+    /// are never merged this way. By default annulments do not cascade.
+    pub fn cascade_annul(mut self, cascade_annul: bool) -> Builder<E, S> {
+        self.cascade_annul = cascade_annul;
+        self
+    }
+
+    /// Sets if the events of each operation should be emitted as a single [`Event::Batch`](crate::Event::Batch).
+    ///
+    /// Operations that change several things at once, like [`Record::go_to`],
+    /// otherwise emit one event for each change. By default the events are not batched.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::sync::mpsc;
+    /// # use undo::{Add, Event, Record};
+    /// let (sender, receiver) = mpsc::channel();
+    /// let mut target = String::new();
+    /// let mut record = Record::builder().coalesce_events(true).connect(sender).build();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// receiver.try_iter().for_each(drop);
+    ///
+    /// record.go_to(&mut target, 0);
+    /// assert_eq!(
+    ///     receiver.try_iter().collect::<Vec<_>>(),
+    ///     [Event::Batch(vec![
+    ///         Event::Undo(false),
+    ///         Event::Redo(true),
+    ///         Event::Saved(true),
+    ///         Event::Index(0),
+    ///         Event::IndexChanged { old: 2, new: 0 },
+    ///     ])]
+    /// );
+    /// ```
+    pub fn coalesce_events(mut self, coalesce_events: bool) -> Builder<E, S> {
+        self.coalesce_events = coalesce_events;
+        self
+    }
+
+    /// Sets if a clone of the target should be stored when it is saved with [`Record::set_saved_with`].
+    ///
+    /// The clone is used by [`Record::revert`] to restore the saved state of the target
+    /// when the saved state is no longer in the record, for example after it was discarded
+    /// by a new edit or dropped because of the limit. By default no clone is stored.
+    pub fn snapshot_on_save(mut self, snapshot_on_save: bool) -> Builder<E, S> {
+        self.snapshot_on_save = snapshot_on_save;
+        self
+    }
+
+    /// Sets if [`Record::undo`] can undo past barriers (off by default).
+    ///
+    /// See [`Record::push_barrier`] for more information.
+    pub fn undo_through_barriers(mut self, on: bool) -> Builder<E, S> {
+        self.through_barriers = on;
+        self
+    }
+
+    /// Sets if the saved state is protected from being removed by the limit (off by default).
+    ///
+    /// When turned on, the entries before the saved state are never removed because
+    /// the `limit` or the memory limit was reached, or because the limit was lowered
+    /// with [`Record::set_limit`], so [`Record::revert`] can always
+    /// return to the saved state. The record can then contain more entries than the limit
+    /// until the target is saved again.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::builder().limit(2).protect_saved(true).build();
+    /// record.edit(&mut target, Add('a'));
+    /// record.set_saved();
+    /// record.edit(&mut target, Add('b'));
+    /// record.edit(&mut target, Add('c'));
+    /// record.edit(&mut target, Add('d'));
+    /// assert_eq!(record.len(), 3);
+    ///
+    /// record.revert(&mut target);
+    /// assert_eq!(target, "a");
+    /// ```
+    pub fn protect_saved(mut self, on: bool) -> Builder<E, S> {
+        self.protect_saved = on;
+        self
+    }
+
+    /// Sets the clock used for the timestamps of the edits.
+    ///
+    /// By default the [`SystemClock`](crate::SystemClock) is used when the `std` feature is enabled,
+    /// otherwise all timestamps are zero. See [`Clock`] for more information.
+    pub fn clock(mut self, clock: &'static dyn Clock) -> Builder<E, S> {
+        self.clock = ClockRef::new(clock);
+        self
+    }
+
+    /// Sets how the edits are timestamped.
+    ///
+    /// This replaces the clock set with [`Builder::clock`]. Turning the timestamps off
+    /// avoids reading the clock for every edit. By default [`TimestampMode::SystemTime`] is used
+    /// when the `std` feature is enabled, otherwise [`TimestampMode::Off`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use core::time::Duration;
+    /// # use undo::{Add, Record, TimestampMode};
+    /// let mut target = String::new();
+    /// let mut record = Record::builder().timestamps(TimestampMode::Off).build();
+    /// record.edit(&mut target, Add('a'));
+    /// assert_eq!(record.get_entry(0).unwrap().time_of_edit(), Duration::ZERO);
+    /// ```
+    pub fn timestamps(mut self, mode: TimestampMode) -> Builder<E, S> {
+        self.clock = mode.clock();
+        self
+    }
+
+    /// Adds a hook that is called before and after the edits are applied, undone, and redone.
+    ///
+    /// The hooks are called in the order they were added. See [`EditHook`] for more information.
+    pub fn with_hook(mut self, hook: impl EditHook<E> + 'static) -> Builder<E, S> {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// Connects the slot.
+    pub fn connect<T>(self, slot: T) -> Builder<E, T> {
+        Builder {
+            capacity: self.capacity,
+            limit: self.limit,
+            saved: self.saved,
+            entries: self.entries,
+            index: self.index,
+            saved_at: self.saved_at,
+            merge_policy: self.merge_policy,
+            memory_limit: self.memory_limit,
+            #[cfg(feature = "compress")]
+            compress_after: self.compress_after,
+            keep_discarded: self.keep_discarded,
+            cascade_annul: self.cascade_annul,
+            coalesce_events: self.coalesce_events,
+            snapshot_on_save: self.snapshot_on_save,
+            through_barriers: self.through_barriers,
+            protect_saved: self.protect_saved,
+            clock: self.clock,
+            hooks: self.hooks,
+            socket: Socket::new(slot),
+            pd: PhantomData,
+        }
+    }
+
     /// Builds the record.
     pub fn build(self) -> Record<E, S> {
+        self.build_with()
+    }
+
+    /// Builds the record with the entries stored in `B` instead of a [`VecDeque`](alloc::collections::VecDeque).
+    ///
+    /// See [`Entries`] for more information.
+    pub fn build_with<B: Entries<E>>(mut self) -> Record<E, S, B> {
+        let len = self.entries.len();
+        let index = self.index.unwrap_or(len);
+        assert!(index <= len, "index out of bounds");
+        assert!(
+            self.saved_at.is_none_or(|i| i <= len),
+            "saved index out of bounds"
+        );
+        let mut entries = B::with_capacity(self.capacity.max(len));
+        entries.extend(self.entries.into_iter().zip(1..).map(|(mut entry, seq)| {
+            entry.seq = seq;
+            entry
+        }));
+        self.socket.set_coalesce(self.coalesce_events);
         Record {
             limit: self.limit,
-            index: 0,
-            saved: self.saved.then_some(0),
+            index,
+            saved: self.saved_at.or(self.saved.then_some(index)),
             socket: self.socket,
-            entries: VecDeque::with_capacity(self.capacity),
+            entries,
+            meta: BTreeMap::new(),
+            savepoints: BTreeMap::new(),
+            merge_policy: self.merge_policy,
+            group: None,
+            memory_limit: self.memory_limit,
+            #[cfg(feature = "compress")]
+            compress_after: self.compress_after,
+            discarded: self.keep_discarded.then(Vec::new),
+            cascade_annul: self.cascade_annul,
+            clock: self.clock,
+            hooks: self.hooks,
+            stats: Stats::default(),
+            seq: len,
+            snapshot: self.snapshot_on_save.then(Snapshot::default),
+            through_barriers: self.through_barriers,
+            protect_saved: self.protect_saved,
         }
     }
 }
 
+#[cfg(feature = "compress")]
+impl<T, C, S> Builder<Compressed<T, C>, S>
+where
+    T: Serialize + DeserializeOwned,
+    C: Compressor,
+{
+    /// Compresses the edits that are more than `n` positions behind the head.
+    ///
+    /// The edits are decompressed again when they are undone.
+    /// See [`Compressed`] for more information.
+    ///
+    /// Requires the `compress` feature to be enabled.
+    pub fn compress_after(mut self, n: usize) -> Builder<Compressed<T, C>, S> {
+        self.compress_after = Some((n, Compressed::compress));
+        self
+    }
+}
+
 impl<E, S> Default for Builder<E, S> {
     fn default() -> Self {
         Builder {
             capacity: 0,
             limit: NonZeroUsize::new(usize::MAX).unwrap(),
             saved: true,
+            entries: Vec::new(),
+            index: None,
+            saved_at: None,
+            merge_policy: MergePolicy::Always,
+            memory_limit: None,
+            #[cfg(feature = "compress")]
+            compress_after: None,
+            keep_discarded: false,
+            cascade_annul: false,
+            coalesce_events: false,
+            snapshot_on_save: false,
+            through_barriers: false,
+            protect_saved: false,
+            clock: ClockRef::default(),
+            hooks: Hooks::default(),
             socket: Socket::default(),
             pd: PhantomData,
         }
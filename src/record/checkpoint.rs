@@ -1,25 +1,26 @@
+use crate::record::Entries;
 use crate::{Edit, Entry, Record, Slot};
 use alloc::collections::VecDeque;
+use alloc::string::String;
 use alloc::vec::Vec;
 
 #[derive(Debug)]
-enum CheckpointEntry<E> {
-    Edit {
-        saved: Option<usize>,
-        tail: VecDeque<Entry<E>>,
-    },
+enum CheckpointEntry<B> {
+    Edit { saved: Option<usize>, tail: B },
     Undo,
     Redo,
 }
 
 /// Wraps a [`Record`] and gives it checkpoint functionality.
 #[derive(Debug)]
-pub struct Checkpoint<'a, E, S> {
-    record: &'a mut Record<E, S>,
-    entries: Vec<CheckpointEntry<E>>,
+pub struct Checkpoint<'a, E, S, B = VecDeque<Entry<E>>> {
+    record: &'a mut Record<E, S, B>,
+    entries: Vec<CheckpointEntry<B>>,
+    start: usize,
+    dropped: usize,
 }
 
-impl<E, S> Checkpoint<'_, E, S> {
+impl<E, S, B: Entries<E>> Checkpoint<'_, E, S, B> {
     /// Reserves capacity for at least `additional` more entries in the checkpoint.
     ///
     /// # Panics
@@ -30,12 +31,50 @@ impl<E, S> Checkpoint<'_, E, S> {
 
     /// Commits the changes and consumes the checkpoint.
     pub fn commit(self) {}
+
+    /// Commits the changes and joins the entries that were applied during the checkpoint
+    /// into a single group with the display text, so they are undone and redone as one edit.
+    ///
+    /// The joined entries are the ones from the lowest index an edit was pushed at,
+    /// or the index when the checkpoint was created, to the current index.
+    /// Does nothing more than [`Checkpoint::commit`] if there are none.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// let mut checkpoint = record.checkpoint();
+    /// checkpoint.edit(&mut target, Add('a'));
+    /// checkpoint.edit(&mut target, Add('b'));
+    /// checkpoint.commit_as_single("Add ab");
+    ///
+    /// assert_eq!(record.undo_string().as_deref(), Some("Add ab"));
+    /// record.undo(&mut target);
+    /// assert_eq!(target, "");
+    /// ```
+    pub fn commit_as_single(mut self, text: impl Into<String>) {
+        self.update_start();
+        if self.record.index > self.start {
+            self.record
+                .join_range(self.start, self.record.index, text.into());
+        }
+    }
+
+    /// Shifts the start down by the entries the limit has dropped from the front of the record.
+    fn update_start(&mut self) {
+        let dropped = self.record.stats.dropped;
+        self.start = self.start.saturating_sub(dropped - self.dropped);
+        self.dropped = dropped;
+    }
 }
 
-impl<E: Edit, S: Slot> Checkpoint<'_, E, S> {
+impl<E: Edit, S: Slot, B: Entries<E>> Checkpoint<'_, E, S, B> {
     /// Calls the `apply` method.
     pub fn edit(&mut self, target: &mut E::Target, edit: E) -> E::Output {
+        self.start = self.start.min(self.record.index);
         let (output, _, tail, saved) = self.record.edit_and_push(target, Entry::new(edit));
+        self.update_start();
         self.entries.push(CheckpointEntry::Edit { saved, tail });
         output
     }
@@ -56,27 +95,33 @@ impl<E: Edit, S: Slot> Checkpoint<'_, E, S> {
 
     /// Cancels the changes and consumes the checkpoint.
     pub fn cancel(self, target: &mut E::Target) -> Vec<E::Output> {
-        self.entries
+        self.record.socket.begin_op();
+        let outputs = self
+            .entries
             .into_iter()
             .rev()
             .filter_map(|entry| match entry {
-                CheckpointEntry::Edit { saved, mut tail } => {
-                    let output = self.record.undo(target)?;
+                CheckpointEntry::Edit { saved, tail } => {
+                    let output = self.record.undo_one(target)?;
                     self.record.entries.pop_back();
-                    self.record.entries.append(&mut tail);
+                    self.record.entries.extend(tail);
                     self.record.saved = self.record.saved.or(saved);
                     Some(output)
                 }
                 CheckpointEntry::Undo => self.record.redo(target),
                 CheckpointEntry::Redo => self.record.undo(target),
             })
-            .collect()
+            .collect();
+        self.record.socket.end_op();
+        outputs
     }
 }
 
-impl<'a, E, S> From<&'a mut Record<E, S>> for Checkpoint<'a, E, S> {
-    fn from(record: &'a mut Record<E, S>) -> Self {
+impl<'a, E, S, B> From<&'a mut Record<E, S, B>> for Checkpoint<'a, E, S, B> {
+    fn from(record: &'a mut Record<E, S, B>) -> Self {
         Checkpoint {
+            start: record.index,
+            dropped: record.stats.dropped,
             record,
             entries: Vec::new(),
         }
@@ -1,17 +1,22 @@
+use super::Entries;
+use crate::format::Window;
 use crate::{At, Entry, Format, Record};
+use alloc::collections::VecDeque;
 use core::fmt::{self, Write};
+use core::ops::RangeBounds;
 #[cfg(feature = "std")]
 use std::time::SystemTime;
 
 /// Configurable display formatting for the [`Record`].
-pub struct Display<'a, E, S> {
-    record: &'a Record<E, S>,
+pub struct Display<'a, E, S, B = VecDeque<Entry<E>>> {
+    record: &'a Record<E, S, B>,
     format: Format,
+    window: Window,
     #[cfg(feature = "std")]
     st_fmt: &'a dyn Fn(SystemTime, SystemTime) -> String,
 }
 
-impl<'a, E, S> Display<'a, E, S> {
+impl<'a, E, S, B> Display<'a, E, S, B> {
     /// Show colored output (on by default).
     ///
     /// Requires the `colored` feature to be enabled.
@@ -39,6 +44,43 @@ impl<'a, E, S> Display<'a, E, S> {
         self
     }
 
+    /// Show only the positions in the range (all by default).
+    ///
+    /// Position `0` is the state before the first edit.
+    ///
+    /// # Examples
+    /// ```
+    /// # use undo::{Add, Record};
+    /// let mut target = String::new();
+    /// let mut record = Record::new();
+    /// record.edit(&mut target, Add('a'));
+    /// record.edit(&mut target, Add('b'));
+    /// record.edit(&mut target, Add('c'));
+    ///
+    /// let s = record.display().detailed(false).range(1..).limit(2).reverse(true).to_string();
+    /// let lines: Vec<_> = s.lines().collect();
+    /// assert_eq!(lines.len(), 2);
+    /// assert!(lines[0].ends_with("Add 'b'"));
+    /// assert!(lines[1].ends_with("Add 'c'"));
+    /// ```
+    pub fn range(&mut self, range: impl RangeBounds<usize>) -> &mut Self {
+        self.window.start = range.start_bound().cloned();
+        self.window.end = range.end_bound().cloned();
+        self
+    }
+
+    /// Show the oldest positions first (off by default).
+    pub fn reverse(&mut self, on: bool) -> &mut Self {
+        self.window.reverse = on;
+        self
+    }
+
+    /// Show at most `n` positions, keeping the most recent ones (all by default).
+    pub fn limit(&mut self, n: usize) -> &mut Self {
+        self.window.limit = Some(n);
+        self
+    }
+
     /// Sets the format used to display [`SystemTime`]s.
     ///
     /// The first input parameter is the current system time.
@@ -51,9 +93,24 @@ impl<'a, E, S> Display<'a, E, S> {
         self.st_fmt = st_fmt;
         self
     }
+
+    /// Show the local time of the edits instead of the time since they were made (off by default).
+    ///
+    /// The times are formatted as `14:02:55`. Use [`Display::set_st_fmt`] for other formats.
+    ///
+    /// Requires the `chrono` feature to be enabled.
+    #[cfg(feature = "chrono")]
+    pub fn absolute_time(&mut self, on: bool) -> &mut Self {
+        self.st_fmt = if on {
+            &crate::format::absolute_st_fmt
+        } else {
+            &crate::format::default_st_fmt
+        };
+        self
+    }
 }
 
-impl<E: fmt::Display, S> Display<'_, E, S> {
+impl<E: fmt::Display, S, B: Entries<E>> Display<'_, E, S, B> {
     fn fmt_list(
         &self,
         f: &mut fmt::Formatter,
@@ -61,7 +118,8 @@ impl<E: fmt::Display, S> Display<'_, E, S> {
         entry: Option<&Entry<E>>,
         #[cfg(feature = "std")] now: SystemTime,
     ) -> fmt::Result {
-        self.format.index(f, index)?;
+        // Entries are shown with their sequence number so the numbers do not shift.
+        self.format.index(f, entry.map_or(0, Entry::seq))?;
 
         #[cfg(feature = "std")]
         if let Some(entry) = entry {
@@ -93,36 +151,32 @@ impl<E: fmt::Display, S> Display<'_, E, S> {
     }
 }
 
-impl<'a, E, S> From<&'a Record<E, S>> for Display<'a, E, S> {
-    fn from(record: &'a Record<E, S>) -> Self {
+impl<'a, E, S, B> From<&'a Record<E, S, B>> for Display<'a, E, S, B> {
+    fn from(record: &'a Record<E, S, B>) -> Self {
         Display {
             record,
             format: Format::default(),
+            window: Window::default(),
             #[cfg(feature = "std")]
             st_fmt: &crate::format::default_st_fmt,
         }
     }
 }
 
-impl<E: fmt::Display, S> fmt::Display for Display<'_, E, S> {
+impl<E: fmt::Display, S, B: Entries<E>> fmt::Display for Display<'_, E, S, B> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         #[cfg(feature = "std")]
-        let now = SystemTime::now();
-        for (i, entry) in self.record.entries.iter().enumerate().rev() {
+        let now = self.record.clock.now_st();
+        for i in self.window.positions(self.record.len()) {
+            let entry = i.checked_sub(1).and_then(|i| self.record.entries.get(i));
             self.fmt_list(
                 f,
-                i + 1,
-                Some(entry),
+                i,
+                entry,
                 #[cfg(feature = "std")]
                 now,
             )?;
         }
-        self.fmt_list(
-            f,
-            0,
-            None,
-            #[cfg(feature = "std")]
-            now,
-        )
+        Ok(())
     }
 }
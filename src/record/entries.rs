@@ -0,0 +1,317 @@
+use crate::Entry;
+use alloc::collections::VecDeque;
+use core::mem;
+
+/// The container that stores the entries of a [`Record`](crate::Record).
+///
+/// The record stores its entries in a [`VecDeque`] by default. Implement this trait
+/// to store them in another container, like a ring buffer with a fixed capacity or
+/// a store that is kept outside of memory, and build the record with
+/// [`Builder::build_with`](crate::record::Builder::build_with).
+///
+/// Unless the saved state is protected with
+/// [`Builder::protect_saved`](crate::record::Builder::protect_saved), the record never stores
+/// more entries than its limit, except for the new entry that is pushed before the oldest
+/// entry is removed. A container with a fixed capacity should therefore be used together with
+/// a limit that is lower than its capacity, and without protecting the saved state,
+/// since the record then grows past the limit until the target is saved again.
+/// The [queue](crate::Record::queue), [checkpoint](crate::Record::checkpoint),
+/// [journal](crate::Record::journal) and [scope](crate::Record::scope) wrappers,
+/// and collecting a record from an iterator, work with any container.
+///
+/// # Examples
+/// ```
+/// # use std::collections::VecDeque;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// # use undo::{Add, Entry, Record};
+/// # use undo::record::Entries;
+/// static PUSHED: AtomicUsize = AtomicUsize::new(0);
+///
+/// #[derive(Default)]
+/// struct Log(VecDeque<Entry<Add>>);
+///
+/// impl Extend<Entry<Add>> for Log {
+///     fn extend<I: IntoIterator<Item = Entry<Add>>>(&mut self, iter: I) {
+///         self.0.extend(iter);
+///     }
+/// }
+///
+/// impl IntoIterator for Log {
+///     type Item = Entry<Add>;
+///     type IntoIter = std::collections::vec_deque::IntoIter<Entry<Add>>;
+///
+///     fn into_iter(self) -> Self::IntoIter {
+///         self.0.into_iter()
+///     }
+/// }
+///
+/// impl Entries<Add> for Log {
+///     fn len(&self) -> usize {
+///         self.0.len()
+///     }
+///
+///     fn get(&self, index: usize) -> Option<&Entry<Add>> {
+///         self.0.get(index)
+///     }
+///
+///     fn get_mut(&mut self, index: usize) -> Option<&mut Entry<Add>> {
+///         self.0.get_mut(index)
+///     }
+///
+///     fn push_back(&mut self, entry: Entry<Add>) {
+///         PUSHED.fetch_add(1, Ordering::Relaxed);
+///         self.0.push_back(entry);
+///     }
+///
+///     fn pop_back(&mut self) -> Option<Entry<Add>> {
+///         self.0.pop_back()
+///     }
+///
+///     fn pop_front(&mut self) -> Option<Entry<Add>> {
+///         self.0.pop_front()
+///     }
+///
+///     fn split_off(&mut self, at: usize) -> Self {
+///         Log(self.0.split_off(at))
+///     }
+///
+///     fn iter<'a>(&'a self) -> impl DoubleEndedIterator<Item = &'a Entry<Add>> + ExactSizeIterator
+///     where
+///         Add: 'a,
+///     {
+///         self.0.iter()
+///     }
+///
+///     fn iter_mut<'a>(
+///         &'a mut self,
+///     ) -> impl DoubleEndedIterator<Item = &'a mut Entry<Add>> + ExactSizeIterator
+///     where
+///         Add: 'a,
+///     {
+///         self.0.iter_mut()
+///     }
+/// }
+///
+/// let mut target = String::new();
+/// let mut record: Record<Add, (), Log> = Record::builder().build_with();
+/// record.edit(&mut target, Add('a'));
+/// record.edit(&mut target, Add('b'));
+/// record.undo(&mut target);
+/// assert_eq!(target, "a");
+/// assert_eq!(PUSHED.load(Ordering::Relaxed), 2);
+///
+/// let mut checkpoint = record.checkpoint();
+/// checkpoint.edit(&mut target, Add('c'));
+/// checkpoint.cancel(&mut target);
+/// assert_eq!(target, "a");
+/// assert_eq!(record.redo_string().as_deref(), Some("Add 'b'"));
+/// ```
+pub trait Entries<E>: Default + Extend<Entry<E>> + IntoIterator<Item = Entry<E>> {
+    /// Returns the number of entries.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if there are no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the entry at the index.
+    fn get(&self, index: usize) -> Option<&Entry<E>>;
+
+    /// Returns a mutable reference to the entry at the index.
+    fn get_mut(&mut self, index: usize) -> Option<&mut Entry<E>>;
+
+    /// Adds the entry to the back.
+    fn push_back(&mut self, entry: Entry<E>);
+
+    /// Removes and returns the entry at the back.
+    fn pop_back(&mut self) -> Option<Entry<E>>;
+
+    /// Removes and returns the entry at the front.
+    fn pop_front(&mut self) -> Option<Entry<E>>;
+
+    /// Splits the entries in two at the index, returning the entries from the index and onward.
+    ///
+    /// The index is never greater than the number of entries.
+    fn split_off(&mut self, at: usize) -> Self;
+
+    /// Returns an iterator over the entries from the front to the back.
+    fn iter<'a>(&'a self) -> impl DoubleEndedIterator<Item = &'a Entry<E>> + ExactSizeIterator
+    where
+        E: 'a;
+
+    /// Returns an iterator that allows modifying the entries from the front to the back.
+    fn iter_mut<'a>(
+        &'a mut self,
+    ) -> impl DoubleEndedIterator<Item = &'a mut Entry<E>> + ExactSizeIterator
+    where
+        E: 'a;
+
+    /// Returns a mutable reference to the entry at the back.
+    fn back_mut(&mut self) -> Option<&mut Entry<E>> {
+        let index = self.len().checked_sub(1)?;
+        self.get_mut(index)
+    }
+
+    /// Removes all the entries.
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Returns an empty container with space for at least `capacity` entries.
+    fn with_capacity(capacity: usize) -> Self {
+        let _ = capacity;
+        Self::default()
+    }
+
+    /// Returns the number of entries the container can hold without reallocating.
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+
+    /// Reserves capacity for at least `additional` more entries.
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
+
+    /// Shrinks the capacity as much as possible.
+    fn shrink_to_fit(&mut self) {}
+
+    /// Inserts the entry at the index, moving the entries after it towards the back.
+    fn insert(&mut self, index: usize, entry: Entry<E>) {
+        let tail = self.split_off(index);
+        self.push_back(entry);
+        self.extend(tail);
+    }
+
+    /// Removes and returns the entry at the index, moving the entries after it towards the front.
+    fn remove(&mut self, index: usize) -> Option<Entry<E>> {
+        let mut tail = self.split_off(index.min(self.len()));
+        let entry = tail.pop_front();
+        self.extend(tail);
+        entry
+    }
+
+    /// Moves all the entries in `other` to the back, leaving `other` empty.
+    fn append(&mut self, other: &mut Self) {
+        self.extend(mem::take(other));
+    }
+}
+
+impl<E> Entries<E> for VecDeque<Entry<E>> {
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        VecDeque::is_empty(self)
+    }
+
+    fn get(&self, index: usize) -> Option<&Entry<E>> {
+        VecDeque::get(self, index)
+    }
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut Entry<E>> {
+        VecDeque::get_mut(self, index)
+    }
+
+    fn push_back(&mut self, entry: Entry<E>) {
+        VecDeque::push_back(self, entry);
+    }
+
+    fn pop_back(&mut self) -> Option<Entry<E>> {
+        VecDeque::pop_back(self)
+    }
+
+    fn pop_front(&mut self) -> Option<Entry<E>> {
+        VecDeque::pop_front(self)
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        VecDeque::split_off(self, at)
+    }
+
+    fn iter<'a>(&'a self) -> impl DoubleEndedIterator<Item = &'a Entry<E>> + ExactSizeIterator
+    where
+        E: 'a,
+    {
+        VecDeque::iter(self)
+    }
+
+    fn iter_mut<'a>(
+        &'a mut self,
+    ) -> impl DoubleEndedIterator<Item = &'a mut Entry<E>> + ExactSizeIterator
+    where
+        E: 'a,
+    {
+        VecDeque::iter_mut(self)
+    }
+
+    fn back_mut(&mut self) -> Option<&mut Entry<E>> {
+        VecDeque::back_mut(self)
+    }
+
+    fn clear(&mut self) {
+        VecDeque::clear(self);
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        VecDeque::with_capacity(capacity)
+    }
+
+    fn capacity(&self) -> usize {
+        VecDeque::capacity(self)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        VecDeque::reserve(self, additional);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        VecDeque::shrink_to_fit(self);
+    }
+
+    fn insert(&mut self, index: usize, entry: Entry<E>) {
+        VecDeque::insert(self, index, entry);
+    }
+
+    fn remove(&mut self, index: usize) -> Option<Entry<E>> {
+        VecDeque::remove(self, index)
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        VecDeque::append(self, other);
+    }
+}
+
+/// Removes `n` entries from the front, returning them in the same order.
+pub(crate) fn drain_front<E, B: Entries<E>>(entries: &mut B, n: usize) -> B {
+    let mut drained = B::with_capacity(n);
+    for _ in 0..n {
+        match entries.pop_front() {
+            Some(entry) => drained.push_back(entry),
+            None => break,
+        }
+    }
+    drained
+}
+
+/// Returns the number of entries at the front where `pred` returns `true`.
+///
+/// The entries must be partitioned so all entries where `pred` returns `true`
+/// are before the entries where it returns `false`.
+pub(crate) fn partition_point<E, B: Entries<E>>(
+    entries: &B,
+    pred: impl Fn(&Entry<E>) -> bool,
+) -> usize {
+    let (mut lo, mut hi) = (0, entries.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if entries.get(mid).is_some_and(&pred) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
@@ -0,0 +1,165 @@
+//! Persistent journal of the changes done to a record.
+
+use crate::record::Entries;
+use crate::{Edit, Entry, Record, Slot};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// An operation done on a record.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Op<'a, E> {
+    /// An edit was applied.
+    Edit(&'a E),
+    /// The active edit was undone.
+    Undo,
+    /// The next edit was redone.
+    Redo,
+}
+
+/// Stores the operations done on a record so they can be replayed later.
+pub trait Journal<E> {
+    /// The error returned when the operation could not be stored.
+    type Error;
+
+    /// Stores the operation.
+    fn write(&mut self, op: Op<E>) -> Result<(), Self::Error>;
+}
+
+/// A [`Journal`] that appends the operations to a file.
+///
+/// Each operation is written on its own line, and `encode` is used to
+/// convert the edits to strings. The strings can not contain newlines.
+#[derive(Debug)]
+pub struct FileJournal<F> {
+    file: File,
+    encode: F,
+}
+
+impl<F> FileJournal<F> {
+    /// Opens the file at `path` in append mode, creating it if it does not exist.
+    pub fn open(path: impl AsRef<Path>, encode: F) -> io::Result<FileJournal<F>> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(FileJournal { file, encode })
+    }
+}
+
+impl<E, F: FnMut(&E) -> String> Journal<E> for FileJournal<F> {
+    type Error = io::Error;
+
+    fn write(&mut self, op: Op<E>) -> io::Result<()> {
+        match op {
+            Op::Edit(edit) => writeln!(self.file, "edit {}", (self.encode)(edit))?,
+            Op::Undo => writeln!(self.file, "undo")?,
+            Op::Redo => writeln!(self.file, "redo")?,
+        }
+        self.file.flush()
+    }
+}
+
+/// Wraps a [`Record`] and writes every change to a [`Journal`] before it is applied.
+///
+/// # Examples
+/// ```
+/// # use std::io::BufReader;
+/// # use std::fs::File;
+/// # use undo::{Add, Record};
+/// # use undo::record::journal::FileJournal;
+/// # let path = std::env::temp_dir().join("undo-journal-doc.txt");
+/// # std::fs::remove_file(&path).ok();
+/// let mut target = String::new();
+/// let mut record = Record::new();
+/// let file = FileJournal::open(&path, |add: &Add| add.0.to_string())?;
+/// let mut journaled = record.journal(file);
+/// journaled.edit(&mut target, Add('a'))?;
+/// journaled.edit(&mut target, Add('b'))?;
+/// journaled.undo(&mut target)?;
+///
+/// let mut recovered = String::new();
+/// let reader = BufReader::new(File::open(&path)?);
+/// let record = Record::replay(reader, &mut recovered, |s| s.parse().ok().map(Add))?;
+/// assert_eq!(recovered, "a");
+/// assert_eq!(record.len(), 2);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Journaled<'a, E, S, J, B = VecDeque<Entry<E>>> {
+    record: &'a mut Record<E, S, B>,
+    journal: J,
+}
+
+impl<E, S, J, B> Journaled<'_, E, S, J, B> {
+    /// Consumes the wrapper and returns the journal.
+    pub fn into_journal(self) -> J {
+        self.journal
+    }
+}
+
+impl<E: Edit, S: Slot, J: Journal<E>, B: Entries<E>> Journaled<'_, E, S, J, B> {
+    /// Writes the edit to the journal and calls [`Record::edit`].
+    pub fn edit(&mut self, target: &mut E::Target, edit: E) -> Result<E::Output, J::Error> {
+        self.journal.write(Op::Edit(&edit))?;
+        Ok(self.record.edit(target, edit))
+    }
+
+    /// Writes the undo to the journal and calls [`Record::undo`].
+    pub fn undo(&mut self, target: &mut E::Target) -> Result<Option<E::Output>, J::Error> {
+        if !self.record.can_undo() {
+            return Ok(None);
+        }
+        self.journal.write(Op::Undo)?;
+        Ok(self.record.undo(target))
+    }
+
+    /// Writes the redo to the journal and calls [`Record::redo`].
+    pub fn redo(&mut self, target: &mut E::Target) -> Result<Option<E::Output>, J::Error> {
+        if !self.record.can_redo() {
+            return Ok(None);
+        }
+        self.journal.write(Op::Redo)?;
+        Ok(self.record.redo(target))
+    }
+}
+
+impl<'a, E, S, J, B> Journaled<'a, E, S, J, B> {
+    pub(crate) fn new(record: &'a mut Record<E, S, B>, journal: J) -> Self {
+        Journaled { record, journal }
+    }
+}
+
+impl<E: Edit> Record<E> {
+    /// Creates a new record by replaying the operations in a journal written by a [`FileJournal`].
+    ///
+    /// The operations are applied to `target`, and `decode` is used to convert the strings back to edits.
+    ///
+    /// # Errors
+    /// Returns an error if the journal could not be read or contains an invalid line.
+    pub fn replay(
+        reader: impl BufRead,
+        target: &mut E::Target,
+        mut decode: impl FnMut(&str) -> Option<E>,
+    ) -> io::Result<Record<E>> {
+        let invalid = |line: &str| io::Error::new(io::ErrorKind::InvalidData, line);
+        let mut record = Record::new();
+        for line in reader.lines() {
+            let line = line?;
+            match line.as_str() {
+                "undo" => {
+                    record.undo(target);
+                }
+                "redo" => {
+                    record.redo(target);
+                }
+                _ => {
+                    let edit = line
+                        .strip_prefix("edit ")
+                        .and_then(&mut decode)
+                        .ok_or_else(|| invalid(&line))?;
+                    record.edit(target, edit);
+                }
+            }
+        }
+        Ok(record)
+    }
+}
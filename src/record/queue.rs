@@ -1,4 +1,6 @@
-use crate::{Edit, Record, Slot};
+use crate::record::Entries;
+use crate::{Edit, Entry, Record, Slot};
+use alloc::collections::VecDeque;
 use alloc::vec::Vec;
 
 #[derive(Debug)]
@@ -26,12 +28,12 @@ enum QueueEntry<E> {
 /// assert_eq!(string, "abc");
 /// ```
 #[derive(Debug)]
-pub struct Queue<'a, E, S> {
-    record: &'a mut Record<E, S>,
+pub struct Queue<'a, E, S, B = VecDeque<Entry<E>>> {
+    record: &'a mut Record<E, S, B>,
     entries: Vec<QueueEntry<E>>,
 }
 
-impl<E, S> Queue<'_, E, S> {
+impl<E, S, B> Queue<'_, E, S, B> {
     /// Reserves capacity for at least `additional` more entries in the queue.
     ///
     /// # Panics
@@ -59,22 +61,26 @@ impl<E, S> Queue<'_, E, S> {
     pub fn cancel(self) {}
 }
 
-impl<E: Edit, S: Slot> Queue<'_, E, S> {
+impl<E: Edit, S: Slot, B: Entries<E>> Queue<'_, E, S, B> {
     /// Applies the queued edits.
     pub fn commit(self, target: &mut E::Target) -> Vec<E::Output> {
-        self.entries
+        self.record.socket.begin_op();
+        let outputs = self
+            .entries
             .into_iter()
             .filter_map(|entry| match entry {
                 QueueEntry::Edit(edit) => Some(self.record.edit(target, edit)),
                 QueueEntry::Undo => self.record.undo(target),
                 QueueEntry::Redo => self.record.redo(target),
             })
-            .collect()
+            .collect();
+        self.record.socket.end_op();
+        outputs
     }
 }
 
-impl<'a, E, S> From<&'a mut Record<E, S>> for Queue<'a, E, S> {
-    fn from(record: &'a mut Record<E, S>) -> Self {
+impl<'a, E, S, B> From<&'a mut Record<E, S, B>> for Queue<'a, E, S, B> {
+    fn from(record: &'a mut Record<E, S, B>) -> Self {
         Queue {
             record,
             entries: Vec::new(),
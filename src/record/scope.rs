@@ -0,0 +1,115 @@
+use crate::record::Entries;
+use crate::{Edit, Entry, Record, Slot};
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Wraps a [`Record`] and records the edits into a nested record with its own undo history.
+///
+/// When the scope is committed the applied edits are moved into the parent record
+/// as a single group that is undone and redone as one edit.
+/// When the scope is cancelled or dropped the edits are undone and discarded.
+///
+/// This is useful for modal dialogs that have their own undo history and "OK/Cancel" buttons.
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, Record};
+/// let mut target = String::new();
+/// let mut record = Record::new();
+/// record.edit(&mut target, Add('a'));
+///
+/// let mut scope = record.scope(&mut target);
+/// scope.edit(Add('b'));
+/// scope.edit(Add('c'));
+/// scope.undo();
+/// assert_eq!(scope.target(), "ab");
+/// drop(scope);
+/// assert_eq!(target, "a");
+///
+/// let mut scope = record.scope(&mut target);
+/// scope.edit(Add('b'));
+/// scope.edit(Add('c'));
+/// scope.commit("Add bc");
+/// assert_eq!(target, "abc");
+///
+/// record.undo(&mut target);
+/// assert_eq!(target, "a");
+/// ```
+#[derive(Debug)]
+pub struct Scope<'a, E: Edit, S: Slot, B = VecDeque<Entry<E>>> {
+    parent: &'a mut Record<E, S, B>,
+    target: &'a mut E::Target,
+    record: Record<E>,
+}
+
+impl<'a, E: Edit, S: Slot, B: Entries<E>> Scope<'a, E, S, B> {
+    pub(crate) fn new(parent: &'a mut Record<E, S, B>, target: &'a mut E::Target) -> Self {
+        Scope {
+            parent,
+            target,
+            record: Record::new(),
+        }
+    }
+
+    /// Returns a reference to the target.
+    pub fn target(&self) -> &E::Target {
+        self.target
+    }
+
+    /// Returns the nested record.
+    pub fn record(&self) -> &Record<E> {
+        &self.record
+    }
+
+    /// Pushes the edit to the nested record and executes its [`Edit::edit`] method.
+    pub fn edit(&mut self, edit: E) -> E::Output {
+        self.record.edit(self.target, edit)
+    }
+
+    /// Undoes the active edit in the nested record.
+    pub fn undo(&mut self) -> Option<E::Output> {
+        self.record.undo(self.target)
+    }
+
+    /// Redoes the next edit in the nested record.
+    pub fn redo(&mut self) -> Option<E::Output> {
+        self.record.redo(self.target)
+    }
+
+    /// Moves the applied edits into the parent record as a single group
+    /// with the display text, and consumes the scope.
+    ///
+    /// The edits that can be redone in the nested record are discarded.
+    pub fn commit(mut self, text: impl Into<String>) {
+        let index = self.record.head();
+        let entries: Vec<_> = self.record.drain(..index).collect();
+        if entries.is_empty() {
+            return;
+        }
+
+        let n = entries.len();
+        self.parent.socket.begin_op();
+        for entry in entries {
+            self.parent.push_unexecuted(entry);
+        }
+        let end = self.parent.index;
+        // Entries might have been removed from the front if the limit was reached.
+        let start = end.saturating_sub(n);
+        self.parent.join_range(start, end, text.into());
+        self.parent.socket.end_op();
+    }
+
+    /// Undoes and discards the edits, and consumes the scope.
+    ///
+    /// This is the same as dropping the scope, but returns the outputs of the undone edits.
+    pub fn cancel(mut self) -> Vec<E::Output> {
+        self.record.undo_all(self.target)
+    }
+}
+
+impl<E: Edit, S: Slot, B> Drop for Scope<'_, E, S, B> {
+    fn drop(&mut self) {
+        self.record.undo_all(self.target);
+    }
+}
@@ -0,0 +1,103 @@
+use alloc::vec::{IntoIter, Vec};
+use core::slice::Iter;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// An ordered list of edits that can be replayed on a target.
+///
+/// Returned by [`Record::script`](crate::Record::script) and
+/// replayed by [`Record::apply_script`](crate::Record::apply_script).
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, Record};
+/// let mut target = String::new();
+/// let mut record = Record::new();
+/// record.edit(&mut target, Add('a'));
+/// record.edit(&mut target, Add('b'));
+/// record.edit(&mut target, Add('c'));
+/// record.undo(&mut target);
+///
+/// let script = record.script();
+/// assert_eq!(script.len(), 2);
+///
+/// let mut copy = String::new();
+/// Record::new().apply_script(&mut copy, script);
+/// assert_eq!(copy, "ab");
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Script<E> {
+    edits: Vec<E>,
+}
+
+impl<E> Script<E> {
+    /// Returns a new empty script.
+    pub const fn new() -> Script<E> {
+        Script { edits: Vec::new() }
+    }
+
+    /// Pushes the edit to the end of the script.
+    pub fn push(&mut self, edit: E) {
+        self.edits.push(edit);
+    }
+
+    /// Returns the number of edits in the script.
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    /// Returns `true` if the script is empty.
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Returns an iterator over the edits in the script.
+    pub fn edits(&self) -> Iter<'_, E> {
+        self.edits.iter()
+    }
+
+    /// Returns the edits in the script.
+    pub fn into_edits(self) -> Vec<E> {
+        self.edits
+    }
+}
+
+impl<E> Default for Script<E> {
+    fn default() -> Self {
+        Script::new()
+    }
+}
+
+impl<E> From<Vec<E>> for Script<E> {
+    fn from(edits: Vec<E>) -> Self {
+        Script { edits }
+    }
+}
+
+impl<E> FromIterator<E> for Script<E> {
+    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+        Script {
+            edits: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<E> IntoIterator for Script<E> {
+    type Item = E;
+    type IntoIter = IntoIter<E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.edits.into_iter()
+    }
+}
+
+impl<'a, E> IntoIterator for &'a Script<E> {
+    type Item = &'a E;
+    type IntoIter = Iter<'a, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.edits.iter()
+    }
+}
@@ -0,0 +1,66 @@
+use crate::Edit;
+use alloc::sync::Arc;
+use core::fmt::{self, Debug, Formatter};
+
+/// A clone of the target that is restored by value.
+trait Stored<E>: Send + Sync {
+    fn restore(&self, target: &mut E::Target)
+    where
+        E: Edit;
+}
+
+struct Held<T>(T);
+
+impl<E: Edit<Target = T>, T: Clone + Send + Sync> Stored<E> for Held<T> {
+    fn restore(&self, target: &mut T) {
+        target.clone_from(&self.0);
+    }
+}
+
+/// The target as it was in the saved state.
+///
+/// See [`Builder::snapshot_on_save`](super::Builder::snapshot_on_save) for more information.
+pub(crate) struct Snapshot<E>(Option<Arc<dyn Stored<E>>>);
+
+impl<E> Snapshot<E> {
+    pub fn is_some(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+}
+
+impl<E: Edit> Snapshot<E> {
+    pub fn store(&mut self, target: &E::Target)
+    where
+        E::Target: Clone + Send + Sync + 'static,
+    {
+        self.0 = Some(Arc::new(Held(target.clone())));
+    }
+
+    /// Returns `false` if there is no snapshot to restore.
+    pub fn restore(&self, target: &mut E::Target) -> bool {
+        self.0.as_ref().map(|s| s.restore(target)).is_some()
+    }
+}
+
+impl<E> Clone for Snapshot<E> {
+    fn clone(&self) -> Self {
+        Snapshot(self.0.clone())
+    }
+}
+
+impl<E> Default for Snapshot<E> {
+    fn default() -> Self {
+        Snapshot(None)
+    }
+}
+
+impl<E> Debug for Snapshot<E> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let state = if self.is_some() { "Some(..)" } else { "None" };
+        write!(f, "Snapshot({state})")
+    }
+}
@@ -0,0 +1,202 @@
+#[cfg(feature = "std")]
+use crate::{Edit, EditHook};
+use core::ops::Range;
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A part of the target that can be combined with other parts.
+///
+/// Used by [`RegionEdit`] to report the parts of the target that are changed by the edits.
+pub trait Region {
+    /// Extends the region so it also covers `other`.
+    fn union(&mut self, other: Self);
+}
+
+/// Covers the range from the lowest start to the highest end of the two ranges.
+///
+/// Empty ranges do not change the region.
+impl<T: PartialOrd> Region for Range<T> {
+    fn union(&mut self, other: Self) {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            *self = other;
+            return;
+        }
+        if other.start < self.start {
+            self.start = other.start;
+        }
+        if other.end > self.end {
+            self.end = other.end;
+        }
+    }
+}
+
+impl Region for () {
+    fn union(&mut self, _: ()) {}
+}
+
+/// Reports the part of the target that is changed by an edit command.
+///
+/// The region must cover the changes done by both applying and undoing the edit.
+/// Used by methods like [`Record::dirty_region`](crate::Record::dirty_region) and
+/// [`History::dirty_region`](crate::History::dirty_region), and by [`RegionTracker`].
+///
+/// # Examples
+/// ```
+/// # use core::ops::Range;
+/// # use undo::RegionEdit;
+/// struct Replace {
+///     at: usize,
+///     old: String,
+///     new: String,
+/// }
+///
+/// impl RegionEdit for Replace {
+///     type Region = Range<usize>;
+///
+///     fn affected(&self) -> Range<usize> {
+///         self.at..self.at + self.old.len().max(self.new.len())
+///     }
+/// }
+/// ```
+pub trait RegionEdit {
+    /// The region type.
+    type Region: Region;
+
+    /// Returns the part of the target that is changed by the edit.
+    fn affected(&self) -> Self::Region;
+}
+
+/// Returns the union of the regions affected by the edits, or `None` if there are no edits.
+#[cfg(feature = "alloc")]
+pub(crate) fn union_of<'a, E: RegionEdit + 'a>(
+    edits: impl IntoIterator<Item = &'a E>,
+) -> Option<E::Region> {
+    edits.into_iter().fold(None, |region, edit| {
+        let affected = edit.affected();
+        Some(match region {
+            Some(mut region) => {
+                region.union(affected);
+                region
+            }
+            None => affected,
+        })
+    })
+}
+
+/// An [`EditHook`] that collects the regions changed by the edits that are applied, undone, and redone.
+///
+/// The tracker is cheap to clone and all the clones share the same region, so one clone can be
+/// added to the structure while another one is kept to take the region, for example after
+/// the events have been handled and the changes should be rendered.
+///
+/// # Examples
+/// ```
+/// # use core::ops::Range;
+/// # use undo::{Edit, Record, RegionEdit, RegionTracker};
+/// struct Push(usize);
+///
+/// impl Edit for Push {
+///     type Target = Vec<usize>;
+///     type Output = ();
+///
+///     fn edit(&mut self, target: &mut Vec<usize>) {
+///         target.push(self.0);
+///     }
+///
+///     fn undo(&mut self, target: &mut Vec<usize>) {
+///         target.pop();
+///     }
+/// }
+///
+/// impl RegionEdit for Push {
+///     type Region = Range<usize>;
+///
+///     fn affected(&self) -> Range<usize> {
+///         self.0..self.0 + 1
+///     }
+/// }
+///
+/// let tracker = RegionTracker::new();
+/// let mut target = Vec::new();
+/// let mut record = Record::builder().with_hook(tracker.clone()).build();
+/// record.edit(&mut target, Push(0));
+/// record.edit(&mut target, Push(1));
+/// assert_eq!(tracker.take(), Some(0..2));
+///
+/// record.undo(&mut target);
+/// assert_eq!(tracker.take(), Some(1..2));
+/// assert_eq!(tracker.take(), None);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct RegionTracker<R> {
+    region: Arc<Mutex<Option<R>>>,
+}
+
+#[cfg(feature = "std")]
+impl<R> RegionTracker<R> {
+    /// Returns a new tracker with no changed region.
+    pub fn new() -> RegionTracker<R> {
+        RegionTracker {
+            region: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns the region changed since the last call, or `None` if nothing has changed.
+    pub fn take(&self) -> Option<R> {
+        self.region
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Region> RegionTracker<R> {
+    fn add(&self, other: R) {
+        let mut region = self.region.lock().unwrap_or_else(PoisonError::into_inner);
+        match region.as_mut() {
+            Some(region) => region.union(other),
+            None => *region = Some(other),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R> Clone for RegionTracker<R> {
+    fn clone(&self) -> Self {
+        RegionTracker {
+            region: self.region.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R> Default for RegionTracker<R> {
+    fn default() -> Self {
+        RegionTracker::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: RegionEdit> EditHook<E> for RegionTracker<E::Region>
+where
+    E::Region: Send,
+{
+    fn after_edit(&self, edit: &E, _: &E::Target, _: &E::Output)
+    where
+        E: Edit,
+    {
+        self.add(edit.affected());
+    }
+
+    fn after_undo(&self, edit: &E, _: &E::Target, _: &E::Output)
+    where
+        E: Edit,
+    {
+        self.add(edit.affected());
+    }
+}
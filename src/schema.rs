@@ -0,0 +1,159 @@
+//! Versioned serialization format for the data structures.
+//!
+//! The types in this module are decoupled from the internals of the data structures,
+//! so data serialized with them can be read by later versions of the crate.
+//! Use [`Record::into_serializable`](crate::Record::into_serializable) and
+//! [`History::into_serializable`](crate::History::into_serializable) to convert
+//! the structures into the format, and the `from_serializable` methods to convert them back.
+//!
+//! # Versions
+//!
+//! * `0`: The format derived from the internal structures before the format was versioned.
+//!   It does not contain a `version` field, and is read by the same types as version `1`.
+//! * `1`: The current format.
+
+use crate::At;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+/// The current version of the format.
+pub const VERSION: u32 = 1;
+
+/// Serializable form of a [`Record`](crate::Record).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordDe<E> {
+    /// The version of the format.
+    #[serde(default)]
+    pub version: u32,
+    /// The maximum number of entries.
+    pub limit: usize,
+    /// The current position in the record.
+    pub index: usize,
+    /// The saved state.
+    pub saved: Option<usize>,
+    /// The entries in the record.
+    pub entries: Vec<EntryDe<E>>,
+    /// The document-level metadata.
+    #[serde(default)]
+    pub meta: BTreeMap<String, String>,
+    /// The named savepoints.
+    #[serde(default)]
+    pub savepoints: BTreeMap<String, usize>,
+}
+
+/// Serializable form of a [`History`](crate::History).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryDe<E> {
+    /// The version of the format.
+    #[serde(default)]
+    pub version: u32,
+    /// The id of the root branch.
+    pub root: usize,
+    /// The generation of the branch ids.
+    #[serde(default)]
+    pub generation: u32,
+    /// The saved state if it is not in the root branch.
+    pub saved: Option<At>,
+    /// The root branch.
+    pub record: RecordDe<E>,
+    /// The branches in the history, including the empty root branch.
+    pub branches: BTreeMap<usize, BranchDe<E>>,
+    /// The number of entries that have been dropped because of the limit.
+    #[serde(default)]
+    pub dropped: usize,
+    /// The named savepoints.
+    #[serde(default)]
+    pub savepoints: BTreeMap<String, At>,
+}
+
+/// Serializable form of a [`Branch`](crate::history::Branch).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BranchDe<E> {
+    /// The parent of the branch.
+    pub parent: At,
+    /// The entries in the branch.
+    pub entries: Vec<EntryDe<E>>,
+    /// The label of the branch.
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Serializable form of an [`Entry`](crate::Entry).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EntryDe<E> {
+    /// The edit.
+    pub edit: E,
+    /// The stable label of the entry.
+    #[serde(default)]
+    pub label: Option<At>,
+    /// The metadata of the entry.
+    #[serde(default)]
+    pub meta: BTreeMap<String, String>,
+    /// The display text of the group if the entry starts a group.
+    #[serde(default)]
+    pub group: Option<String>,
+    /// If the entry is in the same group as the previous entry.
+    #[serde(default)]
+    pub joined: bool,
+    /// The sequence number of the entry.
+    #[serde(default)]
+    pub seq: usize,
+    /// The label of the barrier after the entry.
+    #[serde(default)]
+    pub barrier: Option<String>,
+    /// The time the edit method was called.
+    #[cfg(feature = "std")]
+    #[serde(default = "epoch")]
+    pub st_edit: SystemTime,
+    /// The time the undo method was called.
+    #[cfg(feature = "std")]
+    #[serde(default = "epoch")]
+    pub st_undo: SystemTime,
+    /// The time the redo method was called.
+    #[cfg(feature = "std")]
+    #[serde(default = "epoch")]
+    pub st_redo: SystemTime,
+}
+
+#[cfg(feature = "std")]
+fn epoch() -> SystemTime {
+    SystemTime::UNIX_EPOCH
+}
+
+/// The error returned when the serialized data can not be converted back.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The data was written by a newer version of the format.
+    UnsupportedVersion(u32),
+    /// The data is not consistent, for example an index that is out of bounds.
+    Invalid(&'static str),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Error::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+            Error::Invalid(reason) => write!(f, "invalid data: {reason}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Returns an error if the version is newer than the current version.
+pub(crate) fn check_version(version: u32) -> Result<(), Error> {
+    if version > VERSION {
+        Err(Error::UnsupportedVersion(version))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+//! Slots that report the events to the `log` and `tracing` crates.
+//!
+//! Every [`Event`] is reported with the target `undo`, and the events in an
+//! [`Event::Batch`] are reported one by one.
+
+use crate::{At, Event, Slot};
+use alloc::collections::BTreeMap;
+use core::fmt::{self, Display, Formatter};
+
+/// A [`Slot`] that reports the events as `log` records.
+///
+/// The records are written as the name of the event followed by its data,
+/// like `IndexChanged old=0 new=1`. Requires the `log` feature to be enabled.
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, Record};
+/// # use undo::slot::Logger;
+/// let mut target = String::new();
+/// let mut record = Record::builder().connect(Logger::default()).build();
+/// record.edit(&mut target, Add('a'));
+/// ```
+#[cfg(feature = "log")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Logger {
+    level: log::Level,
+}
+
+#[cfg(feature = "log")]
+impl Logger {
+    /// Returns a logger that writes the records with the level.
+    pub const fn new(level: log::Level) -> Logger {
+        Logger { level }
+    }
+}
+
+/// Writes the records with [`log::Level::Debug`].
+#[cfg(feature = "log")]
+impl Default for Logger {
+    fn default() -> Self {
+        Logger::new(log::Level::Debug)
+    }
+}
+
+#[cfg(feature = "log")]
+impl Slot for Logger {
+    fn on_emit(&mut self, event: Event) {
+        visit(&event, &mut |kind, data| {
+            log::log!(target: "undo", self.level, "{kind}{data}");
+        });
+    }
+}
+
+/// A [`Slot`] that reports the events as `tracing` events.
+///
+/// The name of the event is recorded in the `kind` field and its data in the
+/// `value` field, or in the `old` and `new` fields for events that have both.
+/// Positions are recorded as `root` and `index` fields, prefixed with `old_`
+/// and `new_` for [`Event::HeadChanged`]. Requires the `tracing` feature to be enabled.
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, History};
+/// # use undo::slot::Tracing;
+/// let mut target = String::new();
+/// let mut history = History::builder()
+///     .connect(Tracing::new(tracing::Level::INFO))
+///     .build();
+/// history.edit(&mut target, Add('a'));
+/// ```
+#[cfg(feature = "tracing")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Tracing {
+    level: tracing::Level,
+}
+
+#[cfg(feature = "tracing")]
+impl Tracing {
+    /// Returns a slot that records the events with the level.
+    pub const fn new(level: tracing::Level) -> Tracing {
+        Tracing { level }
+    }
+}
+
+/// Records the events with [`tracing::Level::DEBUG`].
+#[cfg(feature = "tracing")]
+impl Default for Tracing {
+    fn default() -> Self {
+        Tracing::new(tracing::Level::DEBUG)
+    }
+}
+
+/// Calls `tracing::event!` with the level that is only known at runtime.
+#[cfg(feature = "tracing")]
+macro_rules! event {
+    ($level:expr, $($fields:tt)*) => {
+        match $level {
+            tracing::Level::ERROR => tracing::event!(target: "undo", tracing::Level::ERROR, $($fields)*),
+            tracing::Level::WARN => tracing::event!(target: "undo", tracing::Level::WARN, $($fields)*),
+            tracing::Level::INFO => tracing::event!(target: "undo", tracing::Level::INFO, $($fields)*),
+            tracing::Level::DEBUG => tracing::event!(target: "undo", tracing::Level::DEBUG, $($fields)*),
+            tracing::Level::TRACE => tracing::event!(target: "undo", tracing::Level::TRACE, $($fields)*),
+        }
+    };
+}
+
+#[cfg(feature = "tracing")]
+impl Slot for Tracing {
+    fn on_emit(&mut self, event: Event) {
+        visit(&event, &mut |kind, data| match data {
+            Data::None => event!(self.level, kind),
+            Data::Bool(value) => event!(self.level, kind, value),
+            Data::Usize(value) => event!(self.level, kind, value),
+            Data::Pair(old, new) => event!(self.level, kind, old, new),
+            Data::Keys(old, new) => event!(self.level, kind, old, new),
+            Data::At(at) => event!(self.level, kind, root = at.root, index = at.index),
+            Data::Ats(old, new) => event!(
+                self.level,
+                kind,
+                old_root = old.root,
+                old_index = old.index,
+                new_root = new.root,
+                new_index = new.index
+            ),
+            Data::Ids(ids) => event!(self.level, kind, value = ?ids),
+        });
+    }
+}
+
+/// The data of an event.
+enum Data<'a> {
+    None,
+    Bool(bool),
+    Usize(usize),
+    Pair(usize, usize),
+    Keys(Option<&'a str>, Option<&'a str>),
+    At(At),
+    Ats(At, At),
+    Ids(&'a BTreeMap<usize, usize>),
+}
+
+impl Display for Data<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Data::None => Ok(()),
+            Data::Bool(value) => write!(f, " value={value}"),
+            Data::Usize(value) => write!(f, " value={value}"),
+            Data::Pair(old, new) => write!(f, " old={old} new={new}"),
+            Data::Keys(old, new) => {
+                if let Some(old) = old {
+                    write!(f, " old={old}")?;
+                }
+                if let Some(new) = new {
+                    write!(f, " new={new}")?;
+                }
+                Ok(())
+            }
+            Data::At(at) => write!(f, " root={} index={}", at.root, at.index),
+            Data::Ats(old, new) => write!(
+                f,
+                " old_root={} old_index={} new_root={} new_index={}",
+                old.root, old.index, new.root, new.index
+            ),
+            Data::Ids(ids) => write!(f, " value={ids:?}"),
+        }
+    }
+}
+
+/// Calls `f` with the name and the data of the event, once for every event in a batch.
+fn visit(event: &Event, f: &mut impl FnMut(&'static str, Data)) {
+    let (kind, data) = match event {
+        Event::Undo(on) => ("Undo", Data::Bool(*on)),
+        Event::Redo(on) => ("Redo", Data::Bool(*on)),
+        Event::Saved(on) => ("Saved", Data::Bool(*on)),
+        Event::Root(id) => ("Root", Data::Usize(*id)),
+        Event::Index(index) => ("Index", Data::Usize(*index)),
+        Event::Dropped(n) => ("Dropped", Data::Usize(*n)),
+        Event::Pruned(n) => ("Pruned", Data::Usize(*n)),
+        Event::BranchDropped(id) => ("BranchDropped", Data::Usize(*id)),
+        Event::Merged(index) => ("Merged", Data::Usize(*index)),
+        Event::Annulled(index) => ("Annulled", Data::Usize(*index)),
+        Event::Adopted(n) => ("Adopted", Data::Usize(*n)),
+        Event::Branch { old, new } => ("Branch", Data::Pair(*old, *new)),
+        Event::IndexChanged { old, new } => ("IndexChanged", Data::Pair(*old, *new)),
+        Event::ActiveChanged { old, new } => {
+            ("ActiveChanged", Data::Keys(old.as_deref(), new.as_deref()))
+        }
+        Event::Head(at) => ("Head", Data::At(*at)),
+        Event::HeadChanged { old, new } => ("HeadChanged", Data::Ats(*old, *new)),
+        Event::Renumbered(ids) => ("Renumbered", Data::Ids(ids)),
+        Event::SnapshotRestored => ("SnapshotRestored", Data::None),
+        Event::Batch(events) => {
+            events.iter().for_each(|event| visit(event, f));
+            return;
+        }
+    };
+    f(kind, data);
+}
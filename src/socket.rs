@@ -1,40 +1,82 @@
 //! Module used to communicate changes in the data structures.
 
+use crate::At;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
 use core::mem;
+#[cfg(feature = "wasm")]
+use js_sys::wasm_bindgen::JsValue;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use slab::Slab;
 #[cfg(feature = "std")]
 use std::sync::mpsc::{Sender, SyncSender};
 
 /// Slot wrapper that adds some additional functionality.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[repr(transparent)]
+#[cfg_attr(feature = "serde", serde(transparent))]
 #[derive(Clone, Debug)]
-pub(crate) struct Socket<S>(Option<S>);
+pub(crate) struct Socket<S> {
+    slot: Option<S>,
+    /// Batch the events of every operation.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    coalesce: bool,
+    /// The number of batches that have been started but not ended.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    depth: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    batch: Vec<Event>,
+}
 
 impl<S> Socket<S> {
     pub const fn new(slot: S) -> Socket<S> {
-        Socket(Some(slot))
+        Socket {
+            slot: Some(slot),
+            coalesce: false,
+            depth: 0,
+            batch: Vec::new(),
+        }
     }
 
     pub fn connect(&mut self, slot: Option<S>) -> Option<S> {
-        mem::replace(&mut self.0, slot)
+        mem::replace(&mut self.slot, slot)
     }
 
     pub fn disconnect(&mut self) -> Option<S> {
-        self.0.take()
+        self.slot.take()
+    }
+
+    pub fn slot_mut(&mut self) -> Option<&mut S> {
+        self.slot.as_mut()
+    }
+
+    pub fn set_coalesce(&mut self, on: bool) {
+        self.coalesce = on;
     }
 }
 
 impl<S> Default for Socket<S> {
     fn default() -> Self {
-        Socket(None)
+        Socket {
+            slot: None,
+            coalesce: false,
+            depth: 0,
+            batch: Vec::new(),
+        }
     }
 }
 
 impl<S: Slot> Socket<S> {
     pub fn emit(&mut self, event: impl FnOnce() -> Event) {
-        if let Some(slot) = &mut self.0 {
+        let Some(slot) = &mut self.slot else {
+            return;
+        };
+        if self.depth > 0 {
+            coalesce(&mut self.batch, event());
+        } else {
             slot.on_emit(event());
         }
     }
@@ -44,6 +86,81 @@ impl<S: Slot> Socket<S> {
             self.emit(event);
         }
     }
+
+    /// Holds back the events until the matching call to [`Socket::end_batch`].
+    pub fn begin_batch(&mut self) {
+        self.depth += 1;
+    }
+
+    /// Emits the held back events as a single event when the outermost batch ends.
+    pub fn end_batch(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth > 0 {
+            return;
+        }
+        let mut batch = mem::take(&mut self.batch);
+        let event = match batch.len() {
+            0 => return,
+            1 => batch.pop().unwrap(),
+            _ => Event::Batch(batch),
+        };
+        if let Some(slot) = &mut self.slot {
+            slot.on_emit(event);
+        }
+    }
+
+    /// Starts a batch if every operation should be batched.
+    pub fn begin_op(&mut self) {
+        if self.coalesce {
+            self.begin_batch();
+        }
+    }
+
+    /// Ends the batch started by [`Socket::begin_op`].
+    pub fn end_op(&mut self) {
+        if self.coalesce {
+            self.end_batch();
+        }
+    }
+}
+
+/// Adds the event to the batch, combining it with the earlier events that it replaces.
+fn coalesce(batch: &mut Vec<Event>, event: Event) {
+    use Event::*;
+    let same = |a: &Event| mem::discriminant(a) == mem::discriminant(&event);
+    let Some(i) = batch.iter().position(same) else {
+        batch.push(event);
+        return;
+    };
+    match (&mut batch[i], event) {
+        // A change that is reverted cancels out.
+        (Undo(a), Undo(b)) | (Redo(a), Redo(b)) | (Saved(a), Saved(b)) if *a != b => {
+            batch.remove(i);
+        }
+        (ActiveChanged { old, .. }, ActiveChanged { new, .. }) if *old == new => {
+            batch.remove(i);
+        }
+        (ActiveChanged { new, .. }, ActiveChanged { new: b, .. }) => *new = b,
+        (IndexChanged { old, .. }, IndexChanged { new, .. }) if *old == new => {
+            batch.remove(i);
+            batch.retain(|e| !matches!(e, Index(_)));
+        }
+        (Branch { old, .. }, Branch { new, .. }) if *old == new => {
+            batch.remove(i);
+            batch.retain(|e| !matches!(e, Root(_)));
+        }
+        (HeadChanged { old, .. }, HeadChanged { new, .. }) if *old == new => {
+            batch.remove(i);
+            batch.retain(|e| !matches!(e, Head(_)));
+        }
+        (IndexChanged { new, .. }, IndexChanged { new: b, .. })
+        | (Branch { new, .. }, Branch { new: b, .. }) => *new = b,
+        (HeadChanged { new, .. }, HeadChanged { new: b, .. }) => *new = b,
+        (Dropped(a), Dropped(b)) | (Pruned(a), Pruned(b)) | (Adopted(a), Adopted(b)) => *a += b,
+        (Batch(a), Batch(b)) => b.into_iter().for_each(|e| coalesce(a, e)),
+        (BranchDropped(_) | Merged(_) | Annulled(_) | Renumbered(_), b) => batch.push(b),
+        (a, b) => *a = b,
+    }
 }
 
 /// Describes an event on the structures.
@@ -60,8 +177,91 @@ pub enum Event {
     Saved(bool),
     /// Emitted when the root has changed.
     Root(usize),
+    /// Emitted after [`Event::Root`] with both the old and the new root.
+    ///
+    /// The old root is the branch that was left.
+    Branch {
+        /// The root before the change.
+        old: usize,
+        /// The root after the change.
+        new: usize,
+    },
     /// Emitted when the index has changed.
     Index(usize),
+    /// Emitted after [`Event::Index`] with both the old and the new index.
+    IndexChanged {
+        /// The index before the change.
+        old: usize,
+        /// The index after the change.
+        new: usize,
+    },
+    /// Emitted when the head of a [`History`](crate::History) has changed.
+    ///
+    /// Unlike [`Event::Index`], the head also contains the branch, so it is not
+    /// ambiguous when the history switches between branches.
+    Head(At),
+    /// Emitted after [`Event::Head`] with both the old and the new head.
+    HeadChanged {
+        /// The head before the change.
+        old: At,
+        /// The head after the change.
+        new: At,
+    },
+    /// Emitted when entries have been dropped because the limit was reached.
+    ///
+    /// Contains the number of dropped entries.
+    Dropped(usize),
+    /// Emitted when branches have been removed from a [`History`](crate::History).
+    ///
+    /// Contains the number of removed branches.
+    Pruned(usize),
+    /// Emitted when a branch has been removed from a [`History`](crate::History)
+    /// because the maximum number of branches was exceeded.
+    ///
+    /// Contains the id of the removed branch.
+    /// See [`history::Builder::max_branches`](crate::history::Builder::max_branches) for more information.
+    BranchDropped(usize),
+    /// Emitted when an edit has been merged into an existing entry instead of being pushed.
+    ///
+    /// Contains the position of the entry, which is the position the entry leads to.
+    /// See [`Edit::merge`](crate::Edit::merge) for more information.
+    Merged(usize),
+    /// Emitted when an entry has been removed because it was annulled by a merge.
+    ///
+    /// Contains the position of the removed entry, as it was before the removal.
+    /// See [`Merged::Annul`](crate::Merged::Annul) for more information.
+    Annulled(usize),
+    /// Emitted when the branches of a [`History`](crate::History) have been given new ids.
+    ///
+    /// Contains the new id of every branch by its old id. The stored positions must be
+    /// moved to the new ids, since the positions from before are no longer valid.
+    /// See [`History::compact`](crate::History::compact) for more information.
+    Renumbered(BTreeMap<usize, usize>),
+    /// Emitted instead of the events of an operation when the events are batched.
+    ///
+    /// Events that are replaced by later events in the batch are combined or removed,
+    /// so for example [`Event::IndexChanged`] goes from the first old index to the last new index.
+    /// See [`record::Builder::coalesce_events`](crate::record::Builder::coalesce_events)
+    /// and [`Record::begin_batch`](crate::Record::begin_batch) for more information.
+    Batch(Vec<Event>),
+    /// Emitted when edits have been applied to the target without being pushed on the record.
+    ///
+    /// Contains the number of adopted edits. See [`Record::adopt`](crate::Record::adopt) for more information.
+    Adopted(usize),
+    /// Emitted when the target has been restored from the snapshot of the saved state.
+    ///
+    /// See [`record::Builder::snapshot_on_save`](crate::record::Builder::snapshot_on_save) for more information.
+    SnapshotRestored,
+    /// Emitted when the active structure in a [`Group`](crate::Group) has changed.
+    ///
+    /// The keys are formatted with their [`Display`](core::fmt::Display) implementation,
+    /// and are `None` if the group has no active structure.
+    ActiveChanged {
+        /// The key of the structure that was active before the change.
+        old: Option<String>,
+        /// The key of the structure that is active after the change.
+        new: Option<String>,
+    },
 }
 
 /// Handles events.
@@ -82,6 +282,7 @@ pub enum Event {
 /// assert_eq!(iter.next(), Some(Event::Undo(true)));
 /// assert_eq!(iter.next(), Some(Event::Saved(false)));
 /// assert_eq!(iter.next(), Some(Event::Index(1)));
+/// assert_eq!(iter.next(), Some(Event::IndexChanged { old: 0, new: 1 }));
 /// assert_eq!(iter.next(), None);
 ///
 /// record.undo(&mut target);
@@ -89,6 +290,7 @@ pub enum Event {
 /// assert_eq!(iter.next(), Some(Event::Redo(true)));
 /// assert_eq!(iter.next(), Some(Event::Saved(true)));
 /// assert_eq!(iter.next(), Some(Event::Index(0)));
+/// assert_eq!(iter.next(), Some(Event::IndexChanged { old: 1, new: 0 }));
 /// assert_eq!(iter.next(), None);
 /// ```
 pub trait Slot {
@@ -119,3 +321,319 @@ impl Slot for SyncSender<Event> {
         self.send(event).ok();
     }
 }
+
+/// Calls the JavaScript function with the event converted to an object.
+///
+/// The object has a `type` property with the name of the event, and a `value` property
+/// with the data of the event, if any. Events with an old and a new value have `old` and `new`
+/// properties instead, positions are objects with `root` and `index` properties,
+/// and the events in a batch are stored in an array.
+///
+/// Requires the `wasm` feature to be enabled.
+#[cfg(feature = "wasm")]
+impl Slot for js_sys::Function {
+    fn on_emit(&mut self, event: Event) {
+        self.call1(&JsValue::UNDEFINED, &event_to_js(&event)).ok();
+    }
+}
+
+/// Converts the event to the JavaScript object passed to [`js_sys::Function`] slots.
+#[cfg(feature = "wasm")]
+fn event_to_js(event: &Event) -> JsValue {
+    use js_sys::{Array, Object, Reflect};
+
+    fn set(object: &Object, key: &str, value: JsValue) {
+        Reflect::set(object, &key.into(), &value).ok();
+    }
+    fn at_to_js(at: &At) -> JsValue {
+        let object = Object::new();
+        set(&object, "root", at.root.into());
+        set(&object, "index", at.index.into());
+        object.into()
+    }
+
+    let object = Object::new();
+    let kind = match event {
+        Event::Undo(on) => {
+            set(&object, "value", (*on).into());
+            "Undo"
+        }
+        Event::Redo(on) => {
+            set(&object, "value", (*on).into());
+            "Redo"
+        }
+        Event::Saved(on) => {
+            set(&object, "value", (*on).into());
+            "Saved"
+        }
+        Event::Root(id) => {
+            set(&object, "value", (*id).into());
+            "Root"
+        }
+        Event::Index(index) => {
+            set(&object, "value", (*index).into());
+            "Index"
+        }
+        Event::Dropped(n) => {
+            set(&object, "value", (*n).into());
+            "Dropped"
+        }
+        Event::Pruned(n) => {
+            set(&object, "value", (*n).into());
+            "Pruned"
+        }
+        Event::BranchDropped(id) => {
+            set(&object, "value", (*id).into());
+            "BranchDropped"
+        }
+        Event::Merged(index) => {
+            set(&object, "value", (*index).into());
+            "Merged"
+        }
+        Event::Annulled(index) => {
+            set(&object, "value", (*index).into());
+            "Annulled"
+        }
+        Event::Adopted(n) => {
+            set(&object, "value", (*n).into());
+            "Adopted"
+        }
+        Event::Branch { old, new } => {
+            set(&object, "old", (*old).into());
+            set(&object, "new", (*new).into());
+            "Branch"
+        }
+        Event::IndexChanged { old, new } => {
+            set(&object, "old", (*old).into());
+            set(&object, "new", (*new).into());
+            "IndexChanged"
+        }
+        Event::ActiveChanged { old, new } => {
+            let key = |key: &Option<String>| key.as_deref().map_or(JsValue::NULL, JsValue::from);
+            set(&object, "old", key(old));
+            set(&object, "new", key(new));
+            "ActiveChanged"
+        }
+        Event::Head(at) => {
+            set(&object, "value", at_to_js(at));
+            "Head"
+        }
+        Event::HeadChanged { old, new } => {
+            set(&object, "old", at_to_js(old));
+            set(&object, "new", at_to_js(new));
+            "HeadChanged"
+        }
+        Event::Batch(events) => {
+            let array = Array::new();
+            for event in events {
+                array.push(&event_to_js(event));
+            }
+            set(&object, "value", array.into());
+            "Batch"
+        }
+        Event::Renumbered(ids) => {
+            let array = Array::new();
+            for (old, new) in ids {
+                array.push(&Array::of2(&(*old).into(), &(*new).into()));
+            }
+            set(&object, "value", array.into());
+            "Renumbered"
+        }
+        Event::SnapshotRestored => "SnapshotRestored",
+    };
+    set(&object, "type", kind.into());
+    object.into()
+}
+
+#[cfg(feature = "tokio")]
+impl Slot for tokio::sync::mpsc::UnboundedSender<Event> {
+    fn on_emit(&mut self, event: Event) {
+        self.send(event).ok();
+    }
+}
+
+#[cfg(feature = "crossbeam")]
+impl Slot for crossbeam_channel::Sender<Event> {
+    fn on_emit(&mut self, event: Event) {
+        self.send(event).ok();
+    }
+}
+
+#[cfg(feature = "futures")]
+impl Slot for futures_channel::mpsc::UnboundedSender<Event> {
+    fn on_emit(&mut self, event: Event) {
+        self.unbounded_send(event).ok();
+    }
+}
+
+/// Slot that keeps track of the state needed to update the undo and redo menus.
+///
+/// The state is updated from the events, so it should be connected to
+/// the structure when it is created.
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, Record, UiState};
+/// let mut target = String::new();
+/// let mut record = Record::builder().connect(UiState::new()).build();
+/// record.edit(&mut target, Add('a'));
+/// record.edit(&mut target, Add('b'));
+/// record.undo(&mut target);
+///
+/// let state = record.slot_mut().unwrap();
+/// assert!(state.can_undo());
+/// assert!(state.can_redo());
+/// assert!(!state.is_saved());
+/// assert_eq!(state.index(), 1);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UiState {
+    can_undo: bool,
+    can_redo: bool,
+    is_saved: bool,
+    index: usize,
+    head: Option<At>,
+}
+
+impl UiState {
+    /// Returns the state of a new structure without any edits.
+    pub const fn new() -> UiState {
+        UiState {
+            can_undo: false,
+            can_redo: false,
+            is_saved: true,
+            index: 0,
+            head: None,
+        }
+    }
+
+    /// Returns `true` if the structure can undo.
+    pub fn can_undo(&self) -> bool {
+        self.can_undo
+    }
+
+    /// Returns `true` if the structure can redo.
+    pub fn can_redo(&self) -> bool {
+        self.can_redo
+    }
+
+    /// Returns `true` if the target is in a saved state.
+    pub fn is_saved(&self) -> bool {
+        self.is_saved
+    }
+
+    /// Returns the latest index.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the latest head, if the structure is a [`History`](crate::History)
+    /// whose head has changed.
+    pub fn head(&self) -> Option<At> {
+        self.head
+    }
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        UiState::new()
+    }
+}
+
+impl Slot for UiState {
+    fn on_emit(&mut self, event: Event) {
+        match event {
+            Event::Undo(on) => self.can_undo = on,
+            Event::Redo(on) => self.can_redo = on,
+            Event::Saved(on) => self.is_saved = on,
+            Event::Index(index) => self.index = index,
+            Event::Head(head) => self.head = Some(head),
+            Event::Batch(events) => events.into_iter().for_each(|e| self.on_emit(e)),
+            _ => {}
+        }
+    }
+}
+
+/// Identifies a slot connected to a [`MultiSlot`].
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub struct SlotId(usize);
+
+/// Slot that forwards every event to several slots.
+///
+/// # Examples
+/// ```
+/// # use std::sync::mpsc;
+/// # use undo::{Add, Event, MultiSlot, Record};
+/// let (status, status_events) = mpsc::channel();
+/// let (menu, menu_events) = mpsc::channel();
+///
+/// let mut slots = MultiSlot::new();
+/// slots.connect(status);
+/// let id = slots.connect(menu);
+///
+/// let mut target = String::new();
+/// let mut record = Record::builder().connect(slots).build();
+/// record.edit(&mut target, Add('a'));
+/// assert_eq!(status_events.try_iter().count(), 4);
+/// assert_eq!(menu_events.try_iter().count(), 4);
+///
+/// record.slot_mut().unwrap().disconnect(id);
+/// record.undo(&mut target);
+/// assert_eq!(status_events.try_iter().next(), Some(Event::Undo(false)));
+/// assert_eq!(menu_events.try_iter().next(), None);
+/// ```
+#[derive(Default)]
+pub struct MultiSlot {
+    slots: Slab<Box<dyn Slot>>,
+}
+
+impl MultiSlot {
+    /// Returns a new multi slot without any connected slots.
+    pub const fn new() -> MultiSlot {
+        MultiSlot { slots: Slab::new() }
+    }
+
+    /// Connects a slot and returns the id used to disconnect it.
+    pub fn connect(&mut self, slot: impl Slot + 'static) -> SlotId {
+        SlotId(self.slots.insert(Box::new(slot)))
+    }
+
+    /// Disconnects the slot with the id.
+    ///
+    /// Returns `false` if the slot has already been disconnected.
+    pub fn disconnect(&mut self, id: SlotId) -> bool {
+        self.slots.try_remove(id.0).is_some()
+    }
+
+    /// Returns the number of connected slots.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns `true` if no slots are connected.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Disconnects all slots.
+    pub fn clear(&mut self) {
+        self.slots.clear();
+    }
+}
+
+impl Slot for MultiSlot {
+    fn on_emit(&mut self, event: Event) {
+        for (_, slot) in &mut self.slots {
+            slot.on_emit(event.clone());
+        }
+    }
+}
+
+impl Debug for MultiSlot {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("MultiSlot")
+            .field("len", &self.slots.len())
+            .finish()
+    }
+}
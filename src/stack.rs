@@ -0,0 +1,143 @@
+use crate::{Edit, History, Record, Slot};
+use alloc::string::String;
+use core::any::Any;
+use core::fmt::Display;
+
+/// An object-safe interface to a [`Record`] or a [`History`].
+///
+/// This allows structures with different edit and target types to be used through
+/// the same interface, for example as `Box<dyn UndoStack>` in an application
+/// where each document has its own kind of edits.
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, History, Record, UndoStack};
+/// let mut text = String::new();
+/// let mut record = Record::new();
+/// record.edit(&mut text, Add('a'));
+///
+/// let mut stacks: Vec<Box<dyn UndoStack>> = vec![Box::new(record), Box::new(History::<Add>::new())];
+/// assert_eq!(stacks[0].undo_string().as_deref(), Some("Add 'a'"));
+/// assert!(stacks[0].undo_any(&mut text));
+/// assert!(!stacks[1].undo_any(&mut text));
+/// assert_eq!(text, "");
+/// ```
+pub trait UndoStack {
+    /// Returns `true` if the structure can undo.
+    fn can_undo(&self) -> bool;
+
+    /// Returns `true` if the structure can redo.
+    fn can_redo(&self) -> bool;
+
+    /// Returns `true` if the target is in a saved state.
+    fn is_saved(&self) -> bool;
+
+    /// Marks the target as currently being in a saved state.
+    fn set_saved(&mut self);
+
+    /// Undoes the active edit, returning `true` if an edit was undone.
+    ///
+    /// The output of the edit is discarded.
+    ///
+    /// # Panics
+    /// Panics if the target is not of the target type of the edits.
+    fn undo_any(&mut self, target: &mut dyn Any) -> bool;
+
+    /// Redoes the next edit, returning `true` if an edit was redone.
+    ///
+    /// The output of the edit is discarded.
+    ///
+    /// # Panics
+    /// Panics if the target is not of the target type of the edits.
+    fn redo_any(&mut self, target: &mut dyn Any) -> bool;
+
+    /// Returns the string of the edit which will be undone next.
+    fn undo_string(&self) -> Option<String>;
+
+    /// Returns the string of the edit which will be redone next.
+    fn redo_string(&self) -> Option<String>;
+}
+
+fn downcast<T: 'static>(target: &mut dyn Any) -> &mut T {
+    target
+        .downcast_mut()
+        .expect("the target should be of the target type of the edits")
+}
+
+impl<E, S> UndoStack for Record<E, S>
+where
+    E: Edit + Display,
+    E::Target: 'static,
+    S: Slot,
+{
+    fn can_undo(&self) -> bool {
+        Record::can_undo(self)
+    }
+
+    fn can_redo(&self) -> bool {
+        Record::can_redo(self)
+    }
+
+    fn is_saved(&self) -> bool {
+        Record::is_saved(self)
+    }
+
+    fn set_saved(&mut self) {
+        Record::set_saved(self);
+    }
+
+    fn undo_any(&mut self, target: &mut dyn Any) -> bool {
+        self.undo(downcast(target)).is_some()
+    }
+
+    fn redo_any(&mut self, target: &mut dyn Any) -> bool {
+        self.redo(downcast(target)).is_some()
+    }
+
+    fn undo_string(&self) -> Option<String> {
+        Record::undo_string(self)
+    }
+
+    fn redo_string(&self) -> Option<String> {
+        Record::redo_string(self)
+    }
+}
+
+impl<E, S> UndoStack for History<E, S>
+where
+    E: Edit + Display,
+    E::Target: 'static,
+    S: Slot,
+{
+    fn can_undo(&self) -> bool {
+        History::can_undo(self)
+    }
+
+    fn can_redo(&self) -> bool {
+        History::can_redo(self)
+    }
+
+    fn is_saved(&self) -> bool {
+        History::is_saved(self)
+    }
+
+    fn set_saved(&mut self) {
+        History::set_saved(self);
+    }
+
+    fn undo_any(&mut self, target: &mut dyn Any) -> bool {
+        self.undo(downcast(target)).is_some()
+    }
+
+    fn redo_any(&mut self, target: &mut dyn Any) -> bool {
+        self.redo(downcast(target)).is_some()
+    }
+
+    fn undo_string(&self) -> Option<String> {
+        History::undo_string(self)
+    }
+
+    fn redo_string(&self) -> Option<String> {
+        History::redo_string(self)
+    }
+}
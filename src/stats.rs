@@ -0,0 +1,53 @@
+use core::time::Duration;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Statistics about a [`Record`](crate::Record) or a [`History`](crate::History).
+///
+/// The counts are the total number of operations done since the structure was created
+/// or the statistics were reset, and are not affected by clearing the structure.
+///
+/// Returned by [`Record::stats`](crate::Record::stats) and [`History::stats`](crate::History::stats).
+///
+/// # Examples
+/// ```
+/// # use undo::{Add, Record};
+/// let mut target = String::new();
+/// let mut record = Record::new();
+/// record.edit(&mut target, Add('a'));
+/// record.edit(&mut target, Add('b'));
+/// record.undo(&mut target);
+/// record.redo(&mut target);
+///
+/// let stats = record.stats();
+/// assert_eq!(stats.edits, 2);
+/// assert_eq!(stats.undos, 1);
+/// assert_eq!(stats.redos, 1);
+/// assert!(stats.earliest <= stats.latest);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Stats {
+    /// The number of edits that have been applied, including the edits that were merged.
+    pub edits: usize,
+    /// The number of edits that have been undone.
+    pub undos: usize,
+    /// The number of edits that have been redone.
+    pub redos: usize,
+    /// The number of edits that have been merged into the previous edit.
+    pub merges: usize,
+    /// The number of edits that have annulled the previous edit.
+    pub annulled: usize,
+    /// The number of edits that have been dropped because of the limits.
+    pub dropped: usize,
+    /// The earliest time one of the current entries was applied, see [`Clock`](crate::Clock).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub earliest: Option<Duration>,
+    /// The latest time one of the current entries was applied, undone, or redone.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub latest: Option<Duration>,
+    /// The number of branches, including the current branch.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub branches: usize,
+}
@@ -0,0 +1,149 @@
+use crate::{Edit, Event, Record};
+use std::fmt::Display;
+use std::string::String;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::vec::Vec;
+
+/// A [`Record`] that can be shared between threads.
+///
+/// The record is kept behind a mutex, so all methods take `&self` and the
+/// `SyncRecord` can be cloned and sent to other threads, like background workers
+/// that apply edits. The events are forwarded over a channel.
+///
+/// # Examples
+/// ```
+/// # use std::thread;
+/// # use undo::{Add, Event, SyncRecord};
+/// let (record, events) = SyncRecord::new();
+/// let worker = record.clone();
+/// thread::spawn(move || {
+///     let mut target = String::new();
+///     worker.edit(&mut target, Add('a'));
+/// })
+/// .join()
+/// .unwrap();
+///
+/// assert!(record.can_undo());
+/// assert_eq!(events.try_iter().next(), Some(Event::Undo(true)));
+/// ```
+#[derive(Debug)]
+pub struct SyncRecord<E> {
+    inner: Arc<Mutex<Record<E, Sender<Event>>>>,
+}
+
+impl<E> SyncRecord<E> {
+    /// Returns a new shared record and the receiver of its events.
+    pub fn new() -> (SyncRecord<E>, Receiver<Event>) {
+        let (sender, receiver) = mpsc::channel();
+        let record = Record::builder().connect(sender).build();
+        (SyncRecord::from(record), receiver)
+    }
+
+    /// Locks the record, blocking the current thread until it is available.
+    ///
+    /// The guard gives access to the full API of the record.
+    /// The record is still usable if another thread panicked while holding the lock.
+    pub fn lock(&self) -> MutexGuard<'_, Record<E, Sender<Event>>> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the length of the record.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if the record is empty.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Returns `true` if the record can undo.
+    pub fn can_undo(&self) -> bool {
+        self.lock().can_undo()
+    }
+
+    /// Returns `true` if the record can redo.
+    pub fn can_redo(&self) -> bool {
+        self.lock().can_redo()
+    }
+
+    /// Returns `true` if the target is in a saved state, `false` otherwise.
+    pub fn is_saved(&self) -> bool {
+        self.lock().is_saved()
+    }
+
+    /// Returns the current index in the record.
+    pub fn head(&self) -> usize {
+        self.lock().head()
+    }
+
+    /// Marks the target as currently being in a saved.
+    pub fn set_saved(&self) {
+        self.lock().set_saved();
+    }
+
+    /// Clears the saved state of the target.
+    pub fn clear_saved(&self) {
+        self.lock().clear_saved();
+    }
+
+    /// Removes all edits from the record without undoing them.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+}
+
+impl<E: Edit> SyncRecord<E> {
+    /// Pushes the edit on top of the record and executes its [`Edit::edit`] method.
+    pub fn edit(&self, target: &mut E::Target, edit: E) -> E::Output {
+        self.lock().edit(target, edit)
+    }
+
+    /// Calls the [`Edit::undo`] method for the active edit and sets
+    /// the previous one as the new active one.
+    pub fn undo(&self, target: &mut E::Target) -> Option<E::Output> {
+        self.lock().undo(target)
+    }
+
+    /// Calls the [`Edit::redo`] method for the active edit and sets
+    /// the next one as the new active one.
+    pub fn redo(&self, target: &mut E::Target) -> Option<E::Output> {
+        self.lock().redo(target)
+    }
+
+    /// Repeatedly calls [`Edit::undo`] or [`Edit::redo`] until the edit at `index` is reached.
+    pub fn go_to(&self, target: &mut E::Target, index: usize) -> Vec<E::Output> {
+        self.lock().go_to(target, index)
+    }
+}
+
+impl<E: Display> SyncRecord<E> {
+    /// Returns the string of the edit which will be undone
+    /// in the next call to [`SyncRecord::undo`].
+    pub fn undo_string(&self) -> Option<String> {
+        self.lock().undo_string()
+    }
+
+    /// Returns the string of the edit which will be redone
+    /// in the next call to [`SyncRecord::redo`].
+    pub fn redo_string(&self) -> Option<String> {
+        self.lock().redo_string()
+    }
+}
+
+impl<E> Clone for SyncRecord<E> {
+    fn clone(&self) -> Self {
+        SyncRecord {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<E> From<Record<E, Sender<Event>>> for SyncRecord<E> {
+    fn from(record: Record<E, Sender<Event>>) -> Self {
+        SyncRecord {
+            inner: Arc::new(Mutex::new(record)),
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Utilities for testing edits and the structures that store them.
+//!
+//! The functions in this module panic with a description of the problem if a check fails,
+//! so they can be used directly in tests. They check that undoing and redoing the edits
+//! always brings the target back to the same state, which catches edits that do not
+//! restore the target correctly and merges that lose information.
+//!
+//! Strategies for generating random operations are available in the `proptest` module
+//! when the `proptest` feature is enabled.
+
+use crate::socket::Slot;
+use crate::{At, Edit, History, Record};
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+/// Asserts that undoing the edit restores the target, and that redoing it gives
+/// the same target as the edit did.
+///
+/// The target is left in the state after the edit.
+///
+/// # Examples
+/// ```
+/// # use undo::testing::assert_edit_roundtrip;
+/// # use undo::Add;
+/// let mut target = String::from("a");
+/// assert_edit_roundtrip(Add('b'), &mut target);
+/// assert_eq!(target, "ab");
+/// ```
+pub fn assert_edit_roundtrip<E>(mut edit: E, target: &mut E::Target)
+where
+    E: Edit,
+    E::Target: Clone + PartialEq + Debug,
+{
+    let before = target.clone();
+    edit.edit(target);
+    let after = target.clone();
+    edit.undo(target);
+    assert_eq!(*target, before, "undo did not restore the target");
+    edit.redo(target);
+    assert_eq!(
+        *target, after,
+        "redo did not give the same target as the edit"
+    );
+}
+
+/// Asserts that every position in the record gives the same target
+/// no matter which direction it is reached from.
+///
+/// Each position is visited by redoing from the start and then by undoing from the end,
+/// and the target is compared at every step. The record is returned to its head afterwards.
+///
+/// # Examples
+/// ```
+/// # use undo::testing::assert_roundtrip;
+/// # use undo::{Add, Record};
+/// let mut target = String::new();
+/// let mut record = Record::new();
+/// record.edit(&mut target, Add('a'));
+/// record.edit(&mut target, Add('b'));
+/// record.undo(&mut target);
+/// assert_roundtrip(&mut record, &mut target);
+/// assert_eq!(target, "a");
+/// ```
+pub fn assert_roundtrip<E, S>(record: &mut Record<E, S>, target: &mut E::Target)
+where
+    E: Edit,
+    E::Target: Clone + PartialEq + Debug,
+    S: Slot,
+{
+    let head = record.head();
+    let len = record.len();
+    record.go_to(target, 0);
+    let mut states = Vec::with_capacity(len + 1);
+    states.push(target.clone());
+    for index in 1..=len {
+        record.go_to(target, index);
+        states.push(target.clone());
+    }
+    for index in (0..len).rev() {
+        record.go_to(target, index);
+        assert_eq!(
+            *target, states[index],
+            "the target at index {index} is different when reached by undo"
+        );
+    }
+    record.go_to(target, head);
+    assert_eq!(
+        *target, states[head],
+        "the target at the head is different after going back to it"
+    );
+}
+
+/// Asserts that the positions in the history are consistent.
+///
+/// Every entry must have a parent, and the head, the saved state, and the savepoints
+/// must all be positions that exist in the history.
+pub fn assert_invariants<E, S>(history: &History<E, S>) {
+    let start = history.at(history.head().root, 0);
+    let exists = |at: At| !history.path_between(start, at).is_empty();
+    assert!(
+        exists(history.head()),
+        "the head {:?} does not exist",
+        history.head()
+    );
+    for (at, _) in history.all_entries() {
+        assert!(history.parent_of(at).is_some(), "{at:?} has no parent");
+    }
+    if let Some(saved) = history.saved() {
+        assert!(exists(saved), "the saved state {saved:?} does not exist");
+    }
+    for (name, at) in history.saved_states() {
+        assert!(
+            exists(at),
+            "the savepoint {name:?} at {at:?} does not exist"
+        );
+    }
+}
+
+/// Asserts that every position in the history gives the same target
+/// no matter which position it is reached from.
+///
+/// Every position is visited twice, in opposite orders, and the target is compared
+/// at every step. The history is returned to its head afterwards, and
+/// [`assert_invariants`] is checked before and after.
+///
+/// # Examples
+/// ```
+/// # use undo::testing::assert_history_roundtrip;
+/// # use undo::{Add, History};
+/// let mut target = String::new();
+/// let mut history = History::new();
+/// history.edit(&mut target, Add('a'));
+/// history.undo(&mut target);
+/// history.edit(&mut target, Add('b'));
+/// assert_history_roundtrip(&mut history, &mut target);
+/// assert_eq!(target, "b");
+/// ```
+pub fn assert_history_roundtrip<E, S>(history: &mut History<E, S>, target: &mut E::Target)
+where
+    E: Edit,
+    E::Target: Clone + PartialEq + Debug,
+    S: Slot,
+{
+    assert_invariants(history);
+    // The positions change when the history switches branches,
+    // so the positions are identified by the label of their entry instead.
+    let id = |history: &History<E, S>, at: At| history.entry_at(at).map(|e| e.label.unwrap_or(at));
+    let find = |history: &History<E, S>, id: Option<At>| match id {
+        Some(id) => history
+            .all_entries()
+            .find(|&(at, e)| e.label.unwrap_or(at) == id)
+            .map(|(at, _)| at)
+            .expect("the entry has been removed"),
+        None => history.at(history.head().root, 0),
+    };
+
+    let head = id(history, history.head());
+    let mut ids: Vec<_> = history
+        .all_entries()
+        .map(|(at, e)| Some(e.label.unwrap_or(at)))
+        .collect();
+    ids.push(None);
+    let mut states = Vec::with_capacity(ids.len());
+    for &id in &ids {
+        let at = find(history, id);
+        history.go_to(target, at);
+        states.push(target.clone());
+    }
+    for (&id, state) in ids.iter().zip(&states).rev() {
+        let at = find(history, id);
+        history.go_to(target, at);
+        assert_eq!(
+            target, state,
+            "the target at {at:?} is different the second time it is reached"
+        );
+    }
+    let at = find(history, head);
+    history.go_to(target, at);
+    assert_invariants(history);
+}
+
+/// Strategies for generating random operations with [proptest](https://docs.rs/proptest).
+///
+/// Requires the `proptest` feature to be enabled.
+///
+/// # Examples
+/// ```
+/// # use proptest::prelude::*;
+/// # use undo::testing::{assert_roundtrip, proptest::{apply, ops}};
+/// # use undo::{Add, Record};
+/// proptest!(|(ops in ops(any::<char>().prop_map(Add), 0..20))| {
+///     let mut target = String::new();
+///     let mut record = Record::new();
+///     apply(&mut record, &mut target, ops);
+///     assert_roundtrip(&mut record, &mut target);
+/// });
+/// ```
+#[cfg(feature = "proptest")]
+pub mod proptest {
+    use super::*;
+    use ::proptest::collection::vec;
+    use ::proptest::prelude::*;
+    use core::ops::Range;
+
+    /// An operation on a structure.
+    #[derive(Clone, Debug)]
+    pub enum Op<E> {
+        /// Applies the edit.
+        Edit(E),
+        /// Undoes the active edit.
+        Undo,
+        /// Redoes the next edit.
+        Redo,
+    }
+
+    /// Returns a strategy that generates operations using `edit` to generate the edits.
+    ///
+    /// About half of the operations are edits.
+    pub fn ops<E: Clone + Debug>(
+        edit: impl Strategy<Value = E>,
+        len: Range<usize>,
+    ) -> impl Strategy<Value = Vec<Op<E>>> {
+        let op = prop_oneof![
+            2 => edit.prop_map(Op::Edit),
+            1 => Just(Op::Undo),
+            1 => Just(Op::Redo),
+        ];
+        vec(op, len)
+    }
+
+    /// Applies the operations to the record.
+    pub fn apply<E: Edit, S: Slot>(
+        record: &mut Record<E, S>,
+        target: &mut E::Target,
+        ops: impl IntoIterator<Item = Op<E>>,
+    ) {
+        for op in ops {
+            match op {
+                Op::Edit(edit) => drop(record.edit(target, edit)),
+                Op::Undo => drop(record.undo(target)),
+                Op::Redo => drop(record.redo(target)),
+            }
+        }
+    }
+
+    /// Applies the operations to the history.
+    pub fn apply_history<E: Edit, S: Slot>(
+        history: &mut History<E, S>,
+        target: &mut E::Target,
+        ops: impl IntoIterator<Item = Op<E>>,
+    ) {
+        for op in ops {
+            match op {
+                Op::Edit(edit) => drop(history.edit(target, edit)),
+                Op::Undo => drop(history.undo(target)),
+                Op::Redo => drop(history.redo(target)),
+            }
+        }
+    }
+}
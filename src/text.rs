@@ -0,0 +1,197 @@
+use crate::{Edit, Merged};
+use alloc::string::String;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::marker::PhantomData;
+use core::ops::Range;
+
+/// A text buffer that can be edited by [`TextEdit`].
+///
+/// The positions are given in the same unit as the buffer uses for indexing,
+/// which is bytes for [`String`]. Implement this for rope-like types to use them with [`TextEdit`].
+pub trait TextBuffer {
+    /// Inserts the text at the position.
+    fn insert_text(&mut self, at: usize, text: &str);
+
+    /// Removes the text in the range and returns it.
+    fn remove_text(&mut self, range: Range<usize>) -> String;
+
+    /// Returns the length of the text, in the same unit as the positions.
+    fn text_len(text: &str) -> usize {
+        text.len()
+    }
+}
+
+impl TextBuffer for String {
+    fn insert_text(&mut self, at: usize, text: &str) {
+        self.insert_str(at, text);
+    }
+
+    fn remove_text(&mut self, range: Range<usize>) -> String {
+        self.drain(range).collect()
+    }
+}
+
+/// An edit that replaces a range of text with another text.
+///
+/// Insertions next to each other are merged into a single edit, and so are deletions,
+/// like when typing or pressing backspace. Deleting text that was just inserted
+/// shrinks the insertion, and annuls it if all of the inserted text is deleted.
+///
+/// Requires the `text` feature to be enabled.
+///
+/// # Examples
+/// ```
+/// # use undo::{Record, TextEdit};
+/// let mut target = String::new();
+/// let mut record = Record::new();
+/// record.edit(&mut target, TextEdit::insert(0, "ab"));
+/// record.edit(&mut target, TextEdit::insert(2, "c"));
+/// assert_eq!(target, "abc");
+/// assert_eq!(record.len(), 1);
+///
+/// record.edit(&mut target, TextEdit::delete(2..3));
+/// assert_eq!(target, "ab");
+/// assert_eq!(record.len(), 1);
+/// record.edit(&mut target, TextEdit::delete(0..2));
+/// assert!(record.is_empty());
+/// ```
+pub struct TextEdit<T = String> {
+    at: usize,
+    len: usize,
+    removed: String,
+    inserted: String,
+    pd: PhantomData<fn(&mut T)>,
+}
+
+impl<T> TextEdit<T> {
+    /// Returns an edit that inserts the text at the position.
+    pub fn insert(at: usize, text: impl Into<String>) -> TextEdit<T> {
+        TextEdit::replace(at..at, text)
+    }
+
+    /// Returns an edit that deletes the text in the range.
+    pub fn delete(range: Range<usize>) -> TextEdit<T> {
+        TextEdit::replace(range, String::new())
+    }
+
+    /// Returns an edit that replaces the text in the range with the text.
+    pub fn replace(range: Range<usize>, text: impl Into<String>) -> TextEdit<T> {
+        TextEdit {
+            at: range.start,
+            len: range.end.saturating_sub(range.start),
+            removed: String::new(),
+            inserted: text.into(),
+            pd: PhantomData,
+        }
+    }
+
+    /// Returns the position of the edit.
+    pub fn at(&self) -> usize {
+        self.at
+    }
+
+    /// Returns the text that is inserted by the edit.
+    pub fn inserted(&self) -> &str {
+        &self.inserted
+    }
+
+    /// Returns the text that was removed by the edit.
+    ///
+    /// This is empty until the edit has been applied.
+    pub fn removed(&self) -> &str {
+        &self.removed
+    }
+
+    fn is_insert(&self) -> bool {
+        self.len == 0
+    }
+
+    fn is_delete(&self) -> bool {
+        self.inserted.is_empty()
+    }
+}
+
+impl<T: TextBuffer> Edit for TextEdit<T> {
+    type Target = T;
+    type Output = ();
+
+    fn edit(&mut self, target: &mut T) {
+        self.removed = target.remove_text(self.at..self.at + self.len);
+        target.insert_text(self.at, &self.inserted);
+    }
+
+    fn undo(&mut self, target: &mut T) {
+        let len = T::text_len(&self.inserted);
+        target.remove_text(self.at..self.at + len);
+        target.insert_text(self.at, &self.removed);
+    }
+
+    fn merge(&mut self, mut other: Self) -> Merged<Self> {
+        let end = self.at + T::text_len(&self.inserted);
+        if self.is_insert() && other.is_insert() && other.at == end {
+            // Typing.
+            self.inserted.push_str(&other.inserted);
+            Merged::Yes
+        } else if self.is_delete() && other.is_delete() && other.at + other.len == self.at {
+            // Backspace.
+            other.removed.push_str(&self.removed);
+            self.removed = other.removed;
+            self.at = other.at;
+            self.len += other.len;
+            Merged::Yes
+        } else if self.is_delete() && other.is_delete() && other.at == self.at {
+            // Delete.
+            self.removed.push_str(&other.removed);
+            self.len += other.len;
+            Merged::Yes
+        } else if self.is_insert()
+            && other.is_delete()
+            && other.at >= self.at
+            && other.at + other.len == end
+        {
+            // Deleting the end of the inserted text.
+            self.inserted
+                .truncate(self.inserted.len() - other.removed.len());
+            if self.inserted.is_empty() {
+                Merged::Annul
+            } else {
+                Merged::Yes
+            }
+        } else {
+            Merged::No(other)
+        }
+    }
+}
+
+impl<T> Clone for TextEdit<T> {
+    fn clone(&self) -> Self {
+        TextEdit {
+            at: self.at,
+            len: self.len,
+            removed: self.removed.clone(),
+            inserted: self.inserted.clone(),
+            pd: PhantomData,
+        }
+    }
+}
+
+impl<T> Debug for TextEdit<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("TextEdit")
+            .field("at", &self.at)
+            .field("len", &self.len)
+            .field("removed", &self.removed)
+            .field("inserted", &self.inserted)
+            .finish()
+    }
+}
+
+impl<T> Display for TextEdit<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match (self.is_insert(), self.is_delete()) {
+            (true, _) => write!(f, "Insert {:?}", self.inserted),
+            (false, true) => write!(f, "Delete {:?}", self.removed),
+            (false, false) => write!(f, "Replace {:?} with {:?}", self.removed, self.inserted),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt::{self, Display, Formatter};
+use undo::{Edit, Merged};
+
+/// Appends a string to the target and merges with the following appends.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Append(pub String);
+
+impl Append {
+    pub fn new(s: &str) -> Append {
+        Append(s.to_string())
+    }
+}
+
+impl Edit for Append {
+    type Target = String;
+    type Output = ();
+
+    fn edit(&mut self, target: &mut String) {
+        target.push_str(&self.0);
+    }
+
+    fn undo(&mut self, target: &mut String) {
+        target.truncate(target.len() - self.0.len());
+    }
+
+    fn merge(&mut self, other: Self) -> Merged<Self> {
+        self.0.push_str(&other.0);
+        Merged::Yes
+    }
+}
+
+impl Display for Append {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
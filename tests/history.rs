@@ -1,4 +1,8 @@
-use undo::{Add, At, History};
+mod common;
+
+use common::Append;
+use core::num::NonZeroUsize;
+use undo::{Add, At, Edit, History, Merged, Record, Truncate};
 
 const A: Add = Add('a');
 const B: Add = Add('b');
@@ -122,6 +126,67 @@ fn checkpoint() {
     assert_eq!(target, "");
 }
 
+#[test]
+fn checkpoint_cancel_merged() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, Append::new("a"));
+    let before = history.display().detailed(false).to_string();
+
+    let mut checkpoint = history.checkpoint();
+    checkpoint.edit(&mut target, Append::new("b"));
+    assert_eq!(target, "ab");
+    checkpoint.cancel(&mut target);
+
+    assert_eq!(target, "a");
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.display().detailed(false).to_string(), before);
+    history.undo(&mut target);
+    assert_eq!(target, "");
+}
+
+#[test]
+fn checkpoint_cancel_annulled() {
+    struct Step(i32);
+
+    impl Edit for Step {
+        type Target = i32;
+        type Output = ();
+
+        fn edit(&mut self, target: &mut i32) {
+            *target += self.0;
+        }
+
+        fn undo(&mut self, target: &mut i32) {
+            *target -= self.0;
+        }
+
+        fn merge(&mut self, other: Self) -> Merged<Self> {
+            if self.0 + other.0 == 0 {
+                Merged::Annul
+            } else {
+                Merged::No(other)
+            }
+        }
+    }
+
+    let mut target = 0;
+    let mut history = History::new();
+    history.edit(&mut target, Step(1));
+    history.edit(&mut target, Step(2));
+
+    let mut checkpoint = history.checkpoint();
+    checkpoint.edit(&mut target, Step(-2));
+    assert_eq!(target, 1);
+    checkpoint.cancel(&mut target);
+
+    assert_eq!(target, 3);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.head(), At::new(0, 2));
+    history.undo(&mut target);
+    assert_eq!(target, 1);
+}
+
 #[test]
 fn next_and_prev() {
     let mut target = String::new();
@@ -144,3 +209,1276 @@ fn next_and_prev() {
     assert_eq!(history.next_branch_head(), Some(At::new(1, 2)));
     assert_eq!(history.prev_branch_head(), None);
 }
+
+#[test]
+fn compress() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, Append::new("a"));
+    history.set_saved();
+    history.edit(&mut target, Append::new("b"));
+    history.set_saved();
+    history.edit(&mut target, Append::new("c"));
+    history.undo(&mut target).unwrap();
+    history.set_saved();
+    history.edit(&mut target, Append::new("d"));
+    history.set_saved();
+    history.edit(&mut target, Append::new("e"));
+    history.clear_saved();
+    assert_eq!(history.len(), 4);
+
+    history.compress();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.head(), At::new(1, 2));
+    assert_eq!(history.get_branch(0).unwrap().parent(), At::new(1, 1));
+
+    history.go_to(&mut target, At::new(0, 2));
+    assert_eq!(target, "abc");
+    history.undo(&mut target).unwrap();
+    assert_eq!(target, "ab");
+    history.undo(&mut target).unwrap();
+    assert_eq!(target, "");
+}
+
+#[test]
+fn compress_barriers_and_labels() {
+    let mut target = String::new();
+    let mut history = History::builder().auto_promote(false).build();
+    history.edit(&mut target, Append::new("a"));
+    history.push_barrier("sent");
+    history.set_saved();
+    history.edit(&mut target, Append::new("b"));
+    history.set_saved();
+    history.edit(&mut target, Append::new("c"));
+    history.clear_saved();
+    let trunk = history.trunk();
+
+    // The trunk is kept on the merged entry after the branch is left.
+    history.go_to(&mut target, At::new(0, 1));
+    history.edit(&mut target, Append::new("d"));
+    assert_eq!(history.trunk(), trunk);
+    history.compress();
+    assert_eq!(history.len(), 2);
+    let edits: Vec<_> = history.entries().map(|e| e.as_ref().0.as_str()).collect();
+    assert_eq!(edits, ["a", "d"]);
+
+    let trunk = history.trunk();
+    assert_ne!(trunk, history.head());
+    history.go_to(&mut target, trunk);
+    assert_eq!(target, "abc");
+}
+
+#[test]
+fn time_travel() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    std::thread::sleep(std::time::Duration::from_millis(1));
+    let st = std::time::SystemTime::now();
+    std::thread::sleep(std::time::Duration::from_millis(1));
+    history.undo(&mut target).unwrap();
+    history.edit(&mut target, C);
+    assert_eq!(target, "ac");
+
+    history.time_travel(&mut target, st);
+    assert_eq!(target, "ab");
+    history.time_travel(&mut target, std::time::SystemTime::UNIX_EPOCH);
+    assert_eq!(target, "");
+}
+
+#[test]
+fn stable_ids() {
+    fn labels(history: &History<Add>) -> Vec<String> {
+        let mut display = history.display();
+        display
+            .detailed(false)
+            .head(false)
+            .saved(false)
+            .stable_ids(true);
+        #[cfg(feature = "colored")]
+        display.colored(false);
+        let mut labels: Vec<_> = display
+            .to_string()
+            .lines()
+            .filter_map(|line| {
+                line.split_whitespace()
+                    .find(|s| s.contains('-') && s.chars().next().unwrap().is_ascii_digit())
+                    .map(String::from)
+            })
+            .collect();
+        labels.sort();
+        labels
+    }
+
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.edit(&mut target, C);
+    history.undo(&mut target).unwrap();
+    history.undo(&mut target).unwrap();
+    history.edit(&mut target, D);
+    let before = labels(&history);
+    assert_eq!(before, ["0-0", "0-1", "0-2", "0-3", "1-2"]);
+
+    history.go_to(&mut target, At::new(0, 3));
+    assert_eq!(target, "abc");
+    assert_eq!(labels(&history), before);
+}
+
+#[test]
+fn savepoints() {
+    let mut target = String::new();
+    let mut history = History::builder().limit(3).build();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.edit(&mut target, C);
+    history.save_as("abc");
+    history.undo(&mut target).unwrap();
+    history.edit(&mut target, D);
+    history.save_as("abd");
+    // The limit is reached and 'a' is removed.
+    history.edit(&mut target, E);
+    assert_eq!(target, "abde");
+    assert_eq!(history.savepoint("abc"), Some(At::new(0, 2)));
+
+    history.revert_to(&mut target, "abc");
+    assert_eq!(target, "abc");
+    history.revert_to(&mut target, "abd");
+    assert_eq!(target, "abd");
+    assert_eq!(history.saved_states().count(), 2);
+}
+
+#[test]
+fn head_changed() {
+    use std::sync::mpsc;
+    use undo::Event;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut target = String::new();
+    let mut history = History::builder().connect(sender).build();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    let ab = history.head();
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    let ac = history.head();
+    receiver.try_iter().for_each(drop);
+
+    history.go_to(&mut target, ab);
+    assert_eq!(target, "ab");
+    let events: Vec<_> = receiver.try_iter().collect();
+    let heads: Vec<_> = events
+        .iter()
+        .filter(|event| matches!(event, Event::HeadChanged { .. }))
+        .collect();
+    assert_eq!(
+        heads,
+        [&Event::HeadChanged {
+            old: ac,
+            new: history.head()
+        }]
+    );
+    assert!(events.contains(&Event::IndexChanged { old: 1, new: 2 }));
+    assert!(events.contains(&Event::Head(history.head())));
+}
+
+#[test]
+fn rebase() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.edit(&mut target, C);
+    let abc = history.head();
+    history.go_to(&mut target, At::new(abc.root, 1));
+    history.edit(&mut target, D);
+    history.edit(&mut target, E);
+    assert_eq!(target, "ade");
+
+    // Replay 'b' and 'c' on top of 'e'.
+    let tip = history.head();
+    let outputs = history.rebase(&mut target, abc.root, tip);
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(target, "adebc");
+    assert_eq!(history.len(), 5);
+
+    // The rebased branch is kept.
+    history.go_to(&mut target, abc);
+    assert_eq!(target, "abc");
+    assert!(history.rebase(&mut target, 100, abc).is_empty());
+    assert!(history
+        .rebase(&mut target, abc.root, At::new(abc.root, 10))
+        .is_empty());
+}
+
+#[test]
+fn rebase_after_switching_branches() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.edit(&mut target, C);
+    let abc = history.head();
+    history.go_to(&mut target, At::new(abc.root, 1));
+    history.edit(&mut target, D);
+    history.edit(&mut target, E);
+    let ade = history.head();
+
+    // The branch with 'b' and 'c' is the current root after switching to it,
+    // but only the edits after the split are replayed.
+    history.go_to(&mut target, abc);
+    assert_eq!(history.head().root, abc.root);
+    let outputs = history.rebase(&mut target, abc.root, ade);
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(target, "adebc");
+
+    // The branch that now ends with 'b' and 'c' is found the same way when it is not the root.
+    history.go_to(&mut target, abc);
+    assert_ne!(history.head().root, ade.root);
+    history.rebase(&mut target, ade.root, abc);
+    assert_eq!(target, "abcdebc");
+}
+
+#[test]
+fn prune() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.edit(&mut target, C);
+    history.set_saved();
+    history.undo(&mut target);
+    history.edit(&mut target, D);
+    history.undo(&mut target);
+    history.undo(&mut target);
+    history.edit(&mut target, E);
+    history.undo(&mut target);
+    history.undo(&mut target);
+    history.edit(&mut target, F);
+    assert_eq!(history.branches().count(), 4);
+
+    // Nothing has been used since now, but the root branch is kept.
+    std::thread::sleep(std::time::Duration::from_millis(1));
+    let now = std::time::SystemTime::now();
+    assert_eq!(history.prune_older_than(now), 3);
+    assert_eq!(history.branches().count(), 1);
+    assert_eq!(history.saved(), None);
+    assert_eq!(history.prune_branches(|_, _| false), 0);
+    assert_eq!(target, "f");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn schema() {
+    use undo::schema::{self, HistoryDe};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Push(char);
+
+    impl Edit for Push {
+        type Target = String;
+        type Output = ();
+
+        fn edit(&mut self, target: &mut String) {
+            target.push(self.0);
+        }
+
+        fn undo(&mut self, target: &mut String) {
+            self.0 = target.pop().unwrap();
+        }
+    }
+
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, Push('a'));
+    history.edit(&mut target, Push('b'));
+    let ab = history.head();
+    history.undo(&mut target);
+    history.edit(&mut target, Push('c'));
+    history.save_as("ac");
+
+    // Version 0 is the format derived from the internal structures.
+    let legacy = serde_json::to_string(&history).unwrap();
+    let de: HistoryDe<Push> = serde_json::from_str(&legacy).unwrap();
+    assert_eq!(de.version, 0);
+    let mut migrated = History::from_serializable(de).unwrap();
+    assert_eq!(migrated.savepoint("ac"), Some(history.head()));
+    migrated.go_to(&mut target, ab);
+    assert_eq!(target, "ab");
+
+    let current = serde_json::to_string(&migrated.into_serializable()).unwrap();
+    let de: HistoryDe<Push> = serde_json::from_str(&current).unwrap();
+    assert_eq!(de.version, schema::VERSION);
+    assert!(History::from_serializable(de).is_ok());
+
+    let mut de: HistoryDe<Push> = serde_json::from_str(&current).unwrap();
+    de.version = schema::VERSION + 1;
+    assert_eq!(
+        History::from_serializable(de).err(),
+        Some(schema::Error::UnsupportedVersion(schema::VERSION + 1))
+    );
+}
+
+#[test]
+fn checkpoint_restores_tree() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.edit(&mut target, C);
+    let abc = history.head();
+    history.undo(&mut target);
+    history.edit(&mut target, D);
+    history.set_saved();
+    history.save_as("abd");
+    let abd = history.head();
+    let tree = |history: &History<Add>| history.display().detailed(false).to_string();
+    let before = tree(&history);
+
+    let mut outer = history.checkpoint();
+    outer.go_to(&mut target, abc);
+    outer.undo(&mut target);
+    outer.undo(&mut target);
+    outer.edit(&mut target, E);
+    {
+        let mut inner = outer.checkpoint();
+        inner.edit(&mut target, F);
+        inner.go_to(&mut target, abd);
+        inner.undo(&mut target);
+        inner.edit(&mut target, G);
+        assert_eq!(target, "abg");
+        inner.cancel(&mut target);
+    }
+    assert_eq!(target, "ae");
+    {
+        let mut inner = outer.checkpoint();
+        inner.undo(&mut target);
+        inner.edit(&mut target, H);
+        inner.commit();
+    }
+    assert_eq!(target, "ah");
+    outer.cancel(&mut target);
+
+    assert_eq!(target, "abd");
+    assert_eq!(history.head(), abd);
+    assert!(history.is_saved());
+    assert_eq!(history.savepoint("abd"), Some(abd));
+    assert_eq!(tree(&history), before);
+
+    // The history still works after the checkpoint.
+    history.undo(&mut target);
+    history.edit(&mut target, I);
+    history.go_to(&mut target, abc);
+    assert_eq!(target, "abc");
+}
+
+#[test]
+fn topology() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.edit(&mut target, C);
+    let abc = history.head();
+    history.undo(&mut target);
+    history.undo(&mut target);
+    history.edit(&mut target, D);
+    let ad = history.head();
+    history.undo(&mut target);
+    history.edit(&mut target, E);
+    let ae = history.head();
+    let a = history.parent_of(ae).unwrap();
+
+    let origin = history.parent_of(a).unwrap();
+    assert_eq!(history.parent_of(origin), None);
+    assert_eq!(history.parent_of(ad), Some(a));
+    assert_eq!(history.parent_of(ae), Some(a));
+    let ab = history.parent_of(abc).unwrap();
+    assert_eq!(history.parent_of(ab), Some(a));
+
+    let children = history.children_of(a);
+    assert_eq!(children.len(), 3);
+    assert!(children.contains(&ab) && children.contains(&ad) && children.contains(&ae));
+    assert!(history.children_of(abc).is_empty());
+    assert_eq!(history.children_of(origin), [a]);
+
+    assert_eq!(history.path_between(abc, ad), [abc, ab, a, ad]);
+    assert_eq!(history.path_between(a, abc), [a, ab, abc]);
+    assert_eq!(history.path_between(ae, ae), [ae]);
+    assert!(history.path_between(ae, At::new(100, 1)).is_empty());
+}
+
+#[test]
+fn set_limit() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, E);
+    history.undo(&mut target);
+    history.edit(&mut target, B);
+    history.edit(&mut target, F);
+    history.set_saved();
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    history.edit(&mut target, D);
+    assert_eq!(history.branches().count(), 3);
+
+    // The branch with 'e' split off from a removed entry.
+    let limit = NonZeroUsize::new(2).unwrap();
+    assert_eq!(history.set_limit(limit, Truncate::RejectIfSmaller), None);
+    assert_eq!(history.set_limit(limit, Truncate::Front), Some(2));
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.branches().count(), 2);
+
+    let saved = history.saved().unwrap();
+    history.go_to(&mut target, saved);
+    assert_eq!(target, "abf");
+    assert!(history.is_saved());
+    history.undo(&mut target);
+    assert!(!history.can_undo());
+    assert_eq!(target, "ab");
+}
+
+#[test]
+fn groups() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.begin_group("Add bc");
+    history.edit(&mut target, B);
+    history.edit(&mut target, C);
+    history.end_group();
+    let abc = history.head();
+    history.undo(&mut target);
+    assert_eq!(target, "a");
+
+    // The group is kept together in the new branch.
+    history.edit(&mut target, D);
+    history.go_to(&mut target, abc);
+    assert_eq!(target, "abc");
+    assert_eq!(history.undo_string().as_deref(), Some("Add bc"));
+    history.undo(&mut target);
+    assert_eq!(target, "a");
+}
+
+#[test]
+fn branch_event() {
+    use std::sync::mpsc;
+    use undo::Event;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut target = String::new();
+    let mut history = History::builder().connect(sender).build();
+    history.edit(&mut target, A);
+    let first = history.head().root;
+    history.undo(&mut target);
+    history.edit(&mut target, B);
+    let second = history.head().root;
+    history.go_to(&mut target, At::new(first, 1));
+
+    let branches: Vec<_> = receiver
+        .try_iter()
+        .filter_map(|event| match event {
+            Event::Branch { old, new } => Some((old, new)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(branches, [(first, second), (second, first)]);
+}
+
+#[test]
+fn get_entry_mut() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    let ab = history.head();
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+
+    assert!(history.get_entry_mut(At::new(ab.root, 0)).is_none());
+    assert!(history.get_entry_mut(At::new(ab.root, 3)).is_none());
+    *history.get_entry_mut(ab).unwrap().as_mut() = D;
+    let parent = history.parent_of(ab).unwrap();
+    assert_eq!(*history.get_entry_mut(parent).unwrap().as_mut(), A);
+
+    history.go_to(&mut target, ab);
+    assert_eq!(target, "ad");
+}
+
+#[test]
+fn memory_limit() {
+    let mut target = String::new();
+    let mut history = History::builder().memory_limit(12).build();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    history.edit(&mut target, D);
+    assert_eq!(history.branches().count(), 2);
+
+    // The branch with 'b' splits off after 'a', so it now starts at the start of the history.
+    history.edit(&mut target, E);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.memory_usage(), 12);
+    assert_eq!(history.branches().count(), 2);
+    let root = history.head().root;
+    let (b, branch) = history.branches().find(|&(id, _)| id != root).unwrap();
+    assert_eq!(branch.parent(), At::new(root, 0));
+    history.go_to(&mut target, At::new(b, 1));
+    assert_eq!(target, "ab");
+    history.go_to(&mut target, At::new(b, 0));
+    assert_eq!(target, "a");
+}
+
+#[test]
+fn promote_branch() {
+    let mut target = String::new();
+    let mut history = History::builder().auto_promote(false).build();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    let ab = history.head();
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    history.edit(&mut target, D);
+    let acd = history.head();
+
+    // The first branch is still shown as the main line.
+    assert_eq!(history.trunk(), ab);
+    let display = history.display().detailed(false).to_string();
+    assert!(display.lines().next().unwrap().ends_with("Add 'b'"));
+
+    // Edits at the end of the trunk extend it.
+    history.go_to(&mut target, ab);
+    history.edit(&mut target, E);
+    assert_eq!(history.trunk(), history.head());
+
+    history.go_to(&mut target, acd);
+    history.promote_branch();
+    assert_eq!(history.trunk(), acd);
+    let display = history.display().detailed(false).to_string();
+    assert!(display.lines().next().unwrap().ends_with("Add 'd'"));
+}
+
+#[test]
+fn display_json() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+
+    let json = history.display().json(true).to_string();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["head"], serde_json::json!({"root": 1, "index": 2}));
+    assert_eq!(json["saved"], serde_json::json!({"root": 1, "index": 0}));
+
+    let nodes = json["nodes"].as_array().unwrap();
+    assert_eq!(nodes.len(), 4);
+    let edits: Vec<_> = nodes.iter().map(|n| n["edit"].as_str()).collect();
+    assert_eq!(
+        edits,
+        [None, Some("Add 'a'"), Some("Add 'b'"), Some("Add 'c'")]
+    );
+    // Both b and c have a as parent.
+    assert_eq!(nodes[2]["parent"], nodes[1]["at"]);
+    assert_eq!(nodes[3]["parent"], nodes[1]["at"]);
+
+    let dot = history.display().dot(true).to_string();
+    assert_eq!(dot.matches("->").count(), 3);
+    assert!(dot.contains(r#""1-2" [label="Add 'c'", style=bold];"#));
+}
+
+#[test]
+fn coalesce_events() {
+    use std::sync::mpsc;
+    use undo::Event;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut target = String::new();
+    let mut history = History::builder()
+        .coalesce_events(true)
+        .connect(sender)
+        .build();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    let ab = history.head();
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    let events: Vec<_> = receiver.try_iter().collect();
+    assert_eq!(events.len(), 4);
+    assert!(events.iter().all(|e| matches!(e, Event::Batch(_))));
+
+    // Going back and forth between the branches cancels out.
+    history.begin_batch();
+    let ac = history.head();
+    history.go_to(&mut target, ab);
+    history.go_to(&mut target, ac);
+    history.end_batch();
+    assert_eq!(receiver.try_iter().next(), None);
+    assert_eq!(target, "ac");
+}
+
+#[test]
+fn branch_labels() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.set_branch_label(history.head().root, "main");
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    history.set_branch_label(history.head().root, "experiment-1");
+    history.undo(&mut target);
+    history.edit(&mut target, D);
+
+    // The labels follow the branches when the root changes.
+    let main = history.find_branch("main").unwrap();
+    history.go_to(&mut target, At::new(main, 2));
+    assert_eq!(target, "ab");
+    assert_eq!(history.branch_label(history.head().root), Some("main"));
+    let experiment = history.find_branch("experiment-1").unwrap();
+    history.go_to(&mut target, At::new(experiment, 2));
+    assert_eq!(target, "ac");
+    assert_eq!(
+        history.branch_label(history.head().root),
+        Some("experiment-1")
+    );
+
+    let display = history.display().detailed(false).to_string();
+    assert!(display.contains("main"));
+    assert!(display.contains("experiment-1"));
+
+    assert_eq!(history.remove_branch_label(main).as_deref(), Some("main"));
+    assert_eq!(history.find_branch("main"), None);
+}
+
+#[test]
+fn stats() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    let ab = history.head();
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    history.go_to(&mut target, ab);
+    assert_eq!(target, "ab");
+
+    let stats = history.stats();
+    assert_eq!(stats.edits, 3);
+    assert_eq!(stats.undos, 2);
+    assert_eq!(stats.redos, 1);
+    assert_eq!(stats.branches, 2);
+    assert!(stats.earliest.is_some());
+
+    history.reset_stats();
+    assert_eq!(history.stats().edits, 0);
+    assert_eq!(history.stats().branches, 2);
+}
+
+#[test]
+fn go_to_with_report() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.edit(&mut target, C);
+    let abc = history.head();
+    history.undo(&mut target);
+    history.undo(&mut target);
+    history.edit(&mut target, D);
+    history.edit(&mut target, E);
+    history.undo(&mut target);
+    history.edit(&mut target, F);
+    assert_eq!(target, "adf");
+
+    let report = history.go_to_with_report(&mut target, abc);
+    assert_eq!(target, "abc");
+    assert_eq!(report.outputs.len(), 4);
+    let mut edit_at = |at| history.get_entry_mut(at).map(|e| e.as_ref().0);
+    let undone: Vec<_> = report.undone.iter().map(|&at| edit_at(at)).collect();
+    let redone: Vec<_> = report.redone.iter().map(|&at| edit_at(at)).collect();
+    assert_eq!(undone, [Some('f'), Some('d')]);
+    assert_eq!(redone, [Some('b'), Some('c')]);
+    assert_eq!(report.redone.last(), Some(&abc));
+
+    let report = history.go_to_with_report(&mut target, abc);
+    assert!(report.undone.is_empty() && report.redone.is_empty());
+}
+
+#[test]
+fn max_branches() {
+    use std::sync::mpsc;
+    use undo::Event;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut target = String::new();
+    let mut history = History::builder().max_branches(1).connect(sender).build();
+    history.edit(&mut target, Add('a'));
+    let a = history.head();
+    history.undo(&mut target);
+    history.edit(&mut target, Add('b'));
+    let b = history.head();
+    assert_eq!(history.branches().count(), 2);
+
+    // The branch of 'a' is left before the branch of 'b', so it is the one that is dropped.
+    history.go_to(&mut target, a);
+    history.go_to(&mut target, b);
+    history.undo(&mut target);
+    history.edit(&mut target, Add('c'));
+    assert_eq!(target, "c");
+    assert_eq!(history.branches().count(), 2);
+    let dropped: Vec<_> = receiver
+        .try_iter()
+        .filter_map(|e| match e {
+            Event::BranchDropped(id) => Some(id),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(dropped.len(), 1);
+    history.go_to(&mut target, b);
+    assert_eq!(target, "b");
+
+    assert_eq!(history.set_max_branches(Some(0)), 1);
+    assert_eq!(history.branches().count(), 1);
+    assert_eq!(history.max_branches(), Some(0));
+}
+
+#[test]
+fn total_limit() {
+    use std::sync::mpsc;
+    use undo::Event;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut target = String::new();
+    let mut history = History::builder().total_limit(3).connect(sender).build();
+    history.edit(&mut target, Add('a'));
+    history.edit(&mut target, Add('b'));
+    history.undo(&mut target);
+    history.edit(&mut target, Add('c'));
+    let c = history.head();
+    assert_eq!(history.total_len(), 3);
+    assert_eq!(history.branches().count(), 2);
+
+    // The branch of 'b' is dropped before any entry in the current branch.
+    history.edit(&mut target, Add('d'));
+    assert_eq!(history.total_len(), 3);
+    assert_eq!(history.branches().count(), 1);
+    assert!(receiver
+        .try_iter()
+        .any(|e| matches!(e, Event::BranchDropped(_))));
+
+    // Then the oldest entries in the current branch are dropped.
+    history.edit(&mut target, Add('e'));
+    assert_eq!(target, "acde");
+    assert_eq!(history.total_len(), 3);
+    assert!(receiver.try_iter().any(|e| matches!(e, Event::Dropped(1))));
+    history.go_to(&mut target, At::new(c.root, 1));
+    assert_eq!(target, "ac");
+    history.go_to(&mut target, At::new(c.root, 0));
+    assert_eq!(target, "a");
+}
+
+#[test]
+fn push_unexecuted() {
+    use undo::Entry;
+
+    let mut target = String::from("ab");
+    let mut history = History::new();
+    history.push_unexecuted(Entry::new(A));
+    history.push_unexecuted(Entry::new(B).with_meta("user", "bob"));
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.get_entry(1).unwrap().get_meta("user"), Some("bob"));
+
+    history.undo(&mut target);
+    target.push('c');
+    history.push_unexecuted(Entry::new(C));
+    assert_eq!(history.branches().count(), 2);
+    history.undo(&mut target);
+    history.undo(&mut target);
+    assert_eq!(target, "");
+}
+
+#[test]
+fn undo_all_redo_all() {
+    use std::sync::mpsc;
+    use undo::Event;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut target = String::new();
+    let mut history = History::builder().connect(sender).build();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    history.edit(&mut target, D);
+    receiver.try_iter().for_each(drop);
+
+    assert_eq!(history.undo_all(&mut target).len(), 3);
+    assert_eq!(target, "");
+    let events: Vec<_> = receiver.try_iter().collect();
+    assert_eq!(
+        events
+            .iter()
+            .filter(|event| matches!(event, Event::IndexChanged { .. }))
+            .count(),
+        1
+    );
+    assert!(history.undo_all(&mut target).is_empty());
+
+    assert_eq!(history.redo_all(&mut target).len(), 3);
+    assert_eq!(target, "acd");
+    assert_eq!(history.branches().count(), 2);
+}
+
+#[test]
+fn diff() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.edit(&mut target, C);
+    let abc = history.head();
+    history.undo(&mut target);
+    history.undo(&mut target);
+    history.edit(&mut target, D);
+    history.edit(&mut target, E);
+    history.edit(&mut target, F);
+
+    let diff = history.diff(history.head(), abc).unwrap();
+    let undo: Vec<_> = diff.undo.iter().map(|(_, e)| *e.as_ref()).collect();
+    let redo: Vec<_> = diff.redo.iter().map(|(_, e)| *e.as_ref()).collect();
+    assert_eq!(undo, [F, E, D]);
+    assert_eq!(redo, [B, C]);
+    assert_eq!(target, "adef");
+
+    let report = history.go_to_with_report(&mut target, abc);
+    assert_eq!(report.undone.len(), undo.len());
+    assert_eq!(report.redone.len(), redo.len());
+
+    let diff = history.diff(history.head(), history.head()).unwrap();
+    assert!(diff.undo.is_empty() && diff.redo.is_empty());
+    assert!(history.diff(history.head(), At::new(99, 0)).is_none());
+}
+
+#[test]
+fn display_window() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    history.edit(&mut target, D);
+
+    let s = history
+        .display()
+        .detailed(false)
+        .limit(2)
+        .reverse(true)
+        .to_string();
+    let lines: Vec<_> = s.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("Add 'c'"));
+    assert!(lines[1].ends_with("Add 'd'"));
+
+    let s = history.display().detailed(false).range(..1).to_string();
+    assert_eq!(s.lines().count(), 1);
+    assert!(!s.contains("Add"));
+}
+
+#[test]
+fn set_saved_at() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    let ab = history.head();
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+
+    assert!(history.set_saved_at(ab));
+    assert_eq!(history.saved(), Some(ab));
+    assert!(!history.set_saved_at(At::new(99, 0)));
+    assert_eq!(history.saved(), Some(ab));
+
+    // The saved state follows the position when the branches are switched.
+    history.go_to(&mut target, ab);
+    assert!(history.is_saved());
+    history.undo(&mut target);
+    history.redo(&mut target);
+    assert!(history.is_saved());
+
+    assert!(history.set_saved_at(At::new(history.head().root, 1)));
+    history.revert(&mut target);
+    assert_eq!(target, "a");
+}
+
+#[test]
+fn testing_roundtrip() {
+    use undo::testing::assert_history_roundtrip;
+
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    history.edit(&mut target, D);
+    history.undo(&mut target);
+    history.undo(&mut target);
+    history.edit(&mut target, E);
+    history.set_saved();
+    history.undo(&mut target);
+
+    assert_history_roundtrip(&mut history, &mut target);
+    assert_eq!(target, "a");
+    assert_eq!(history.branches().count(), 3);
+}
+
+#[test]
+fn visit() {
+    use undo::history::{Labels, TreeVisitor};
+    use undo::Entry;
+
+    #[derive(Default)]
+    struct Collect {
+        entries: Vec<At>,
+        depth: usize,
+        max_depth: usize,
+        saved: Option<At>,
+    }
+
+    impl TreeVisitor<Add> for Collect {
+        fn enter_branch(&mut self, _: usize, _: At) {
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+        }
+
+        fn entry(&mut self, at: At, _: &Entry<Add>, labels: Labels) {
+            self.entries.push(at);
+            if labels.saved {
+                self.saved = Some(at);
+            }
+        }
+
+        fn exit_branch(&mut self, _: usize) {
+            self.depth -= 1;
+        }
+    }
+
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, Add('a'));
+    history.edit(&mut target, Add('b'));
+    history.set_saved();
+    history.edit(&mut target, Add('c'));
+    history.undo(&mut target);
+    history.undo(&mut target);
+    history.edit(&mut target, Add('d'));
+    history.undo(&mut target);
+    history.edit(&mut target, Add('e'));
+
+    let mut collect = Collect::default();
+    history.visit(&mut collect);
+    assert_eq!(collect.depth, 0);
+    assert_eq!(collect.max_depth, 2);
+    assert_eq!(collect.entries.len(), history.all_entries().count());
+    assert_eq!(collect.saved, history.saved());
+    assert!(collect.saved.is_some());
+}
+
+#[test]
+fn ui_state() {
+    use undo::UiState;
+
+    let mut target = String::new();
+    let mut history = History::builder()
+        .coalesce_events(true)
+        .connect(UiState::new())
+        .build();
+    history.edit(&mut target, Add('a'));
+    history.undo(&mut target);
+    history.edit(&mut target, Add('b'));
+    history.set_saved();
+    let b = history.head();
+    let state = *history.slot_mut().unwrap();
+    assert!(state.can_undo());
+    assert!(!state.can_redo());
+    assert!(state.is_saved());
+    assert_eq!(state.head(), Some(b));
+
+    let a = history.children_of(At::new(b.root, 0))[1];
+    history.go_to(&mut target, a);
+    let state = *history.slot_mut().unwrap();
+    assert!(!state.is_saved());
+    assert_eq!(state.head(), Some(history.head()));
+    assert_eq!(state.index(), 1);
+}
+
+#[test]
+fn extract_path() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.save_as("ab");
+    let ab = history.head();
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    history.edit(&mut target, D);
+    history.set_saved();
+    let acd = history.head();
+    history.go_to(&mut target, ab);
+
+    let mut record: Record<_> = history.extract_path(acd).unwrap();
+    assert_eq!(record.len(), 3);
+    assert_eq!(record.saved(), Some(3));
+    assert_eq!(record.savepoint("ab"), None);
+    let mut other = String::from("acd");
+    record.undo_all(&mut other);
+    assert_eq!(other, "");
+
+    let record: Record<_> = history.extract_path(ab).unwrap();
+    assert_eq!(record.len(), 2);
+    assert_eq!(record.saved(), None);
+    assert_eq!(record.savepoint("ab"), Some(2));
+    assert!(history.extract_path::<()>(At::new(ab.root, 9)).is_none());
+}
+
+#[test]
+fn eq() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    history.set_saved();
+
+    let mut fork = history.fork::<()>();
+    assert_eq!(history, fork);
+    fork.go_to(&mut target, At::new(0, 2));
+    assert_ne!(history, fork);
+    fork.go_to(&mut target, history.head());
+    assert_eq!(history, fork);
+    fork.set_branch_label(history.head().root, "c");
+    assert_ne!(history, fork);
+
+    let record = Record::from(history.fork::<()>());
+    assert_eq!(record, Record::<_>::from(history));
+}
+
+#[test]
+fn document_autosave() {
+    use core::num::NonZeroUsize;
+    use std::cell::Cell;
+    use std::io;
+    use std::rc::Rc;
+    use undo::Document;
+
+    let saves = Rc::new(Cell::new(0));
+    let count = saves.clone();
+    let mut document = Document::new(String::new(), move |target: &String| {
+        if target.ends_with('c') {
+            return Err(io::Error::other("full"));
+        }
+        count.set(count.get() + 1);
+        Ok(())
+    });
+    document.set_autosave(NonZeroUsize::new(2));
+    document.edit(A);
+    assert_eq!(saves.get(), 0);
+    document.edit(B);
+    assert_eq!(saves.get(), 1);
+    assert!(!document.is_dirty());
+
+    document.edit(C);
+    document.undo();
+    // Undoing back to the saved state does not save.
+    assert!(!document.is_dirty());
+    assert_eq!(saves.get(), 1);
+    document.redo();
+    document.edit(C);
+    assert_eq!(saves.get(), 1);
+    assert!(document.is_dirty());
+    assert!(document.take_autosave_error().is_some());
+    document.revert();
+    assert_eq!(document.target(), "ab");
+}
+
+#[test]
+fn reflog() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    assert_eq!(history.reflog().count(), 0);
+
+    history.set_reflog_limit(3);
+    history.edit(&mut target, B);
+    let ab = history.head();
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    history.go_to(&mut target, ab);
+    let moves: Vec<_> = history.reflog().map(|m| (m.from, m.to)).collect();
+    let a = history.parent_of(ab);
+    let ac = history.children_of(a.unwrap())[1];
+    assert_eq!(moves, [(Some(ab), a), (a, Some(ac)), (Some(ac), Some(ab))]);
+
+    history.prune_branches(|_, _| false);
+    let last = history.reflog().last().unwrap();
+    assert_eq!((last.from, last.to), (None, Some(ab)));
+}
+
+#[test]
+fn go_to_with() {
+    use undo::{Ignore, Last};
+
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    let ab = history.head();
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    history.set_saved();
+    let ac = history.head();
+
+    let Ignore = history.go_to_with(&mut target, ab);
+    assert_eq!(target, "ab");
+    let last: Last<()> = history.revert_with(&mut target);
+    assert_eq!(last.into_inner(), Some(()));
+    assert_eq!(history.head(), ac);
+    assert_eq!(target, "ac");
+    let last: Last<()> = history.go_to_with(&mut target, ac);
+    assert_eq!(last.into_inner(), None);
+}
+
+#[test]
+fn redo_into() {
+    let mut target = String::new();
+    let mut history = History::new();
+    history.edit(&mut target, A);
+    history.edit(&mut target, B);
+    history.edit(&mut target, C);
+    let abc = history.head().root;
+    history.undo(&mut target);
+    history.undo(&mut target);
+    history.edit(&mut target, D);
+    let ad = history.head().root;
+    history.undo(&mut target);
+
+    assert_eq!(history.children_of(history.head()).len(), 2);
+    assert_eq!(history.redo_into(&mut target, abc), Some(()));
+    assert_eq!(target, "ab");
+    assert_eq!(history.head(), At::new(abc, 2));
+    assert!(history.can_redo());
+    assert_eq!(history.redo_into(&mut target, ad), None);
+    assert_eq!(history.redo_into(&mut target, abc), Some(()));
+    assert_eq!(target, "abc");
+
+    history.undo(&mut target);
+    history.undo(&mut target);
+    assert_eq!(history.redo_into(&mut target, ad), Some(()));
+    assert_eq!(target, "ad");
+}
+
+#[test]
+fn dirty_region() {
+    use core::ops::Range;
+    use undo::RegionEdit;
+
+    struct Push(usize);
+
+    impl Edit for Push {
+        type Target = Vec<usize>;
+        type Output = ();
+
+        fn edit(&mut self, target: &mut Vec<usize>) {
+            target.push(self.0);
+        }
+
+        fn undo(&mut self, target: &mut Vec<usize>) {
+            target.pop();
+        }
+    }
+
+    impl RegionEdit for Push {
+        type Region = Range<usize>;
+
+        fn affected(&self) -> Range<usize> {
+            self.0..self.0 + 1
+        }
+    }
+
+    let mut target = Vec::new();
+    let mut history = History::new();
+    history.edit(&mut target, Push(0));
+    history.edit(&mut target, Push(1));
+    assert_eq!(history.dirty_region(), Some(0..2));
+    history.set_saved();
+    assert_eq!(history.dirty_region(), None);
+
+    history.undo(&mut target);
+    history.edit(&mut target, Push(5));
+    // Going from the saved state undoes 1 and applies 5.
+    assert_eq!(history.dirty_region(), Some(1..6));
+    history.revert(&mut target);
+    assert_eq!(history.dirty_region(), None);
+}
+
+#[test]
+fn compact() {
+    use std::sync::mpsc;
+    use undo::Event;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut target = String::new();
+    let mut history = History::builder().reflog(20).connect(sender).build();
+    history.edit(&mut target, A);
+    history.undo(&mut target);
+    history.edit(&mut target, B);
+    history.save_as("b");
+    history.undo(&mut target);
+    history.edit(&mut target, C);
+    history.undo(&mut target);
+    history.edit(&mut target, D);
+    history.set_saved();
+    let d = history.head().root;
+    let a = history.children_of(At::new(d, 0))[1];
+    history.go_to(&mut target, a);
+    history.prune_branches(|id, _| id == d);
+    assert_eq!(history.unused_branch_ids(), 2);
+    let moves = history.reflog().count();
+    receiver.try_iter().count();
+
+    let ids = history.compact();
+    assert_eq!(ids.len(), 2);
+    assert_eq!(history.unused_branch_ids(), 0);
+    assert_eq!(history.head(), history.at(ids[&a.root], 1));
+    // The positions from before the compaction are stale, even if their ids are reused.
+    assert!(history.is_valid(history.head()));
+    assert!(!history.is_valid(a));
+    assert!(history.go_to(&mut target, a).is_empty());
+    assert!(receiver
+        .try_iter()
+        .any(|e| matches!(e, Event::Renumbered(new) if new == ids)));
+    assert_eq!(history.reflog().count(), moves);
+    assert_eq!(history.reflog().last().unwrap().to, Some(history.head()));
+
+    // The labels of the entries can not be given to new entries.
+    history.undo(&mut target);
+    history.edit(&mut target, E);
+    history.edit(&mut target, F);
+    let ef = history.head();
+    assert_eq!(history.reflog().last().unwrap().to, Some(ef));
+    assert_eq!(history.reflog().count(), moves + 3);
+    history.revert(&mut target);
+    assert_eq!(target, "d");
+    history.go_to(&mut target, ef);
+    assert_eq!(target, "ef");
+}
@@ -1,4 +1,8 @@
-use undo::{Add, Record};
+mod common;
+
+use common::Append;
+use core::num::NonZeroUsize;
+use undo::{Add, Any, Edit, Join, MergePolicy, Merged, Record, SizedEdit, Truncate};
 
 const A: Add = Add('a');
 const B: Add = Add('b');
@@ -73,3 +77,1311 @@ fn checkpoint_saved() {
     assert!(record.is_saved());
     assert_eq!(target, "abc");
 }
+
+#[test]
+fn meta() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.set_meta("id", "document-1");
+    record.edit(&mut target, A);
+    record.clear();
+    assert_eq!(record.get_meta("id"), Some("document-1"));
+    assert_eq!(record.remove_meta("id").as_deref(), Some("document-1"));
+    assert_eq!(record.meta().count(), 0);
+}
+
+#[test]
+fn merge_range() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    for s in ["a", "b", "c", "d"] {
+        record.edit(&mut target, Append::new(s));
+        record.set_saved();
+    }
+    assert_eq!(record.len(), 4);
+
+    record.undo(&mut target).unwrap();
+    assert!(!record.merge_range(1..4));
+    assert!(record.merge_range(0..3));
+    assert_eq!(record.len(), 2);
+    assert_eq!(record.head(), 1);
+    assert_eq!(record.saved(), Some(2));
+
+    record.undo(&mut target).unwrap();
+    assert_eq!(target, "");
+    record.redo(&mut target).unwrap();
+    record.redo(&mut target).unwrap();
+    assert_eq!(target, "abcd");
+    assert!(record.is_saved());
+}
+
+#[test]
+fn merge_range_policy_and_barriers() {
+    let mut target = String::new();
+    let mut record = Record::builder().merge_policy(MergePolicy::Never).build();
+    for s in ["a", "b", "c", "d"] {
+        record.edit(&mut target, Append::new(s));
+    }
+    assert!(!record.merge_range(..));
+    assert_eq!(record.len(), 4);
+
+    let mut record = Record::builder()
+        .merge_policy(MergePolicy::With(|_, next: &Append| next.0 != "c"))
+        .build();
+    let mut target = String::new();
+    for s in ["a", "b", "c", "d", "e", "f"] {
+        record.edit(&mut target, Append::new(s));
+        record.set_saved();
+        if s == "a" || s == "d" {
+            record.push_barrier(s);
+        }
+    }
+    record.clear_saved();
+    assert_eq!(record.len(), 6);
+
+    // The entries are only merged between the barriers, and not into "c".
+    assert!(!record.merge_range(..));
+    let entries: Vec<_> = record
+        .entries()
+        .map(|e| (e.as_ref().0.as_str(), e.barrier()))
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            ("a", Some("a")),
+            ("b", None),
+            ("cd", Some("d")),
+            ("ef", None)
+        ]
+    );
+}
+
+#[test]
+fn merge_top() {
+    let mut target = String::new();
+    let mut record = Record::builder().merge_policy(MergePolicy::Never).build();
+    assert!(!record.merge_top());
+    for s in ["a", "b", "c"] {
+        record.edit(&mut target, Append::new(s));
+    }
+    assert_eq!(record.len(), 3);
+
+    record.undo(&mut target).unwrap();
+    assert!(record.merge_top());
+    assert_eq!(record.len(), 2);
+    assert_eq!(record.head(), 1);
+    record.undo(&mut target).unwrap();
+    assert_eq!(target, "");
+}
+
+#[test]
+fn any_merge() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.edit(&mut target, Any::new(Append::new("a")));
+    record.edit(&mut target, Any::new(Append::new("b")));
+    record.edit(&mut target, Any::new(Add('c')));
+    assert_eq!(target, "abc");
+    assert_eq!(record.len(), 2);
+
+    record.undo(&mut target).unwrap();
+    record.undo(&mut target).unwrap();
+    assert_eq!(target, "");
+}
+
+#[test]
+fn join_merge() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.edit(&mut target, Join::new(Append::new("a"), Append::new("b")));
+    record.edit(&mut target, Join::new(Append::new("c"), Append::new("d")));
+    assert_eq!(target, "abcd");
+    assert_eq!(record.len(), 1);
+
+    record.undo(&mut target).unwrap();
+    assert_eq!(target, "");
+}
+
+#[test]
+fn join_merge_disagree() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.edit(&mut target, Join::new(Append::new("a"), A));
+    record.edit(&mut target, Join::new(Append::new("b"), B));
+    assert_eq!(target, "aabb");
+    assert_eq!(record.len(), 2);
+
+    record.undo(&mut target).unwrap();
+    assert_eq!(target, "aa");
+    record.undo(&mut target).unwrap();
+    assert_eq!(target, "");
+}
+
+#[test]
+fn builder_inference() {
+    let mut target = String::new();
+    let mut record = Record::builder().limit(2).build();
+    record.edit(&mut target, A);
+    record.edit(&mut target, B);
+    record.edit(&mut target, C);
+    assert_eq!(record.len(), 2);
+}
+
+#[test]
+fn undo_n_redo_n() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.edit(&mut target, A);
+    record.edit(&mut target, B);
+    record.edit(&mut target, C);
+
+    assert_eq!(record.undo_n(&mut target, 2).len(), 2);
+    assert_eq!(target, "a");
+    assert_eq!(record.undo_n(&mut target, 5).len(), 1);
+    assert_eq!(target, "");
+    assert!(record.undo_n(&mut target, 1).is_empty());
+    assert_eq!(record.redo_n(&mut target, 10).len(), 3);
+    assert_eq!(target, "abc");
+
+    // Groups are undone and redone as a single edit, and barriers stop the undo.
+    record.push_barrier("sent");
+    record.begin_group("Add def");
+    record.edit(&mut target, D);
+    record.edit(&mut target, E);
+    record.edit(&mut target, F);
+    record.end_group();
+    assert_eq!(record.undo_n(&mut target, 1).len(), 1);
+    assert_eq!(target, "abc");
+    assert_eq!(record.redo_n(&mut target, 1).len(), 1);
+    assert_eq!(target, "abcdef");
+    assert_eq!(record.undo_n(&mut target, 3).len(), 1);
+    assert_eq!(target, "abc");
+}
+
+#[test]
+fn try_undo_try_redo_group() {
+    struct Fallible(char);
+
+    impl Edit for Fallible {
+        type Target = (String, bool);
+        type Output = Result<(), char>;
+
+        fn edit(&mut self, (target, fail): &mut (String, bool)) -> Result<(), char> {
+            if *fail {
+                return Err(self.0);
+            }
+            target.push(self.0);
+            Ok(())
+        }
+
+        fn undo(&mut self, (target, fail): &mut (String, bool)) -> Result<(), char> {
+            if *fail {
+                return Err(self.0);
+            }
+            target.pop();
+            Ok(())
+        }
+    }
+
+    let mut target = (String::new(), false);
+    let mut record = Record::new();
+    record.begin_group("Add ab");
+    record.try_edit(&mut target, Fallible('a')).unwrap();
+    record.try_edit(&mut target, Fallible('b')).unwrap();
+    record.end_group();
+
+    assert_eq!(record.try_undo(&mut target), Some(Ok(())));
+    assert_eq!(record.head(), 0);
+    assert_eq!(target.0, "");
+    assert_eq!(record.try_redo(&mut target), Some(Ok(())));
+    assert_eq!(record.head(), 2);
+    assert_eq!(target.0, "ab");
+
+    // The position is not changed if the first undo fails.
+    target.1 = true;
+    assert_eq!(record.try_undo(&mut target), Some(Err('b')));
+    assert_eq!(record.head(), 2);
+}
+
+#[test]
+fn merge_policy() {
+    let mut target = String::new();
+    let mut record = Record::builder()
+        .merge_policy(MergePolicy::With(|_, next: &Append| next.0 != " "))
+        .build();
+    record.edit(&mut target, Append::new("a"));
+    record.edit(&mut target, Append::new("b"));
+    record.edit(&mut target, Append::new(" "));
+    record.edit(&mut target, Append::new("c"));
+    assert_eq!(target, "ab c");
+    assert_eq!(record.len(), 2);
+
+    let mut record = Record::builder()
+        .merge_policy(MergePolicy::WithinDuration(std::time::Duration::ZERO))
+        .build();
+    record.edit(&mut target, Append::new("d"));
+    std::thread::sleep(std::time::Duration::from_millis(1));
+    record.edit(&mut target, Append::new("e"));
+    assert_eq!(record.len(), 2);
+}
+
+#[test]
+fn dropped() {
+    use std::sync::mpsc;
+    use undo::Event;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut target = String::new();
+    let mut record = Record::builder().limit(2).connect(sender).build();
+    record.edit(&mut target, A);
+    record.edit(&mut target, B);
+    assert!(!receiver.try_iter().any(|e| matches!(e, Event::Dropped(_))));
+    record.edit(&mut target, C);
+    assert_eq!(receiver.try_iter().last(), Some(Event::Dropped(1)));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn edit_async() {
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+    use undo::AsyncEdit;
+
+    struct Push(char);
+
+    impl AsyncEdit for Push {
+        type Target = String;
+        type Output = ();
+
+        async fn edit(&mut self, target: &mut String) {
+            target.push(self.0);
+        }
+
+        async fn undo(&mut self, target: &mut String) {
+            self.0 = target.pop().unwrap();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    let mut target = String::new();
+    let mut record = Record::new();
+    block_on(async {
+        record.edit_async(&mut target, Push('a')).await;
+        record.edit_async(&mut target, Push('b')).await;
+        assert_eq!(record.len(), 2);
+        record.undo_async(&mut target).await;
+        record.undo_async(&mut target).await;
+        assert!(record.undo_async(&mut target).await.is_none());
+        assert_eq!(target, "");
+        record.redo_async(&mut target).await;
+        assert_eq!(target, "a");
+
+        record.begin_group("bc");
+        record.edit_async(&mut target, Push('b')).await;
+        record.edit_async(&mut target, Push('c')).await;
+        record.end_group();
+        record.undo_async(&mut target).await;
+        assert_eq!(target, "a");
+        record.redo_async(&mut target).await;
+        assert_eq!(target, "abc");
+    });
+    assert!(!record.is_saved());
+}
+
+#[test]
+fn edit_variants() {
+    use undo::{EditRef, EditWithCtx};
+
+    // Pushes the text and merges with the previous edit if the flag is set.
+    struct Push(String, bool);
+
+    impl Push {
+        fn merge_if(&mut self, other: Self) -> Merged<Self> {
+            if other.1 {
+                self.0.push_str(&other.0);
+                Merged::Yes
+            } else {
+                Merged::No(other)
+            }
+        }
+    }
+
+    impl Edit for Push {
+        type Target = String;
+        type Output = ();
+
+        fn edit(&mut self, target: &mut String) {
+            target.push_str(&self.0);
+        }
+
+        fn undo(&mut self, target: &mut String) {
+            target.truncate(target.len() - self.0.len());
+        }
+
+        fn merge(&mut self, other: Self) -> Merged<Self> {
+            self.merge_if(other)
+        }
+    }
+
+    impl EditWithCtx for Push {
+        type Target = String;
+        type Context = ();
+        type Output = ();
+
+        fn edit(&mut self, target: &mut String, _: &mut ()) {
+            Edit::edit(self, target);
+        }
+
+        fn undo(&mut self, target: &mut String, _: &mut ()) {
+            Edit::undo(self, target);
+        }
+
+        fn merge(&mut self, other: Self) -> Merged<Self> {
+            self.merge_if(other)
+        }
+    }
+
+    impl EditRef for Push {
+        type Target = String;
+        type Output<'a> = &'a str;
+
+        fn edit<'a>(&mut self, target: &'a mut String) -> &'a str {
+            Edit::edit(self, target);
+            target
+        }
+
+        fn undo<'a>(&mut self, target: &'a mut String) -> &'a str {
+            Edit::undo(self, target);
+            target
+        }
+
+        fn merge(&mut self, other: Self) -> Merged<Self> {
+            self.merge_if(other)
+        }
+    }
+
+    // Runs the same edits with `edit`, `edit_with`, and `edit_ref`, and returns the states.
+    let run = |variant: usize| {
+        let mut target = String::new();
+        let mut record = Record::new();
+        let mut states = Vec::new();
+        let edit = |record: &mut Record<Push>, target: &mut String, edit: Push| match variant {
+            0 => record.edit(target, edit),
+            1 => record.edit_with(target, &mut (), edit),
+            _ => drop(record.edit_ref(target, edit)),
+        };
+        record.begin_group("ab");
+        edit(&mut record, &mut target, Push("a".into(), false));
+        edit(&mut record, &mut target, Push("b".into(), false));
+        record.end_group();
+        edit(&mut record, &mut target, Push("c".into(), false));
+        edit(&mut record, &mut target, Push("d".into(), true));
+        states.push((target.clone(), record.len(), record.head()));
+        for undo in [true, true, false, false] {
+            match (variant, undo) {
+                (0, true) => drop(record.undo(&mut target)),
+                (0, false) => drop(record.redo(&mut target)),
+                (1, true) => drop(record.undo_with(&mut target, &mut ())),
+                (1, false) => drop(record.redo_with(&mut target, &mut ())),
+                (_, true) => drop(record.undo_ref(&mut target)),
+                (_, false) => drop(record.redo_ref(&mut target)),
+            }
+            states.push((target.clone(), record.len(), record.head()));
+        }
+        states
+    };
+
+    let states = run(0);
+    let expected = ["abcd", "ab", "", "ab", "abcd"];
+    assert_eq!(
+        states.iter().map(|s| s.0.as_str()).collect::<Vec<_>>(),
+        expected
+    );
+    assert!(states.iter().all(|s| s.1 == 3));
+    assert_eq!(run(1), states);
+    assert_eq!(run(2), states);
+}
+
+#[test]
+fn differ() {
+    use undo::Differ;
+
+    // Small deterministic generator so the test covers many pairs of inputs.
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move |n: u64| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed % n
+    };
+    let chars = ['a', 'b', 'c', 'é', '😀'];
+    for _ in 0..500 {
+        let old: String = (0..next(12)).map(|_| chars[next(5) as usize]).collect();
+        let new: String = (0..next(12)).map(|_| chars[next(5) as usize]).collect();
+        let diff = String::diff(&old, &new);
+        let mut s = old.clone();
+        s.apply(&diff);
+        assert_eq!(s, new);
+        s.revert(&diff);
+        assert_eq!(s, old);
+
+        let old = old.into_bytes();
+        let new = new.into_bytes();
+        let diff = Vec::diff(&old, &new);
+        let mut v = old.clone();
+        v.apply(&diff);
+        assert_eq!(v, new);
+        v.revert(&diff);
+        assert_eq!(v, old);
+    }
+}
+
+#[test]
+fn set_limit() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.edit(&mut target, A);
+    record.set_saved();
+    record.edit(&mut target, B);
+    record.edit(&mut target, C);
+    record.save_as("c");
+    record.edit(&mut target, D);
+    record.undo(&mut target);
+    record.undo(&mut target);
+
+    // Only 'a' and 'b' are in front of the index, so 'd' is removed from the back.
+    let limit = NonZeroUsize::new(1).unwrap();
+    assert_eq!(record.set_limit(limit, Truncate::RejectIfSmaller), None);
+    assert_eq!(record.limit(), usize::MAX);
+    assert_eq!(record.set_limit(limit, Truncate::Front), Some(3));
+    assert_eq!(record.limit(), 1);
+    assert_eq!(record.len(), 1);
+    assert_eq!(record.head(), 0);
+    assert_eq!(record.saved(), None);
+    assert_eq!(record.savepoint("c"), Some(1));
+    assert!(!record.can_undo());
+
+    record.redo(&mut target);
+    assert_eq!(target, "abc");
+    assert!(!record.can_redo());
+    record.edit(&mut target, E);
+    assert_eq!(record.len(), 1);
+    assert_eq!(target, "abce");
+}
+
+#[test]
+fn edit_with_meta() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.edit_with_meta(&mut target, Append::new("a"), [("user", "alice")]);
+    record.edit_with_meta(&mut target, Append::new("b"), [("user", "alice")]);
+    record.edit_with_meta(&mut target, Append::new("c"), [("user", "bob")]);
+    record.edit(&mut target, Append::new("d"));
+    assert_eq!(target, "abcd");
+
+    // Only the entries with the same metadata are merged.
+    assert_eq!(record.len(), 3);
+    let users: Vec<_> = record.entries().map(|e| e.get_meta("user")).collect();
+    assert_eq!(users, [Some("alice"), Some("bob"), None]);
+    assert_eq!(record.get_entry(1).unwrap().meta().count(), 1);
+
+    record.undo(&mut target);
+    record.undo(&mut target);
+    assert_eq!(target, "ab");
+}
+
+#[test]
+fn groups() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.edit(&mut target, Append::new("a"));
+    record.begin_group("Add bcd");
+    record.edit(&mut target, Append::new("b"));
+    record.begin_group("Add c");
+    record.edit(&mut target, Append::new("c"));
+    assert!(record.end_group());
+    record.edit(&mut target, Append::new("d"));
+    assert!(record.end_group());
+    assert!(!record.end_group());
+    record.edit(&mut target, Append::new("e"));
+    assert_eq!(target, "abcde");
+
+    // The edits are only merged inside of the group.
+    assert_eq!(record.len(), 3);
+    assert_eq!(record.undo_string().as_deref(), Some("e"));
+    record.undo(&mut target);
+    assert_eq!(record.undo_string().as_deref(), Some("Add bcd"));
+    record.undo(&mut target);
+    assert_eq!(target, "a");
+    assert_eq!(record.redo_string().as_deref(), Some("Add bcd"));
+    record.redo(&mut target);
+    assert_eq!(target, "abcd");
+}
+
+#[test]
+fn split_off_and_append() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.edit(&mut target, A);
+    record.edit(&mut target, B);
+    record.save_as("b");
+    record.edit(&mut target, C);
+    record.set_saved();
+    record.edit(&mut target, D);
+    record.save_as("d");
+    record.undo(&mut target);
+
+    let mut tail = record.split_off(2);
+    assert_eq!((record.len(), record.head()), (2, 2));
+    assert_eq!((tail.len(), tail.head()), (2, 1));
+    assert_eq!(record.saved(), None);
+    assert_eq!(tail.saved(), Some(1));
+    assert_eq!(record.savepoint("b"), Some(2));
+    assert_eq!(tail.savepoint("d"), Some(2));
+    assert!(!record.can_redo());
+
+    record.append(&mut tail);
+    assert!(tail.is_empty());
+    assert_eq!(tail.saved(), None);
+    assert_eq!((record.len(), record.head()), (4, 3));
+    assert_eq!(record.saved(), Some(3));
+    assert_eq!(record.savepoint("d"), Some(4));
+    record.redo(&mut target);
+    assert_eq!(target, "abcd");
+
+    // The oldest entries are removed if the limit is reached.
+    let mut other = Record::builder().limit(3).build();
+    other.append(&mut record);
+    assert_eq!((other.len(), other.head()), (3, 3));
+    other.go_to(&mut target, 0);
+    assert_eq!(target, "a");
+}
+
+struct Paste(Vec<u8>);
+
+impl Edit for Paste {
+    type Target = Vec<u8>;
+    type Output = ();
+
+    fn edit(&mut self, target: &mut Vec<u8>) {
+        target.extend(&self.0);
+    }
+
+    fn undo(&mut self, target: &mut Vec<u8>) {
+        target.truncate(target.len() - self.0.len());
+    }
+}
+
+impl SizedEdit for Paste {
+    fn size_hint(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[test]
+fn memory_limit() {
+    let mut target = Vec::new();
+    let mut record = Record::builder().memory_limit(10).build();
+    record.edit(&mut target, Paste(vec![0; 4]));
+    record.save_as("small");
+    record.edit(&mut target, Paste(vec![1; 4]));
+    assert_eq!(record.len(), 2);
+    assert_eq!(record.memory_usage(), 8);
+
+    // The two oldest edits are removed to make room for the large edit.
+    record.edit(&mut target, Paste(vec![2; 2]));
+    record.edit(&mut target, Paste(vec![3; 8]));
+    assert_eq!(record.len(), 2);
+    assert_eq!(record.head(), 2);
+    assert_eq!(record.memory_usage(), 10);
+    assert_eq!(record.savepoint("small"), None);
+
+    // The most recent edit is always kept.
+    record.edit(&mut target, Paste(vec![4; 20]));
+    assert_eq!(record.len(), 1);
+    record.undo(&mut target);
+    assert_eq!(target.len(), 18);
+}
+
+#[test]
+fn protect_saved_set_limit() {
+    let mut target = String::new();
+    let mut record = Record::builder().protect_saved(true).build();
+    record.edit(&mut target, A);
+    record.set_saved();
+    record.edit(&mut target, B);
+    record.edit(&mut target, C);
+
+    // Only the entries before the saved state can be removed.
+    let limit = NonZeroUsize::new(1).unwrap();
+    assert_eq!(record.set_limit(limit, Truncate::Front), Some(1));
+    assert_eq!(record.len(), 2);
+    assert_eq!(record.saved(), Some(0));
+
+    // The entries that can be redone to reach the saved state are kept as well.
+    record.set_saved();
+    record.undo(&mut target);
+    record.undo(&mut target);
+    assert_eq!(record.set_limit(limit, Truncate::Front), Some(0));
+    assert_eq!(record.len(), 2);
+    assert_eq!(record.saved(), Some(2));
+    record.revert(&mut target);
+    assert_eq!(target, "abc");
+}
+
+#[test]
+fn set_limit_open_group() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.edit(&mut target, A);
+    record.edit(&mut target, B);
+    record.edit(&mut target, C);
+    record.begin_group("Add dg");
+    record.edit(&mut target, D);
+    record.edit(&mut target, E);
+    record.edit(&mut target, F);
+
+    let limit = NonZeroUsize::new(4).unwrap();
+    assert_eq!(record.set_limit(limit, Truncate::Front), Some(2));
+    record.go_to(&mut target, 2);
+    assert_eq!(target, "abcd");
+
+    // The edit is still joined with the start of the group.
+    record.edit(&mut target, Add('g'));
+    record.end_group();
+    record.undo(&mut target);
+    assert_eq!(target, "abc");
+}
+
+#[test]
+fn keep_discarded() {
+    let mut target = String::new();
+    let mut record = Record::builder().keep_discarded(true).build();
+    record.edit(&mut target, A);
+    record.edit(&mut target, B);
+    record.edit(&mut target, C);
+    record.set_saved();
+    record.undo(&mut target);
+    record.undo(&mut target);
+    record.edit(&mut target, D);
+    assert_eq!(target, "ad");
+    assert!(!record.is_saved());
+
+    let discarded: Vec<_> = record.discarded().map(|d| (d.index(), d.len())).collect();
+    assert_eq!(discarded, [(1, 2)]);
+
+    // The current entries are swapped with the discarded entries.
+    record.restore_discarded(&mut target, 0);
+    assert_eq!(target, "a");
+    let discarded: Vec<_> = record.discarded().map(|d| (d.index(), d.len())).collect();
+    assert_eq!(discarded, [(1, 1)]);
+    record.redo(&mut target);
+    record.redo(&mut target);
+    assert_eq!(target, "abc");
+    assert!(record.is_saved());
+
+    record.restore_discarded(&mut target, 0);
+    record.redo(&mut target);
+    assert_eq!(target, "ad");
+    assert_eq!(record.saved(), None);
+    assert!(record.restore_discarded(&mut target, 1).is_empty());
+
+    record.clear();
+    assert_eq!(record.discarded().count(), 0);
+}
+
+#[test]
+fn keep_discarded_memory_limit() {
+    let mut target = Vec::new();
+    let mut record = Record::builder()
+        .keep_discarded(true)
+        .memory_limit(4)
+        .build();
+    for i in 0..4 {
+        record.edit(&mut target, Paste(vec![i]));
+    }
+    record.undo(&mut target);
+    record.set_saved();
+    record.undo(&mut target);
+
+    // The edit discards the saved tail and drops the two oldest entries.
+    record.edit(&mut target, Paste(vec![4; 4]));
+    assert_eq!(record.len(), 1);
+    let discarded: Vec<_> = record.discarded().map(|d| (d.index(), d.len())).collect();
+    assert_eq!(discarded, [(0, 2)]);
+
+    record.restore_discarded(&mut target, 0);
+    assert_eq!(target, [0, 1]);
+    record.redo(&mut target);
+    assert_eq!(target, [0, 1, 2]);
+    assert!(record.is_saved());
+}
+
+#[cfg(feature = "crossbeam")]
+#[test]
+fn crossbeam_slot() {
+    use undo::Event;
+
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    let mut target = String::new();
+    let mut record = Record::builder().connect(sender).build();
+    record.edit(&mut target, A);
+    assert_eq!(receiver.try_recv(), Ok(Event::Undo(true)));
+    // Sending to a closed channel is ignored.
+    drop(receiver);
+    record.undo(&mut target);
+    assert_eq!(target, "");
+}
+
+#[test]
+fn drain() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.edit(&mut target, A);
+    record.edit(&mut target, B);
+    record.edit(&mut target, C);
+    record.edit(&mut target, D);
+    record.undo(&mut target);
+    record.undo(&mut target);
+    record.set_saved();
+
+    // The range can not contain both applied entries and entries that can be redone.
+    assert_eq!(record.drain(1..3).count(), 0);
+    assert_eq!(record.drain(..3).count(), 0);
+    assert_eq!(record.len(), 4);
+
+    let drained: Vec<_> = record.drain(3..).map(|e| *e.as_ref()).collect();
+    assert_eq!(drained, [D]);
+    let drained: Vec<_> = record.drain(..1).map(|e| *e.as_ref()).collect();
+    assert_eq!(drained, [A]);
+    assert_eq!(record.head(), 1);
+    assert!(record.is_saved());
+
+    record.redo(&mut target);
+    assert_eq!(target, "abc");
+    assert!(!record.can_redo());
+    record.undo(&mut target);
+    record.undo(&mut target);
+    assert_eq!(target, "a");
+    assert!(!record.can_undo());
+}
+
+struct Step(i32);
+
+impl Edit for Step {
+    type Target = i32;
+    type Output = ();
+
+    fn edit(&mut self, target: &mut i32) {
+        *target += self.0;
+    }
+
+    fn undo(&mut self, target: &mut i32) {
+        *target -= self.0;
+    }
+
+    fn merge(&mut self, other: Self) -> Merged<Self> {
+        if self.0 + other.0 == 0 {
+            Merged::Annul
+        } else {
+            Merged::No(other)
+        }
+    }
+}
+
+#[test]
+fn cascade_annul() {
+    for cascade in [false, true] {
+        let mut target = 0;
+        let mut record = Record::builder().cascade_annul(cascade).build();
+        record.edit(&mut target, Step(2));
+        record.edit(&mut target, Step(1));
+        // The saved state stops the edits from annulling when they are pushed.
+        record.set_saved();
+        record.edit(&mut target, Step(-1));
+        record.edit(&mut target, Step(-2));
+        assert_eq!(record.len(), 4);
+        record.clear_saved();
+
+        record.edit(&mut target, Step(2));
+        assert_eq!(target, 2);
+        assert_eq!(record.len(), if cascade { 1 } else { 3 });
+        assert_eq!(record.head(), record.len());
+    }
+}
+
+#[cfg(feature = "text")]
+#[test]
+fn text_edit() {
+    use undo::TextEdit;
+
+    let mut target = String::from("hello world");
+    let mut record = Record::new();
+    // Backspace twice, then delete forward once.
+    record.edit(&mut target, TextEdit::delete(4..5));
+    record.edit(&mut target, TextEdit::delete(3..4));
+    record.edit(&mut target, TextEdit::delete(3..4));
+    assert_eq!(target, "helworld");
+    assert_eq!(record.len(), 1);
+    assert_eq!(record.undo_string().as_deref(), Some("Delete \"lo \""));
+
+    record.edit(&mut target, TextEdit::replace(0..3, "HEL"));
+    assert_eq!(target, "HELworld");
+    assert_eq!(record.len(), 2);
+    record.undo(&mut target);
+    record.undo(&mut target);
+    assert_eq!(target, "hello world");
+}
+
+#[test]
+fn seq() {
+    let mut target = String::new();
+    let mut record = Record::builder().limit(2).build();
+    record.edit(&mut target, A);
+    record.edit(&mut target, B);
+    record.undo(&mut target);
+    record.edit(&mut target, C);
+    record.edit(&mut target, D);
+    assert_eq!(target, "acd");
+    assert_eq!(record.find_seq(3), Some(0));
+    assert_eq!(record.find_seq(4), Some(1));
+    assert_eq!(record.find_seq(1), None);
+
+    let display = record.display().detailed(false).to_string();
+    let numbers: Vec<_> = display
+        .lines()
+        .map(|l| l.split(' ').next().unwrap())
+        .collect();
+    assert_eq!(numbers, ["4", "3", "0"]);
+}
+
+#[test]
+fn snapshot_on_save() {
+    use std::sync::mpsc;
+    use undo::Event;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut target = String::new();
+    let mut record = Record::builder()
+        .limit(2)
+        .snapshot_on_save(true)
+        .connect(sender)
+        .build();
+    record.edit(&mut target, A);
+    record.set_saved_with(&target);
+    record.edit(&mut target, B);
+    record.edit(&mut target, C);
+    record.edit(&mut target, D);
+    assert_eq!(record.saved(), None);
+    receiver.try_iter().for_each(drop);
+
+    assert!(record.revert(&mut target).is_empty());
+    assert_eq!(target, "a");
+    assert!(record.is_empty());
+    assert!(record.is_saved());
+    assert!(receiver.try_iter().any(|e| e == Event::SnapshotRestored));
+
+    // The snapshot is cleared when the target is saved without one.
+    record.edit(&mut target, E);
+    record.set_saved();
+    record.edit(&mut target, F);
+    record.clear_saved();
+    record.revert(&mut target);
+    assert_eq!(target, "aef");
+}
+
+#[test]
+fn adopt() {
+    use std::sync::mpsc;
+    use undo::Event;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut target = String::new();
+    let mut record = Record::builder().connect(sender).build();
+    record.edit(&mut target, A);
+    record.set_saved();
+    receiver.try_iter().for_each(drop);
+
+    assert_eq!(record.adopt(&mut target, [B, C]).len(), 2);
+    assert_eq!(target, "abc");
+    assert_eq!(record.len(), 1);
+    assert_eq!(record.head(), 1);
+    assert_eq!(
+        receiver.try_iter().collect::<Vec<_>>(),
+        [Event::Saved(false), Event::Adopted(2)]
+    );
+
+    assert!(record.adopt(&mut target, []).is_empty());
+    assert_eq!(receiver.try_iter().count(), 0);
+}
+
+#[test]
+fn checkpoint_commit_as_single() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.edit(&mut target, A);
+    record.edit(&mut target, B);
+
+    let mut checkpoint = record.checkpoint();
+    checkpoint.undo(&mut target);
+    checkpoint.edit(&mut target, C);
+    checkpoint.edit(&mut target, D);
+    checkpoint.commit_as_single("Add cd");
+    assert_eq!(target, "acd");
+    assert_eq!(record.undo_string().as_deref(), Some("Add cd"));
+
+    record.undo(&mut target);
+    assert_eq!(target, "a");
+    record.redo(&mut target);
+    assert_eq!(target, "acd");
+
+    // Nothing is joined if no entries were applied.
+    let mut checkpoint = record.checkpoint();
+    checkpoint.undo(&mut target);
+    checkpoint.commit_as_single("Nothing");
+    assert_eq!(record.redo_string().as_deref(), Some("Add cd"));
+}
+
+#[test]
+fn checkpoint_commit_as_single_limit() {
+    let mut target = String::new();
+    let mut record = Record::builder().limit(2).build();
+    record.edit(&mut target, A);
+
+    // The edits drop the entry before the checkpoint from the record.
+    let mut checkpoint = record.checkpoint();
+    checkpoint.edit(&mut target, B);
+    checkpoint.edit(&mut target, C);
+    checkpoint.commit_as_single("Add bc");
+    assert_eq!(target, "abc");
+    assert_eq!(record.len(), 2);
+    assert_eq!(record.undo_string().as_deref(), Some("Add bc"));
+
+    record.undo(&mut target);
+    assert_eq!(target, "a");
+    assert!(!record.can_undo());
+}
+
+#[test]
+fn timestamps() {
+    use std::time::{Duration, UNIX_EPOCH};
+    use undo::TimestampMode;
+
+    let mut target = String::new();
+    let mut record = Record::builder()
+        .timestamps(TimestampMode::Monotonic)
+        .build();
+    record.edit(&mut target, A);
+    record.edit(&mut target, B);
+    let a = record.get_entry(0).unwrap().time_of_edit();
+    let b = record.get_entry(1).unwrap().time_of_edit();
+    assert!(a > Duration::ZERO && a <= b);
+
+    let st = UNIX_EPOCH + Duration::from_secs(100);
+    record.get_entry_mut(0).unwrap().set_st_of_edit(st);
+    assert_eq!(record.get_entry(0).unwrap().st_of_edit(), st);
+}
+
+#[test]
+fn merge_with_meta() {
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use core::time::Duration;
+    use undo::{Clock, EntryMeta};
+
+    struct Manual(AtomicU64);
+
+    impl Clock for Manual {
+        fn now(&self) -> Duration {
+            Duration::from_millis(self.0.load(Ordering::Relaxed))
+        }
+    }
+
+    static CLOCK: Manual = Manual(AtomicU64::new(0));
+
+    struct Type(String);
+
+    impl Edit for Type {
+        type Target = String;
+        type Output = ();
+
+        fn edit(&mut self, target: &mut String) {
+            target.push_str(&self.0);
+        }
+
+        fn undo(&mut self, target: &mut String) {
+            target.truncate(target.len() - self.0.len());
+        }
+
+        fn merge_with_meta(&mut self, other: Self, a: &EntryMeta, b: &EntryMeta) -> Merged<Self> {
+            if b.time_of_edit() - a.time_of_edit() < Duration::from_millis(500) {
+                self.0.push_str(&other.0);
+                Merged::Yes
+            } else {
+                Merged::No(other)
+            }
+        }
+    }
+
+    let mut target = String::new();
+    let mut record = Record::builder().clock(&CLOCK).build();
+    record.edit(&mut target, Type("a".into()));
+    CLOCK.0.store(300, Ordering::Relaxed);
+    record.edit(&mut target, Type("b".into()));
+    // The time of the merged entry is the time of the last edit.
+    CLOCK.0.store(700, Ordering::Relaxed);
+    record.edit(&mut target, Type("c".into()));
+    CLOCK.0.store(1300, Ordering::Relaxed);
+    record.edit(&mut target, Type("d".into()));
+    assert_eq!(target, "abcd");
+    assert_eq!(record.len(), 2);
+    record.undo(&mut target);
+    assert_eq!(target, "abc");
+}
+
+#[test]
+fn scope() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.edit(&mut target, Add('a'));
+
+    let mut scope = record.scope(&mut target);
+    scope.edit(Add('b'));
+    scope.edit(Add('c'));
+    assert_eq!(scope.cancel().len(), 2);
+    assert_eq!(target, "a");
+    assert_eq!(record.len(), 1);
+
+    // The edits that can be redone in the scope are not moved into the record.
+    let mut scope = record.scope(&mut target);
+    scope.edit(Add('b'));
+    scope.edit(Add('c'));
+    scope.undo();
+    assert!(scope.record().can_redo());
+    scope.commit("Add b");
+    assert_eq!(target, "ab");
+    assert_eq!(record.len(), 2);
+    assert_eq!(record.undo_string().as_deref(), Some("Add b"));
+
+    // Nothing is added if no edits are applied.
+    record.scope(&mut target).commit("Nothing");
+    assert_eq!(record.len(), 2);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn absolute_time() {
+    let mut target = String::new();
+    let mut record = Record::new();
+    record.edit(&mut target, Add('a'));
+    let s = record.display().absolute_time(true).to_string();
+    let time = s.split_whitespace().nth(1).unwrap();
+    assert_eq!(time.len(), 8);
+    assert_eq!(time.matches(':').count(), 2);
+}
+
+#[test]
+fn move_edit() {
+    use undo::record::MoveError;
+
+    #[derive(Debug)]
+    enum Op {
+        Add(i32),
+        Mul(i32),
+    }
+
+    impl Edit for Op {
+        type Target = i32;
+        type Output = ();
+
+        fn edit(&mut self, target: &mut i32) {
+            match *self {
+                Op::Add(n) => *target += n,
+                Op::Mul(n) => *target *= n,
+            }
+        }
+
+        fn undo(&mut self, target: &mut i32) {
+            match *self {
+                Op::Add(n) => *target -= n,
+                Op::Mul(n) => *target /= n,
+            }
+        }
+
+        fn commute(&self, other: &Op) -> bool {
+            matches!(
+                (self, other),
+                (Op::Add(_), Op::Add(_)) | (Op::Mul(_), Op::Mul(_))
+            )
+        }
+    }
+
+    let mut target = 0;
+    let mut record = Record::new();
+    record.edit(&mut target, Op::Add(1));
+    record.edit(&mut target, Op::Add(2));
+    record.edit(&mut target, Op::Mul(3));
+    record.edit(&mut target, Op::Add(4));
+    record.go_to(&mut target, 1);
+    record.set_saved();
+    record.go_to(&mut target, 0);
+
+    assert_eq!(record.move_edit(3, 1), Err(MoveError::Conflict(2)));
+    assert_eq!(record.swap(0, 2), Err(MoveError::Conflict(2)));
+    assert_eq!(record.saved(), Some(1));
+    // The saved state is between the swapped entries, so it no longer exists.
+    record.swap(0, 1).unwrap();
+    assert_eq!(record.saved(), None);
+    record.move_edit(1, 0).unwrap();
+    record.redo_all(&mut target);
+    assert_eq!(target, 13);
+
+    record.undo(&mut target);
+    assert_eq!(record.move_edit(3, 2), Err(MoveError::OutOfBounds));
+    record.undo(&mut target);
+    assert_eq!(record.swap(2, 3), Err(MoveError::Conflict(3)));
+}
+
+#[test]
+fn edit_many() {
+    use std::sync::mpsc;
+    use undo::{BatchPolicy, Event};
+
+    struct Move(i32);
+
+    impl Edit for Move {
+        type Target = i32;
+        type Output = ();
+
+        fn edit(&mut self, target: &mut i32) {
+            *target += self.0;
+        }
+
+        fn undo(&mut self, target: &mut i32) {
+            *target -= self.0;
+        }
+
+        fn merge(&mut self, other: Self) -> Merged<Self> {
+            self.0 += other.0;
+            if self.0 == 0 {
+                Merged::Annul
+            } else {
+                Merged::Yes
+            }
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let mut target = 0;
+    let mut record = Record::builder()
+        .merge_policy(MergePolicy::Never)
+        .connect(sender)
+        .build();
+    let edits = [Move(1), Move(2), Move(3)];
+    record.edit_many(&mut target, edits, BatchPolicy::Continue);
+    assert_eq!(record.len(), 3);
+    // The events are emitted as a single batch.
+    assert!(matches!(receiver.try_iter().next(), Some(Event::Batch(_))));
+    assert_eq!(receiver.try_iter().count(), 0);
+
+    // The merge policy is ignored.
+    record.edit_many(&mut target, [Move(4)], BatchPolicy::MergeAggressively);
+    assert_eq!(record.len(), 3);
+    assert_eq!(target, 10);
+    record.edit(&mut target, Move(1));
+    assert_eq!(record.len(), 4);
+
+    let mut target = 0;
+    let mut record = Record::new();
+    let edits = [Move(2), Move(-2), Move(4)];
+    let outputs = record.edit_many(&mut target, edits, BatchPolicy::StopOnAnnul);
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(target, 0);
+    assert!(record.is_empty());
+}
+
+#[test]
+fn barriers() {
+    let mut target = String::new();
+    let mut record = Record::builder().merge_policy(MergePolicy::Always).build();
+    assert!(!record.push_barrier("empty"));
+    record.edit(&mut target, A);
+    assert!(record.push_barrier("sent"));
+    // The entry before the barrier is not merged.
+    record.edit(&mut target, B);
+    assert_eq!(record.len(), 2);
+    assert_eq!(record.entries().next().unwrap().barrier(), Some("sent"));
+
+    record.undo(&mut target);
+    assert!(!record.can_undo());
+    assert_eq!(record.undo(&mut target), None);
+    assert_eq!(target, "a");
+
+    // Going to a position is not stopped by barriers.
+    record.go_to(&mut target, 0);
+    assert_eq!(target, "");
+    record.go_to(&mut target, 1);
+
+    let mut record = Record::builder().undo_through_barriers(true).build();
+    let mut target = String::new();
+    record.edit(&mut target, A);
+    record.push_barrier("sent");
+    assert!(record.can_undo());
+    record.undo(&mut target);
+    assert_eq!(target, "");
+}
+
+#[test]
+fn merge_events() {
+    use std::sync::mpsc;
+    use undo::Event;
+
+    struct Move(i32);
+
+    impl Edit for Move {
+        type Target = i32;
+        type Output = ();
+
+        fn edit(&mut self, target: &mut i32) {
+            *target += self.0;
+        }
+
+        fn undo(&mut self, target: &mut i32) {
+            *target -= self.0;
+        }
+
+        fn merge(&mut self, other: Self) -> Merged<Self> {
+            self.0 += other.0;
+            if self.0 == 0 {
+                Merged::Annul
+            } else {
+                Merged::Yes
+            }
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let mut target = 0;
+    let mut record = Record::builder().connect(sender).build();
+    record.edit(&mut target, Move(1));
+    record.edit(&mut target, Move(2));
+    assert_eq!(
+        receiver
+            .try_iter()
+            .filter(|e| *e == Event::Merged(1))
+            .count(),
+        1
+    );
+    record.edit(&mut target, Move(-3));
+    assert_eq!(receiver.try_iter().next(), Some(Event::Annulled(1)));
+    assert!(record.is_empty());
+}